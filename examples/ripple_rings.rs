@@ -8,7 +8,7 @@
 //! - `KeyPath::TransformScale` for ring expansion
 //! - `KeyPath::Opacity` for rings fading as they expand
 //! - `KeyPath::Custom("lineWidth")` for pulsing stroke width
-//! - `phase_offset` for staggered timing across multiple rings
+//! - `Stagger::phase_offsets` for staggered timing across multiple rings
 //! - Multiple animations per layer
 //! - `Easing::Out` for realistic ripple physics (fast start, slow end)
 //!
@@ -111,12 +111,12 @@ fn main() {
     let max_ring_size = 400.0; // Maximum expansion size
     let initial_ring_size = 30.0; // Starting size (small, near center)
 
-    // Create expanding ripple rings
-    for i in 0..num_rings {
-        // Phase offset creates the staggered emanation effect
-        // Each ring starts at a different point in the cycle
-        let phase = i as f64 / num_rings as f64;
+    // Phase offsets create the staggered emanation effect:
+    // each ring starts at a different point in the cycle
+    let phases = Stagger::new().phase_offsets(num_rings);
 
+    // Create expanding ripple rings
+    for (i, &phase) in phases.iter().enumerate() {
         // Alternate between cyan and blue for visual depth
         let ring_color = if i % 2 == 0 { water_cyan } else { water_blue };
 
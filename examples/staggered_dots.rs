@@ -54,14 +54,14 @@ fn main() {
         )
     };
 
+    // Phase offsets create the wave effect:
+    // each dot is offset by 1/num_dots of the cycle
+    let phases = Stagger::new().phase_offsets(num_dots);
+
     // Create each dot with staggered phase offset
-    for (i, &color) in colors.iter().enumerate().take(num_dots) {
+    for (i, (&color, &phase)) in colors.iter().zip(&phases).enumerate() {
         let x = start_x + (i as f64 * dot_spacing);
 
-        // Phase offset creates the wave effect
-        // Each dot is offset by 1/num_dots of the cycle
-        let phase = i as f64 / num_dots as f64;
-
         let dot = CAShapeLayerBuilder::new()
             .path(dot_path.clone())
             .fill_color(color)
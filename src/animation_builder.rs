@@ -33,11 +33,26 @@
 //! | `.values(from, to)` | Numeric from/to values (f64) |
 //! | `.values_point(from, to)` | CGPoint from/to |
 //! | `.values_color(from, to)` | Color from/to |
+//! | `.values_path(from, to)` | CGPath from/to, normalized for morphing |
+//! | `.by(delta)` | Relative change from the current value |
+//! | `.from_current_to(to)` | From the current value to `to` |
+//! | `.additive()` | Add to the layer's value, stacking with others |
+//! | `.cumulative()` | Repeats continue where the last one ended |
 //! | `.duration(Duration)` | Animation cycle duration |
 //! | `.easing(Easing)` | Timing curve (default: `InOut`) |
 //! | `.autoreverses()` | Ping-pong animation |
 //! | `.repeat(Repeat)` | Repeat behavior (default: `Once`) |
 //! | `.phase_offset(f64)` | Start at fraction of cycle (0.0-1.0) |
+//! | `.delay(Duration)` | Wait before starting |
+//! | `.speed(f32)` | Playback rate (default: 1.0) |
+//! | `.hold_at_end(Duration)` | Pause at `to` before reversing |
+//! | `.hold_at_start(Duration)` | Pause at `from` at the start of each cycle |
+//! | `.fill_mode(FillMode)` | What shows before/after (default: `Forwards`) |
+//! | `.on_start(\|\| ..)` | Closure run when the animation starts |
+//! | `.on_complete(\|finished\| ..)` | Closure run when the animation stops |
+//! | `.evaluator()` | Sample values in Rust (see below) |
+//! | `.try_build()` | Build, or an [`AnimationError`] if invalid |
+//! | `.build_animation()` | Build, as keyframes if holds are set |
 //!
 //! ## `KeyPath`
 //!
@@ -56,6 +71,7 @@
 //! | `BackgroundColor` | Fill color (CGColor) |
 //! | `CornerRadius` | Corner rounding |
 //! | `BorderWidth` | Border thickness |
+//! | `Path` | Shape layer path (CGPath) |
 //!
 //! ## `Easing`
 //!
@@ -76,6 +92,8 @@
 //! |---------|----------|
 //! | `Once` | Play once, hold final value (default) |
 //! | `Times(n)` | Play n times, hold final value |
+//! | `Count(2.5)` | Play a fractional number of times |
+//! | `For(Duration)` | Repeat until the total time has passed |
 //! | `Forever` | Loop indefinitely |
 //!
 //! # Default Behaviors
@@ -91,11 +109,211 @@
 //!     .remove_on_completion()  // Snap back to original value
 //! ```
 //!
+//! For entry animations that wait, pick a [`FillMode`] that also covers
+//! the delay. Delays are relative; add delayed animations built outside a
+//! layer builder with [`CALayerExt::add_animation`](crate::CALayerExt::add_animation),
+//! which starts them on the layer's own clock:
+//!
+//! ```ignore
+//! let fade = CABasicAnimationBuilder::new(KeyPath::Opacity)
+//!     .values(0.0, 1.0)
+//!     .delay(300.millis())
+//!     .fill_mode(FillMode::Both)  // Hold the first frame until it starts
+//!     .build();
+//! layer.add_animation(&fade, "fade");
+//! ```
+//!
+//! # Completion Callbacks
+//!
+//! `.on_start()` and `.on_complete()` run closures on the main thread when
+//! the animation starts and stops, so sequences don't have to guess timings:
+//!
+//! ```ignore
+//! .animate("intro", KeyPath::Opacity, |a| {
+//!     a.values(0.0, 1.0)
+//!         .duration(300.millis())
+//!         .on_complete(|finished| println!("intro finished: {finished}"))
+//! })
+//! ```
+//!
+//! # Validation
+//!
+//! `.build()` panics on configurations Core Animation would silently
+//! accept, such as a zero duration or `Repeat::Times(0)`. For values that
+//! come from config files, `.try_build()` returns an [`AnimationError`]
+//! instead, and `.validate()` checks without creating the animation:
+//!
+//! ```ignore
+//! let fade = CABasicAnimationBuilder::new(KeyPath::Opacity)
+//!     .values(0.0, 1.0)
+//!     .duration(settings.fade);
+//! assert_eq!(fade.validate(), Ok(()));
+//! ```
+//!
+//! # Holds
+//!
+//! `.hold_at_end()` and `.hold_at_start()` pause an autoreversing animation
+//! at either end of its cycle. `CABasicAnimation` cannot pause, so the
+//! builder turns into a keyframe animation with the same timing; the layer
+//! builders' `.animate()` does this automatically:
+//!
+//! ```ignore
+//! .animate("breathe", KeyPath::TransformScale, |a| {
+//!     a.values(1.0, 1.2)
+//!         .duration(400.millis())
+//!         .autoreverses()
+//!         .hold_at_end(300.millis())  // Stay big for a moment
+//!         .repeat(Repeat::Forever)
+//! })
+//! ```
+//!
+//! # Path Morphing
+//!
+//! Core Animation snaps between paths whose elements differ in number or
+//! kind. `.values_path()` normalizes both shapes first (see
+//! [`normalize_for_morph`]), so any two
+//! shapes morph smoothly:
+//!
+//! ```ignore
+//! CAShapeLayerBuilder::new()
+//!     .path(play.clone())
+//!     .animate("morph", KeyPath::Path, |a| {
+//!         a.values_path(&play, &pause)
+//!             .duration(300.millis())
+//!             .autoreverses()
+//!             .repeat(Repeat::Forever)
+//!     })
+//!     .build();
+//! ```
+//!
+//! # Motion Paths
+//!
+//! [`MotionPathBuilder`] moves a layer's `position` along any path, with
+//! optional auto-rotation and start/end offsets measured by arc length:
+//!
+//! ```ignore
+//! CAShapeLayerBuilder::new()
+//!     .circle(12.0)
+//!     .animate_motion("orbit", |m| {
+//!         m.path(&orbit)
+//!             .rotation(RotationMode::Auto)
+//!             .start_offset(0.25)
+//!             .duration(2.seconds())
+//!             .easing(Easing::Linear)
+//!             .repeat(Repeat::Forever)
+//!     })
+//!     .build();
+//! ```
+//!
+//! # Decay
+//!
+//! [`DecayAnimationBuilder`] lets a value coast to a stop from an initial
+//! velocity, e.g. after a fling. The resting value and duration are computed
+//! up front, with optional bounds and a rubber-band overshoot:
+//!
+//! ```ignore
+//! let anim = DecayAnimationBuilder::new(KeyPath::PositionX)
+//!     .from(toast_x)
+//!     .velocity(fling_velocity)
+//!     .deceleration(DecelerationRate::Fast)
+//!     .bounds(0.0, 600.0)
+//!     .rubber_band()
+//!     .build();
+//! ```
+//!
+//! # Evaluating Without a Display
+//!
+//! [`AnimationEvaluator`] computes the value an animation shows at any time,
+//! following Core Animation's timing rules. Use it to test mid-animation
+//! states or to drive previews:
+//!
+//! ```ignore
+//! let eval = CABasicAnimationBuilder::new(KeyPath::TransformScale)
+//!     .values(1.0, 2.0)
+//!     .duration(1.seconds())
+//!     .autoreverses()
+//!     .evaluator();
+//!
+//! assert_eq!(eval.value_at(1.seconds()), Some(AnimationValue::Scalar(2.0)));
+//! ```
+//!
+//! [`KeyframeEvaluator`] does the same for keyframe animations, in every
+//! [`CalculationMode`], including the spline curves of `Cubic` and
+//! `CubicPaced`.
+//!
+//! # Keyframes and Groups
+//!
+//! [`CAKeyframeAnimationBuilder`] animates through a list of values, and
+//! [`CAAnimationGroupBuilder`] plays several animations as one:
+//!
+//! ```ignore
+//! let anim = CAAnimationGroupBuilder::new()
+//!     .keyframes(KeyPath::TransformScale, |k| {
+//!         k.values([1.0, 1.3, 1.0]).duration(600.millis())
+//!     })
+//!     .basic(KeyPath::Opacity, |a| a.values(0.5, 1.0).duration(600.millis()))
+//!     .duration(600.millis())
+//!     .build();
+//! ```
+//!
+//! Motion that is easiest to write as a formula can be sampled into
+//! keyframes with `.keyframes_fn()`, which adds samples where the curve
+//! bends until straight lines between them follow it closely. The function
+//! may return a number, a `CGPoint` or a [`Color`](crate::Color):
+//!
+//! ```ignore
+//! CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+//!     .keyframes_fn(1.seconds(), |t| (t * TAU).sin() * 20.0)
+//!     .repeat(Repeat::Forever)
+//! ```
+//!
+//! `.wiggle()` and `.wiggle_point()` sample seeded [`Noise`](crate::noise::Noise)
+//! the same way, into an additive loop that drifts around the layer's value:
+//!
+//! ```ignore
+//! CAKeyframeAnimationBuilder::new(KeyPath::TransformRotation)
+//!     .wiggle(1.5, 0.05, seed)
+//!     .build();
+//! ```
+//!
+//! # Transitions
+//!
+//! [`TransitionBuilder`] animates the next content change of a layer, such
+//! as new text or swapped sublayers, with a fade, push, move-in or reveal.
+//! [`CALayerExt::transition`](crate::CALayerExt::transition) adds one right
+//! before the change:
+//!
+//! ```ignore
+//! label.transition(TransitionKind::Fade);
+//! label.setString(Some(&NSString::from_str("Saved")));
+//!
+//! page.transition(
+//!     TransitionBuilder::new(TransitionKind::Push)
+//!         .direction(TransitionDirection::FromRight)
+//!         .duration(350.millis()),
+//! );
+//! page.setContents(Some(&next_image));
+//! ```
+//!
+//! # Presets
+//!
+//! Common effects (shake, wiggle, heartbeat, bounce-in, pop, fade-in-up,
+//! flip, jello, tada) are ready-made in [`presets`]:
+//!
+//! ```ignore
+//! CAShapeLayerBuilder::new()
+//!     .animate_preset("intro", Preset::BounceIn)
+//!     .animate_preset("nudge", Preset::Shake.intensity(0.5).delay(2.seconds()))
+//!     .build();
+//! ```
+//!
 //! # Integration with Layer Builders
 //!
-//! The `.animate()` method is available on:
+//! The `.animate()`, `.animate_motion()` and `.animate_preset()` methods
+//! are available on:
 //! - `CALayerBuilder`
 //! - `CAShapeLayerBuilder`
+//! - `CATextLayerBuilder`
 //!
 //! Multiple animations can be added to a single layer:
 //!
@@ -148,16 +366,52 @@
 //! The user-facing API pattern remains consistent; only the builder inside
 //! the closure changes.
 
+use std::hash::{Hash, Hasher};
 use std::time::Duration;
 
 use objc2::rc::Retained;
-use objc2_foundation::{NSNumber, NSString};
+use objc2::runtime::AnyObject;
+use objc2_core_foundation::{CFRetained, CGPoint};
+use objc2_core_graphics::{CGColor, CGPath};
+use objc2_foundation::{NSNumber, NSString, NSValue};
 use objc2_quartz_core::{
-    kCAFillModeForwards, kCAMediaTimingFunctionEaseIn, kCAMediaTimingFunctionEaseInEaseOut,
-    kCAMediaTimingFunctionEaseOut, kCAMediaTimingFunctionLinear, CABasicAnimation, CAMediaTiming,
+    kCAAnimationCubic, kCAAnimationCubicPaced, kCAAnimationDiscrete, kCAAnimationLinear,
+    kCAAnimationPaced, kCAFillModeBackwards, kCAFillModeBoth, kCAFillModeForwards,
+    kCAFillModeRemoved, kCAMediaTimingFunctionEaseIn, kCAMediaTimingFunctionEaseInEaseOut,
+    kCAMediaTimingFunctionEaseOut, kCAMediaTimingFunctionLinear, CAAnimation,
+    CAAnimationCalculationMode, CABasicAnimation, CAMediaTiming, CAMediaTimingFillMode,
     CAMediaTimingFunction,
 };
 
+use crate::color::Color;
+use crate::path::{normalize_for_morph, BezierPath};
+use crate::settings::{slow_down, AnimationSettings, MotionPolicy};
+
+mod decay;
+mod delegate;
+mod evaluator;
+mod group;
+mod hold;
+mod keyframe;
+mod motion_path;
+pub mod presets;
+mod reduced;
+mod sampled;
+mod transition;
+mod validate;
+mod wiggle;
+
+use delegate::Callbacks;
+
+pub use decay::{DecayAnimationBuilder, DecelerationRate};
+pub use evaluator::{AnimationEvaluator, KeyframeEvaluator};
+pub use group::CAAnimationGroupBuilder;
+pub use keyframe::CAKeyframeAnimationBuilder;
+pub use motion_path::{MotionPathBuilder, RotationMode};
+pub use sampled::SampleValue;
+pub use transition::{TransitionBuilder, TransitionDirection, TransitionKind};
+pub use validate::AnimationError;
+
 /// Property key path for animation targets.
 ///
 /// Each variant maps to a Core Animation key path string that identifies
@@ -218,6 +472,9 @@ pub enum KeyPath {
     /// Bounds rectangle (`bounds`).
     /// Value type: CGRect.
     Bounds,
+    /// Shape layer path (`path`).
+    /// Value type: CGPath.
+    Path,
     /// Custom key path string.
     Custom(&'static str),
 }
@@ -250,6 +507,7 @@ impl KeyPath {
             KeyPath::ShadowRadius => "shadowRadius",
             KeyPath::ShadowOffset => "shadowOffset",
             KeyPath::Bounds => "bounds",
+            KeyPath::Path => "path",
             KeyPath::Custom(s) => s,
         }
     }
@@ -262,6 +520,71 @@ impl KeyPath {
     }
 }
 
+/// A from/to value of an animation.
+///
+/// Set through the typed builder methods such as
+/// [`values`](CABasicAnimationBuilder::values) or
+/// [`values_path`](CABasicAnimationBuilder::values_path).
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationValue {
+    /// A number (scale, opacity, radians, points).
+    Scalar(f64),
+    /// A path, already normalized for morphing.
+    Path(BezierPath),
+    /// A point, such as a `position`.
+    Point(CGPoint),
+    /// A color, such as a `backgroundColor`.
+    Color(Color),
+}
+
+impl AnimationValue {
+    /// Creates the Objective-C object Core Animation expects for this value.
+    fn to_object(&self) -> Retained<AnyObject> {
+        match self {
+            AnimationValue::Scalar(v) => {
+                let number = NSNumber::new_f64(*v);
+                Retained::into_super(Retained::into_super(Retained::into_super(number)))
+            }
+            AnimationValue::Path(path) => {
+                let path = CFRetained::into_raw(path.to_cgpath());
+                // SAFETY: CGPath is a CF type, and CF types are valid
+                // Objective-C objects. Ownership moves to the `Retained`.
+                unsafe { Retained::from_raw(path.as_ptr().cast::<AnyObject>()) }
+                    .expect("CGPath pointer is non-null")
+            }
+            AnimationValue::Point(point) => {
+                let value = NSValue::valueWithPoint(*point);
+                Retained::into_super(Retained::into_super(value))
+            }
+            AnimationValue::Color(color) => {
+                let color = CFRetained::into_raw(CFRetained::<CGColor>::from(color));
+                // SAFETY: CGColor is a CF type, and CF types are valid
+                // Objective-C objects. Ownership moves to the `Retained`.
+                unsafe { Retained::from_raw(color.as_ptr().cast::<AnyObject>()) }
+                    .expect("CGColor pointer is non-null")
+            }
+        }
+    }
+}
+
+impl From<f64> for AnimationValue {
+    fn from(value: f64) -> Self {
+        AnimationValue::Scalar(value)
+    }
+}
+
+impl From<CGPoint> for AnimationValue {
+    fn from(point: CGPoint) -> Self {
+        AnimationValue::Point(point)
+    }
+}
+
+impl From<Color> for AnimationValue {
+    fn from(color: Color) -> Self {
+        AnimationValue::Color(color)
+    }
+}
+
 /// Timing curve for animation interpolation.
 ///
 /// Controls how the animation progresses over time. The default is `InOut`
@@ -275,8 +598,11 @@ impl KeyPath {
 ///
 /// // Smooth start and end (default)
 /// builder.easing(Easing::InOut)
+///
+/// // Custom curve, as in CSS `cubic-bezier(0.2, 0, 0, 1)`
+/// builder.easing(Easing::CubicBezier(0.2, 0.0, 0.0, 1.0))
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Easing {
     /// Constant speed throughout the animation.
     Linear,
@@ -287,11 +613,16 @@ pub enum Easing {
     /// Slow start and end with acceleration in the middle (default).
     #[default]
     InOut,
+    /// Custom cubic Bézier curve with control points `(x1, y1, x2, y2)`.
+    ///
+    /// The curve runs from (0, 0) to (1, 1). The x coordinates must lie
+    /// between 0.0 and 1.0.
+    CubicBezier(f64, f64, f64, f64),
 }
 
 impl Easing {
     /// Creates the corresponding `CAMediaTimingFunction` for this easing curve.
-    fn to_timing_function(self) -> Retained<CAMediaTimingFunction> {
+    pub(crate) fn to_timing_function(self) -> Retained<CAMediaTimingFunction> {
         // SAFETY: The timing function name constants are valid extern statics
         // that are always available on macOS.
         let name = unsafe {
@@ -300,15 +631,151 @@ impl Easing {
                 Easing::In => kCAMediaTimingFunctionEaseIn,
                 Easing::Out => kCAMediaTimingFunctionEaseOut,
                 Easing::InOut => kCAMediaTimingFunctionEaseInEaseOut,
+                Easing::CubicBezier(x1, y1, x2, y2) => {
+                    return CAMediaTimingFunction::functionWithControlPoints(
+                        x1 as f32, y1 as f32, x2 as f32, y2 as f32,
+                    );
+                }
             }
         };
         CAMediaTimingFunction::functionWithName(name)
     }
+
+    /// Returns the cubic Bézier control points `(x1, y1, x2, y2)` of the
+    /// curve, as used by the named `CAMediaTimingFunction`s.
+    pub(crate) fn control_points(self) -> (f64, f64, f64, f64) {
+        match self {
+            Easing::Linear => (0.0, 0.0, 1.0, 1.0),
+            Easing::In => (0.42, 0.0, 1.0, 1.0),
+            Easing::Out => (0.0, 0.0, 0.58, 1.0),
+            Easing::InOut => (0.42, 0.0, 0.58, 1.0),
+            Easing::CubicBezier(x1, y1, x2, y2) => (x1, y1, x2, y2),
+        }
+    }
+
+    /// The same curve, played backwards.
+    pub(crate) fn reversed(self) -> Easing {
+        if self == Easing::Linear {
+            return self;
+        }
+        let (x1, y1, x2, y2) = self.control_points();
+        Easing::CubicBezier(1.0 - x2, 1.0 - y2, 1.0 - x1, 1.0 - y1)
+    }
+
+    /// Maps linear progress (0.0 to 1.0) to eased progress.
+    ///
+    /// Matches the curve Core Animation uses for this easing, so values
+    /// computed in Rust line up with what is rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(Easing::Linear.ease(0.25), 0.25);
+    /// assert!(Easing::In.ease(0.25) < 0.25);
+    /// ```
+    pub fn ease(self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        if self == Easing::Linear {
+            return progress;
+        }
+        let (x1, y1, x2, y2) = self.control_points();
+        let t = solve_bezier_parameter(x1, x2, progress);
+        bezier_coordinate(y1, y2, t)
+    }
+}
+
+impl Easing {
+    /// Identity for comparing and hashing, with control points by bits.
+    ///
+    /// `+ 0.0` turns `-0.0` into `0.0`, so equal curves compare equal.
+    fn bits(self) -> (u8, [u64; 4]) {
+        match self {
+            Easing::Linear => (0, [0; 4]),
+            Easing::In => (1, [0; 4]),
+            Easing::Out => (2, [0; 4]),
+            Easing::InOut => (3, [0; 4]),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                (4, [x1, y1, x2, y2].map(|value| (value + 0.0).to_bits()))
+            }
+        }
+    }
+}
+
+// Implemented by hand to keep `Eq` and `Hash` with float control points.
+impl PartialEq for Easing {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for Easing {}
+
+impl Hash for Easing {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+/// Slope (dy/dx) of the cubic Bézier `(x1, y1, x2, y2)` at progress `x`.
+pub(crate) fn bezier_slope(points: (f64, f64, f64, f64), x: f64) -> f64 {
+    let (x1, y1, x2, y2) = points;
+    let t = solve_bezier_parameter(x1, x2, x.clamp(0.0, 1.0));
+    let dx = bezier_derivative(x1, x2, t);
+    if dx.abs() < 1e-9 {
+        return 0.0;
+    }
+    bezier_derivative(y1, y2, t) / dx
+}
+
+/// One coordinate of a cubic Bézier from (0, 0) to (1, 1) at parameter `t`.
+fn bezier_coordinate(p1: f64, p2: f64, t: f64) -> f64 {
+    let mt = 1.0 - t;
+    3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t
+}
+
+/// Derivative of [`bezier_coordinate`] with respect to `t`.
+fn bezier_derivative(p1: f64, p2: f64, t: f64) -> f64 {
+    let mt = 1.0 - t;
+    3.0 * mt * mt * p1 + 6.0 * mt * t * (p2 - p1) + 3.0 * t * t * (1.0 - p2)
+}
+
+/// Finds the curve parameter whose x coordinate is `x`.
+///
+/// Newton's method converges in a few steps for typical curves; bisection
+/// takes over where the slope is too flat.
+fn solve_bezier_parameter(x1: f64, x2: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-7;
+
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier_coordinate(x1, x2, t) - x;
+        if error.abs() < EPSILON {
+            return t;
+        }
+        let slope = bezier_derivative(x1, x2, t);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        t -= error / slope;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    while high - low > EPSILON {
+        if bezier_coordinate(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+    t
 }
 
 /// Repeat behavior for animations.
 ///
-/// Controls how many times the animation plays before stopping.
+/// Controls how many times the animation plays before stopping. One cycle
+/// includes the way back when the animation autoreverses.
 ///
 /// # Examples
 ///
@@ -319,6 +786,12 @@ impl Easing {
 /// // Play 3 times
 /// builder.repeat(Repeat::Times(3))
 ///
+/// // Play two and a half times, ending halfway through the third cycle
+/// builder.repeat(Repeat::Count(2.5))
+///
+/// // Keep repeating for 10 seconds, however many cycles that is
+/// builder.repeat(Repeat::For(10.seconds()))
+///
 /// // Loop forever
 /// builder.repeat(Repeat::Forever)
 /// ```
@@ -329,25 +802,273 @@ pub enum Repeat {
     Once,
     /// Play the animation a specific number of times.
     Times(u32),
+    /// Play the animation a possibly fractional number of times.
+    ///
+    /// The animation stops partway through its last cycle.
+    Count(f32),
+    /// Repeat the animation until the given total time has passed.
+    ///
+    /// Maps to `repeatDuration`. The time is measured in the animation's
+    /// own time, so it is scaled by `speed` like the duration.
+    For(Duration),
     /// Loop the animation indefinitely.
     Forever,
 }
 
 impl Repeat {
-    /// Returns the `repeatCount` value for Core Animation.
+    /// Returns the `(repeatCount, repeatDuration)` pair for Core Animation.
     ///
-    /// - `Once` returns 1.0 (play once)
-    /// - `Times(n)` returns n as f32
-    /// - `Forever` returns `f32::INFINITY`
-    fn to_repeat_count(self) -> f32 {
+    /// - `Once` returns a count of 1.0 (play once)
+    /// - `Times(n)` and `Count(n)` return n as the count
+    /// - `For(d)` returns d in seconds as the duration and no count
+    /// - `Forever` returns a count of `f32::INFINITY`
+    fn to_ca_repeat(self) -> (f32, f64) {
+        match self {
+            Repeat::Once => (1.0, 0.0),
+            Repeat::Times(n) => (n as f32, 0.0),
+            Repeat::Count(n) => (n, 0.0),
+            Repeat::For(duration) => (0.0, duration.as_secs_f64()),
+            Repeat::Forever => (f32::INFINITY, 0.0),
+        }
+    }
+}
+
+/// What an animation shows outside its active time.
+///
+/// Maps to `fillMode` on `CAMediaTiming`. Without an explicit fill mode,
+/// builders use `Forwards`, or `Removed` together with
+/// `.remove_on_completion()`.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Hold the first frame during the delay, and the last one afterwards
+/// builder
+///     .values(0.0, 1.0)
+///     .delay(500.millis())
+///     .fill_mode(FillMode::Both)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FillMode {
+    /// Show the layer's own value before and after the animation.
+    Removed,
+    /// Hold the last frame after the animation ends.
+    Forwards,
+    /// Show the first frame while waiting for a delay.
+    Backwards,
+    /// Both `Forwards` and `Backwards`.
+    Both,
+}
+
+impl FillMode {
+    /// Returns the Core Animation fill mode string.
+    pub(crate) fn to_ca_fill_mode(self) -> &'static CAMediaTimingFillMode {
+        // SAFETY: The fill mode constants are valid extern statics that are
+        // always available on macOS.
+        unsafe {
+            match self {
+                FillMode::Removed => kCAFillModeRemoved,
+                FillMode::Forwards => kCAFillModeForwards,
+                FillMode::Backwards => kCAFillModeBackwards,
+                FillMode::Both => kCAFillModeBoth,
+            }
+        }
+    }
+
+    /// Reads a Core Animation fill mode string.
+    pub(crate) fn from_ca_fill_mode(fill_mode: &CAMediaTimingFillMode) -> Option<Self> {
+        [
+            FillMode::Removed,
+            FillMode::Forwards,
+            FillMode::Backwards,
+            FillMode::Both,
+        ]
+        .into_iter()
+        .find(|mode| mode.to_ca_fill_mode() == fill_mode)
+    }
+
+    /// This fill mode, also showing the first frame before the start.
+    pub(crate) fn holding_start(self) -> Self {
         match self {
-            Repeat::Once => 1.0,
-            Repeat::Times(n) => n as f32,
-            Repeat::Forever => f32::INFINITY,
+            FillMode::Removed | FillMode::Backwards => FillMode::Backwards,
+            FillMode::Forwards | FillMode::Both => FillMode::Both,
+        }
+    }
+
+    /// Whether the first frame is shown before the animation starts.
+    fn fills_backwards(self) -> bool {
+        matches!(self, FillMode::Backwards | FillMode::Both)
+    }
+
+    /// Whether the last frame is held after the animation ends.
+    fn fills_forwards(self) -> bool {
+        matches!(self, FillMode::Forwards | FillMode::Both)
+    }
+}
+
+/// How a keyframe animation moves between its keyframes.
+///
+/// Maps to `calculationMode` on `CAKeyframeAnimation`. The cubic modes
+/// pass through every value on a smooth curve, shaped per keyframe by
+/// [`tensions`](CAKeyframeAnimationBuilder::tensions),
+/// [`continuities`](CAKeyframeAnimationBuilder::continuities) and
+/// [`biases`](CAKeyframeAnimationBuilder::biases).
+///
+/// # Examples
+///
+/// ```ignore
+/// // Constant speed along the whole path
+/// builder.calculation_mode(CalculationMode::Paced)
+///
+/// // Smooth curve through the values instead of straight segments
+/// builder.calculation_mode(CalculationMode::Cubic)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CalculationMode {
+    /// Straight segments between keyframes, reached at their key times or
+    /// evenly spread (default).
+    #[default]
+    Linear,
+    /// Each value is held until the next one replaces it, without
+    /// interpolation.
+    Discrete,
+    /// Straight segments, with time distributed by distance for constant
+    /// speed. Key times and per-segment easings are ignored.
+    Paced,
+    /// A Kochanek–Bartels spline through the values, reached at their key
+    /// times.
+    Cubic,
+    /// The spline of `Cubic`, traveled at constant speed. Key times and
+    /// per-segment easings are ignored.
+    CubicPaced,
+}
+
+impl CalculationMode {
+    /// Returns the Core Animation calculation mode string.
+    fn to_ca_mode(self) -> &'static CAAnimationCalculationMode {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                CalculationMode::Linear => kCAAnimationLinear,
+                CalculationMode::Discrete => kCAAnimationDiscrete,
+                CalculationMode::Paced => kCAAnimationPaced,
+                CalculationMode::Cubic => kCAAnimationCubic,
+                CalculationMode::CubicPaced => kCAAnimationCubicPaced,
+            }
+        }
+    }
+}
+
+/// Duration of every animation under [`MotionPolicy::None`].
+///
+/// Not zero, which Core Animation would replace with its default duration.
+const NO_MOTION_DURATION: Duration = Duration::from_millis(1);
+
+/// Timing settings shared by the animation builders.
+///
+/// Holds everything that maps to `CAMediaTiming` and the removal behavior,
+/// so each builder only deals with its own values.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Timing {
+    pub(crate) duration: Duration,
+    pub(crate) easing: Easing,
+    pub(crate) autoreverses: bool,
+    pub(crate) repeat: Repeat,
+    pub(crate) phase_offset: f64,
+    pub(crate) delay: Duration,
+    pub(crate) speed: f32,
+    pub(crate) fill_mode: Option<FillMode>,
+    pub(crate) remove_on_completion: bool,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(250),
+            easing: Easing::default(),
+            autoreverses: false,
+            repeat: Repeat::default(),
+            phase_offset: 0.0,
+            delay: Duration::ZERO,
+            speed: 1.0,
+            fill_mode: None,
+            remove_on_completion: false,
         }
     }
 }
 
+impl Timing {
+    /// The explicit fill mode, or the one implied by the removal setting.
+    pub(crate) fn fill_mode(&self) -> FillMode {
+        self.fill_mode.unwrap_or(if self.remove_on_completion {
+            FillMode::Removed
+        } else {
+            FillMode::Forwards
+        })
+    }
+
+    /// These settings as far as the motion policy allows.
+    ///
+    /// `Reduced` plays animations that would repeat forever once. `None`
+    /// plays everything once over [`NO_MOTION_DURATION`], so the animation
+    /// lands on its end value right away.
+    pub(crate) fn for_policy(&self, policy: MotionPolicy) -> Timing {
+        let mut timing = self.clone();
+        match policy {
+            MotionPolicy::Full => {}
+            MotionPolicy::Reduced => {
+                if timing.repeat == Repeat::Forever {
+                    timing.repeat = Repeat::Once;
+                }
+            }
+            MotionPolicy::None => {
+                timing.repeat = Repeat::Once;
+                timing.duration = NO_MOTION_DURATION;
+            }
+        }
+        timing
+    }
+
+    /// Applies these settings to an animation, limited by the global
+    /// motion policy.
+    pub(crate) fn apply(&self, anim: &CAAnimation) {
+        let timing = self.for_policy(AnimationSettings::motion_policy());
+        // Set timing properties (from CAMediaTiming trait)
+        let duration_secs = timing.duration.as_secs_f64();
+        anim.setDuration(duration_secs);
+        anim.setAutoreverses(timing.autoreverses);
+        let (repeat_count, repeat_duration) = timing.repeat.to_ca_repeat();
+        anim.setRepeatCount(repeat_count);
+        anim.setRepeatDuration(repeat_duration);
+
+        // Set phase offset as timeOffset
+        // For autoreverses, multiply by 2 because the full cycle is forward + backward
+        if timing.phase_offset > 0.0 {
+            let cycle_duration = if timing.autoreverses {
+                duration_secs * 2.0
+            } else {
+                duration_secs
+            };
+            anim.setTimeOffset(timing.phase_offset * cycle_duration);
+        }
+
+        // Set timing function (easing)
+        let timing_function = timing.easing.to_timing_function();
+        anim.setTimingFunction(Some(&timing_function));
+
+        // The global speed slows delays down along with the animation.
+        // The delay stays relative until the animation is added to a layer,
+        // see `CALayerExt::add_animation`.
+        anim.setSpeed(timing.speed);
+        anim.setBeginTime(timing.delay.as_secs_f64());
+        slow_down(anim);
+
+        // Set fill mode and removedOnCompletion for value persistence
+        anim.setRemovedOnCompletion(timing.remove_on_completion);
+        anim.setFillMode(timing.fill_mode().to_ca_fill_mode());
+    }
+}
+
 /// Builder for configuring `CABasicAnimation` instances.
 ///
 /// Creates from→to animations on a single property. The builder uses
@@ -378,15 +1099,16 @@ impl Repeat {
 /// to the original value. Call `.remove_on_completion()` to opt-in to
 /// snap-back behavior.
 pub struct CABasicAnimationBuilder {
-    key_path: KeyPath,
-    from_value: Option<f64>,
-    to_value: Option<f64>,
-    duration: Duration,
-    easing: Easing,
-    autoreverses: bool,
-    repeat: Repeat,
-    phase_offset: f64,
-    remove_on_completion: bool,
+    pub(crate) key_path: KeyPath,
+    pub(crate) from_value: Option<AnimationValue>,
+    pub(crate) to_value: Option<AnimationValue>,
+    pub(crate) by_value: Option<AnimationValue>,
+    pub(crate) additive: bool,
+    pub(crate) cumulative: bool,
+    pub(crate) timing: Timing,
+    pub(crate) hold_at_end: Duration,
+    pub(crate) hold_at_start: Duration,
+    pub(crate) callbacks: Callbacks,
 }
 
 impl CABasicAnimationBuilder {
@@ -407,12 +1129,13 @@ impl CABasicAnimationBuilder {
             key_path,
             from_value: None,
             to_value: None,
-            duration: Duration::from_millis(250),
-            easing: Easing::default(),
-            autoreverses: false,
-            repeat: Repeat::default(),
-            phase_offset: 0.0,
-            remove_on_completion: false,
+            by_value: None,
+            additive: false,
+            cumulative: false,
+            timing: Timing::default(),
+            hold_at_end: Duration::ZERO,
+            hold_at_start: Duration::ZERO,
+            callbacks: Callbacks::default(),
         }
     }
 
@@ -440,8 +1163,116 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn values(mut self, from: f64, to: f64) -> Self {
-        self.from_value = Some(from);
-        self.to_value = Some(to);
+        self.from_value = Some(AnimationValue::Scalar(from));
+        self.to_value = Some(AnimationValue::Scalar(to));
+        self.by_value = None;
+        self
+    }
+
+    /// Animates by a relative amount from the current value.
+    ///
+    /// Maps to `byValue`: the layer moves from whatever it currently shows
+    /// to that value plus `delta`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Nudge 20 points to the right of wherever the layer is
+    /// CABasicAnimationBuilder::new(KeyPath::PositionX).by(20.0)
+    /// ```
+    #[must_use]
+    pub fn by(mut self, delta: f64) -> Self {
+        self.from_value = None;
+        self.to_value = None;
+        self.by_value = Some(AnimationValue::Scalar(delta));
+        self
+    }
+
+    /// Animates from the current value to `to`.
+    ///
+    /// Leaves `fromValue` unset, so the animation starts at whatever the
+    /// layer currently shows instead of jumping to a fixed start.
+    #[must_use]
+    pub fn from_current_to(mut self, to: f64) -> Self {
+        self.from_value = None;
+        self.to_value = Some(AnimationValue::Scalar(to));
+        self.by_value = None;
+        self
+    }
+
+    /// Adds the animated value to the layer's value instead of replacing it.
+    ///
+    /// Several additive animations on the same property stack, e.g. a
+    /// wobble on top of a move. Values are then offsets, not absolutes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Wobble ±5° around whatever rotation the layer has
+    /// .animate("wobble", KeyPath::TransformRotation, |a| {
+    ///     a.values(-0.087, 0.087)
+    ///         .additive()
+    ///         .autoreverses()
+    ///         .repeat(Repeat::Forever)
+    /// })
+    /// ```
+    #[must_use]
+    pub fn additive(mut self) -> Self {
+        self.additive = true;
+        self
+    }
+
+    /// Makes each repeat continue from where the previous one ended.
+    ///
+    /// Without this, repeating animations reset at every cycle. Use it for
+    /// continuous motion, such as spinning that never snaps back.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // One full turn per second, forever, without resetting
+    /// builder
+    ///     .values(0.0, std::f64::consts::TAU)
+    ///     .easing(Easing::Linear)
+    ///     .cumulative()
+    ///     .repeat(Repeat::Forever)
+    /// ```
+    #[must_use]
+    pub fn cumulative(mut self) -> Self {
+        self.cumulative = true;
+        self
+    }
+
+    /// Sets the from and to paths for a morph animation.
+    ///
+    /// Use with [`KeyPath::Path`] on a shape layer. Both paths are
+    /// normalized with [`normalize_for_morph`] so they have matching cubic
+    /// segments and aligned start points. Without this, Core Animation
+    /// snaps instead of interpolating whenever the element lists differ.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Starting shape
+    /// * `to` - Ending shape
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Circle to square, even though the element lists differ
+    /// let circle = unsafe { CGPath::with_ellipse_in_rect(rect, std::ptr::null()) };
+    /// let square = unsafe { CGPath::with_rect(rect, std::ptr::null()) };
+    ///
+    /// CABasicAnimationBuilder::new(KeyPath::Path)
+    ///     .values_path(&circle, &square)
+    ///     .duration(400.millis())
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn values_path(mut self, from: &CGPath, to: &CGPath) -> Self {
+        let (from, to) = normalize_for_morph(&BezierPath::from(from), &BezierPath::from(to));
+        self.from_value = Some(AnimationValue::Path(from));
+        self.to_value = Some(AnimationValue::Path(to));
+        self.by_value = None;
         self
     }
 
@@ -464,7 +1295,7 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn duration(mut self, duration: Duration) -> Self {
-        self.duration = duration;
+        self.timing.duration = duration;
         self
     }
 
@@ -488,7 +1319,7 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn easing(mut self, easing: Easing) -> Self {
-        self.easing = easing;
+        self.timing.easing = easing;
         self
     }
 
@@ -508,7 +1339,7 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn autoreverses(mut self) -> Self {
-        self.autoreverses = true;
+        self.timing.autoreverses = true;
         self
     }
 
@@ -529,7 +1360,7 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn repeat(mut self, repeat: Repeat) -> Self {
-        self.repeat = repeat;
+        self.timing.repeat = repeat;
         self
     }
 
@@ -554,7 +1385,7 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn phase_offset(mut self, offset: f64) -> Self {
-        self.phase_offset = offset;
+        self.timing.phase_offset = offset;
         self
     }
 
@@ -575,12 +1406,150 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn remove_on_completion(mut self) -> Self {
-        self.remove_on_completion = true;
+        self.timing.remove_on_completion = true;
+        self
+    }
+
+    /// Waits before starting the animation.
+    ///
+    /// The delay is stored as a relative `beginTime` and counts from when
+    /// the animation is added with `.animate()` on a layer builder or
+    /// [`CALayerExt::add_animation`](crate::CALayerExt::add_animation), on
+    /// the clock the layer has at that moment. Inside a group it counts
+    /// from the group's start. Combine with [`FillMode::Backwards`] or
+    /// [`FillMode::Both`] to show the first frame while waiting.
+    ///
+    /// Layer builders add their animations before the layer has a
+    /// superlayer, so the delay counts in media time. Windows build their
+    /// layers into the container first; for a tree that is paused, seeked
+    /// or plays at another speed, attach the layer before adding the
+    /// animation with `add_animation`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Fade in after half a second, invisible until then
+    /// builder
+    ///     .values(0.0, 1.0)
+    ///     .delay(500.millis())
+    ///     .fill_mode(FillMode::Both)
+    /// ```
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.timing.delay = delay;
+        self
+    }
+
+    /// Sets the playback rate.
+    ///
+    /// * `speed` - 1.0 = normal (default), 2.0 = twice as fast, 0.5 = half
+    ///
+    /// The speed applies to the duration, not to a [`delay`](Self::delay).
+    /// It is multiplied by the global [`AnimationSettings::speed`].
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.timing.speed = speed;
+        self
+    }
+
+    /// Pauses at the `to` value before the animation reverses.
+    ///
+    /// Without [`autoreverses`](Self::autoreverses), the pause comes at the
+    /// end of every cycle instead. Needs numeric from and to values, and turns the
+    /// animation into keyframes, see
+    /// [`build_animation`](Self::build_animation).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Stay at full scale for 300 ms before shrinking again
+    /// builder
+    ///     .values(1.0, 1.2)
+    ///     .autoreverses()
+    ///     .hold_at_end(300.millis())
+    ///     .repeat(Repeat::Forever)
+    /// ```
+    #[must_use]
+    pub fn hold_at_end(mut self, hold: Duration) -> Self {
+        self.hold_at_end = hold;
+        self
+    }
+
+    /// Pauses at the `from` value at the start of every cycle.
+    ///
+    /// Unlike a [`delay`](Self::delay), the pause repeats with the
+    /// animation. Together with [`hold_at_end`](Self::hold_at_end), this
+    /// gives a breathing rhythm with rests at both ends. Needs numeric from
+    /// and to values, like `hold_at_end`.
+    #[must_use]
+    pub fn hold_at_start(mut self, hold: Duration) -> Self {
+        self.hold_at_start = hold;
+        self
+    }
+
+    /// Sets what the animation shows outside its active time.
+    ///
+    /// Overrides the default of `Forwards` (or `Removed` with
+    /// [`remove_on_completion`](Self::remove_on_completion)).
+    #[must_use]
+    pub fn fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.timing.fill_mode = Some(fill_mode);
+        self
+    }
+
+    /// Sets a closure to run when the animation starts.
+    ///
+    /// Runs on the main thread, once for every layer the animation is
+    /// added to.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// builder
+    ///     .values(0.0, 1.0)
+    ///     .on_start(|| println!("fading in"))
+    /// ```
+    #[must_use]
+    pub fn on_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.callbacks.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a closure to run when the animation stops.
+    ///
+    /// The closure receives `true` if the animation ran to completion, or
+    /// `false` if it was removed early (for example by `removeAllAnimations`
+    /// or by adding another animation with the same name). Runs on the main
+    /// thread. Animations with `Repeat::Forever` only stop when removed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Chain a second step without guessing timings
+    /// builder
+    ///     .values(0.0, 1.0)
+    ///     .duration(300.millis())
+    ///     .on_complete(|finished| {
+    ///         if finished {
+    ///             println!("intro done");
+    ///         }
+    ///     })
+    /// ```
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.callbacks.on_complete = Some(Box::new(callback));
         self
     }
 
     /// Builds and returns the configured `CABasicAnimation`.
     ///
+    /// # Panics
+    ///
+    /// Panics if the configuration is invalid (see
+    /// [`try_build`](Self::try_build)), or if
+    /// [`on_start`](Self::on_start) or [`on_complete`](Self::on_complete)
+    /// was set and this is not called from the main thread.
+    ///
     /// # Returns
     ///
     /// A retained `CABasicAnimation` ready to be added to a layer.
@@ -599,57 +1568,80 @@ impl CABasicAnimationBuilder {
     /// ```
     #[must_use]
     pub fn build(self) -> Retained<CABasicAnimation> {
+        self.try_build()
+            .unwrap_or_else(|err| panic!("invalid animation: {err}"))
+    }
+
+    /// Builds the configured `CABasicAnimation`, or explains why the
+    /// configuration makes no sense.
+    ///
+    /// Catches what Core Animation silently accepts: a zero duration, a
+    /// phase offset outside 0.0–1.0, `Repeat::Times(0)`, missing or
+    /// non-finite values, and values of the wrong type for the key path.
+    /// Use [`validate`](Self::validate) to check without building.
+    ///
+    /// Holds need a keyframe animation, so with
+    /// [`hold_at_end`](Self::hold_at_end) or
+    /// [`hold_at_start`](Self::hold_at_start) this returns
+    /// [`AnimationError::HoldNeedsKeyframes`]; use
+    /// [`build_animation`](Self::build_animation) instead.
+    ///
+    /// Under [`MotionPolicy::Reduced`], large moves, scales and rotations
+    /// jump straight to the end value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`on_start`](Self::on_start) or
+    /// [`on_complete`](Self::on_complete) was set and this is not called
+    /// from the main thread.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let anim = CABasicAnimationBuilder::new(KeyPath::Opacity)
+    ///     .values(0.0, 1.0)
+    ///     .duration(config.fade_duration)
+    ///     .try_build()?;
+    /// ```
+    pub fn try_build(mut self) -> Result<Retained<CABasicAnimation>, AnimationError> {
+        self.validate()?;
+        if self.has_holds() {
+            return Err(AnimationError::HoldNeedsKeyframes);
+        }
+        self.reduce_motion();
+
         let key_path_str = self.key_path.to_nsstring();
         let anim = CABasicAnimation::animationWithKeyPath(Some(&key_path_str));
 
         // Set from/to values if provided
-        if let Some(from) = self.from_value {
-            let from_number = NSNumber::new_f64(from);
-            // SAFETY: NSNumber is a valid object type for fromValue
+        if let Some(ref from) = self.from_value {
+            let from_object = from.to_object();
+            // SAFETY: NSNumber and CGPath are valid object types for fromValue
             unsafe {
-                anim.setFromValue(Some(&from_number));
+                anim.setFromValue(Some(&from_object));
             }
         }
-        if let Some(to) = self.to_value {
-            let to_number = NSNumber::new_f64(to);
-            // SAFETY: NSNumber is a valid object type for toValue
+        if let Some(ref to) = self.to_value {
+            let to_object = to.to_object();
+            // SAFETY: NSNumber and CGPath are valid object types for toValue
             unsafe {
-                anim.setToValue(Some(&to_number));
+                anim.setToValue(Some(&to_object));
             }
         }
-
-        // Set timing properties (from CAMediaTiming trait)
-        let duration_secs = self.duration.as_secs_f64();
-        anim.setDuration(duration_secs);
-        anim.setAutoreverses(self.autoreverses);
-        anim.setRepeatCount(self.repeat.to_repeat_count());
-
-        // Set phase offset as timeOffset
-        // For autoreverses, multiply by 2 because the full cycle is forward + backward
-        if self.phase_offset > 0.0 {
-            let cycle_duration = if self.autoreverses {
-                duration_secs * 2.0
-            } else {
-                duration_secs
-            };
-            anim.setTimeOffset(self.phase_offset * cycle_duration);
+        if let Some(ref by) = self.by_value {
+            let by_object = by.to_object();
+            // SAFETY: NSNumber is a valid object type for byValue
+            unsafe {
+                anim.setByValue(Some(&by_object));
+            }
         }
+        anim.setAdditive(self.additive);
+        anim.setCumulative(self.cumulative);
 
-        // Set timing function (easing)
-        let timing_function = self.easing.to_timing_function();
-        anim.setTimingFunction(Some(&timing_function));
-
-        // Set fill mode and removedOnCompletion for value persistence
-        if self.remove_on_completion {
-            anim.setRemovedOnCompletion(true);
-            // Default fill mode is fine for removal
-        } else {
-            anim.setRemovedOnCompletion(false);
-            // SAFETY: kCAFillModeForwards is a valid extern static
-            anim.setFillMode(unsafe { kCAFillModeForwards });
-        }
+        self.timing.apply(&anim);
+        self.callbacks.apply(&anim);
 
-        anim
+        Ok(anim)
     }
 }
 
@@ -675,6 +1667,7 @@ mod tests {
         assert_eq!(KeyPath::ShadowRadius.as_str(), "shadowRadius");
         assert_eq!(KeyPath::ShadowOffset.as_str(), "shadowOffset");
         assert_eq!(KeyPath::Bounds.as_str(), "bounds");
+        assert_eq!(KeyPath::Path.as_str(), "path");
         assert_eq!(KeyPath::Custom("custom.path").as_str(), "custom.path");
     }
 
@@ -683,16 +1676,65 @@ mod tests {
         assert_eq!(Easing::default(), Easing::InOut);
     }
 
+    #[test]
+    fn test_easing_curves() {
+        for easing in [Easing::Linear, Easing::In, Easing::Out, Easing::InOut] {
+            assert_eq!(easing.ease(0.0), 0.0);
+            assert!((easing.ease(1.0) - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(Easing::Linear.ease(0.3), 0.3);
+        assert!(Easing::In.ease(0.5) < 0.5);
+        assert!(Easing::Out.ease(0.5) > 0.5);
+        assert!((Easing::InOut.ease(0.5) - 0.5).abs() < 1e-6);
+        // Reference value for CSS ease-in at x = 0.25
+        assert!((Easing::In.ease(0.25) - 0.0935).abs() < 1e-3);
+        assert_eq!(Easing::Out.ease(1.5), Easing::Out.ease(1.0));
+    }
+
+    #[test]
+    fn test_easing_cubic_bezier() {
+        let custom = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+        for progress in [0.1, 0.25, 0.5, 0.9] {
+            assert_eq!(custom.ease(progress), Easing::InOut.ease(progress));
+        }
+        assert!(Easing::CubicBezier(0.2, 0.0, 0.0, 1.0).ease(0.5) > 0.5);
+    }
+
+    #[test]
+    fn test_easing_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let easings: HashSet<Easing> = [
+            Easing::InOut,
+            Easing::InOut,
+            Easing::CubicBezier(0.2, 0.0, 0.0, 1.0),
+            Easing::CubicBezier(0.2, -0.0, 0.0, 1.0),
+            Easing::CubicBezier(0.2, 0.0, 0.0, 0.9),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(easings.len(), 3);
+        // A custom curve equal to a named one is still a different easing.
+        assert_ne!(Easing::CubicBezier(0.42, 0.0, 0.58, 1.0), Easing::InOut);
+    }
+
     #[test]
     fn test_repeat_default() {
         assert_eq!(Repeat::default(), Repeat::Once);
     }
 
     #[test]
-    fn test_repeat_to_count() {
-        assert_eq!(Repeat::Once.to_repeat_count(), 1.0);
-        assert_eq!(Repeat::Times(5).to_repeat_count(), 5.0);
-        assert!(Repeat::Forever.to_repeat_count().is_infinite());
+    fn test_repeat_to_ca_repeat() {
+        assert_eq!(Repeat::Once.to_ca_repeat(), (1.0, 0.0));
+        assert_eq!(Repeat::Times(5).to_ca_repeat(), (5.0, 0.0));
+        assert_eq!(Repeat::Count(2.5).to_ca_repeat(), (2.5, 0.0));
+        assert_eq!(
+            Repeat::For(Duration::from_millis(1500)).to_ca_repeat(),
+            (0.0, 1.5)
+        );
+        let (count, duration) = Repeat::Forever.to_ca_repeat();
+        assert!(count.is_infinite());
+        assert_eq!(duration, 0.0);
     }
 
     #[test]
@@ -701,12 +1743,18 @@ mod tests {
         assert_eq!(builder.key_path, KeyPath::Opacity);
         assert_eq!(builder.from_value, None);
         assert_eq!(builder.to_value, None);
-        assert_eq!(builder.duration, Duration::from_millis(250));
-        assert_eq!(builder.easing, Easing::InOut);
-        assert!(!builder.autoreverses);
-        assert_eq!(builder.repeat, Repeat::Once);
-        assert_eq!(builder.phase_offset, 0.0);
-        assert!(!builder.remove_on_completion);
+        assert_eq!(builder.by_value, None);
+        assert!(!builder.additive);
+        assert!(!builder.cumulative);
+        assert_eq!(builder.timing.duration, Duration::from_millis(250));
+        assert_eq!(builder.timing.easing, Easing::InOut);
+        assert!(!builder.timing.autoreverses);
+        assert_eq!(builder.timing.repeat, Repeat::Once);
+        assert_eq!(builder.timing.phase_offset, 0.0);
+        assert_eq!(builder.timing.delay, Duration::ZERO);
+        assert_eq!(builder.timing.speed, 1.0);
+        assert_eq!(builder.timing.fill_mode(), FillMode::Forwards);
+        assert!(!builder.timing.remove_on_completion);
     }
 
     #[test]
@@ -721,13 +1769,95 @@ mod tests {
             .remove_on_completion();
 
         assert_eq!(builder.key_path, KeyPath::TransformScale);
-        assert_eq!(builder.from_value, Some(0.5));
-        assert_eq!(builder.to_value, Some(1.5));
-        assert_eq!(builder.duration, Duration::from_secs(1));
-        assert_eq!(builder.easing, Easing::Linear);
-        assert!(builder.autoreverses);
-        assert_eq!(builder.repeat, Repeat::Forever);
-        assert_eq!(builder.phase_offset, 0.25);
-        assert!(builder.remove_on_completion);
+        assert_eq!(builder.from_value, Some(AnimationValue::Scalar(0.5)));
+        assert_eq!(builder.to_value, Some(AnimationValue::Scalar(1.5)));
+        assert_eq!(builder.timing.duration, Duration::from_secs(1));
+        assert_eq!(builder.timing.easing, Easing::Linear);
+        assert!(builder.timing.autoreverses);
+        assert_eq!(builder.timing.repeat, Repeat::Forever);
+        assert_eq!(builder.timing.phase_offset, 0.25);
+        assert!(builder.timing.remove_on_completion);
+        assert_eq!(builder.timing.fill_mode(), FillMode::Removed);
+
+        let builder = builder
+            .delay(Duration::from_millis(300))
+            .speed(2.0)
+            .fill_mode(FillMode::Both);
+        assert_eq!(builder.timing.delay, Duration::from_millis(300));
+        assert_eq!(builder.timing.speed, 2.0);
+        assert_eq!(builder.timing.fill_mode(), FillMode::Both);
+    }
+
+    #[test]
+    fn test_builder_relative_values() {
+        let builder = CABasicAnimationBuilder::new(KeyPath::PositionX)
+            .values(0.0, 10.0)
+            .by(20.0)
+            .additive()
+            .cumulative();
+        assert_eq!(builder.from_value, None);
+        assert_eq!(builder.to_value, None);
+        assert_eq!(builder.by_value, Some(AnimationValue::Scalar(20.0)));
+        assert!(builder.additive);
+        assert!(builder.cumulative);
+
+        let builder = builder.from_current_to(5.0);
+        assert_eq!(builder.from_value, None);
+        assert_eq!(builder.to_value, Some(AnimationValue::Scalar(5.0)));
+        assert_eq!(builder.by_value, None);
+    }
+
+    #[test]
+    fn test_builder_callbacks() {
+        let builder = CABasicAnimationBuilder::new(KeyPath::Opacity);
+        assert!(builder.callbacks.on_start.is_none());
+        assert!(builder.callbacks.on_complete.is_none());
+
+        let builder = builder.on_start(|| {}).on_complete(|_finished| {});
+        assert!(builder.callbacks.on_start.is_some());
+        assert!(builder.callbacks.on_complete.is_some());
+    }
+
+    #[test]
+    fn test_timing_for_policy() {
+        let timing = Timing {
+            duration: Duration::from_millis(800),
+            autoreverses: true,
+            repeat: Repeat::Forever,
+            ..Timing::default()
+        };
+        assert_eq!(timing.for_policy(MotionPolicy::Full), timing);
+
+        let reduced = timing.for_policy(MotionPolicy::Reduced);
+        assert_eq!(reduced.repeat, Repeat::Once);
+        assert_eq!(reduced.duration, Duration::from_millis(800));
+        let times = Timing {
+            repeat: Repeat::Times(3),
+            ..timing.clone()
+        };
+        assert_eq!(times.for_policy(MotionPolicy::Reduced), times);
+
+        let none = times.for_policy(MotionPolicy::None);
+        assert_eq!(none.repeat, Repeat::Once);
+        assert_eq!(none.duration, NO_MOTION_DURATION);
+        assert!(none.autoreverses);
+    }
+
+    #[test]
+    fn test_values_path_normalizes() {
+        let rect = objc2_core_foundation::CGRect::new(
+            objc2_core_foundation::CGPoint::ZERO,
+            objc2_core_foundation::CGSize::new(40.0, 40.0),
+        );
+        let circle = unsafe { CGPath::with_ellipse_in_rect(rect, std::ptr::null()) };
+        let square = unsafe { CGPath::with_rect(rect, std::ptr::null()) };
+
+        let builder = CABasicAnimationBuilder::new(KeyPath::Path).values_path(&circle, &square);
+        let (Some(AnimationValue::Path(from)), Some(AnimationValue::Path(to))) =
+            (&builder.from_value, &builder.to_value)
+        else {
+            panic!("expected path values");
+        };
+        assert_eq!(from.elements().len(), to.elements().len());
     }
 }
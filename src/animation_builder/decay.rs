@@ -0,0 +1,490 @@
+//! Momentum-driven motion that slows to a stop, using `CAKeyframeAnimation`.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_quartz_core::CAKeyframeAnimation;
+
+use super::{
+    CAKeyframeAnimationBuilder, CalculationMode, Callbacks, Easing, FillMode, KeyPath, Timing,
+    NO_MOTION_DURATION,
+};
+use crate::retarget::Spring;
+
+/// Keyframes per second of motion.
+const SAMPLE_RATE: f64 = 60.0;
+
+/// Distance from the resting value at which the motion counts as stopped.
+const REST_THRESHOLD: f64 = 0.5;
+
+/// Upper bound on the length of a rubber-band bounce, in seconds.
+const MAX_BOUNCE: f64 = 5.0;
+
+/// How quickly a decaying motion loses speed.
+///
+/// The rate is the fraction of velocity kept after each millisecond, as in
+/// UIKit's `UIScrollView.DecelerationRate`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DecelerationRate {
+    /// Long glide, like a scroll view (0.998, default).
+    #[default]
+    Normal,
+    /// Short glide, like a paging picker (0.99).
+    Fast,
+    /// Custom rate between 0.0 and 1.0 (exclusive).
+    Custom(f64),
+}
+
+impl DecelerationRate {
+    /// Returns the fraction of velocity kept after each millisecond.
+    pub fn per_millisecond(self) -> f64 {
+        match self {
+            DecelerationRate::Normal => 0.998,
+            DecelerationRate::Fast => 0.99,
+            DecelerationRate::Custom(rate) => rate.clamp(f64::EPSILON, 1.0 - f64::EPSILON),
+        }
+    }
+
+    /// Exponential decay constant per second (negative).
+    fn per_second_log(self) -> f64 {
+        1000.0 * self.per_millisecond().ln()
+    }
+}
+
+/// Builder for fling-style animations that coast to a stop.
+///
+/// Starting at [`from`](Self::from) with an initial
+/// [`velocity`](Self::velocity), the value slows down exponentially until
+/// it rests. The resting value and the duration follow from the velocity
+/// and the [`DecelerationRate`], and are available before building through
+/// [`resting_value`](Self::resting_value) and
+/// [`settle_duration`](Self::settle_duration).
+///
+/// With [`bounds`](Self::bounds), the motion stops at the edge it runs
+/// into. Add [`rubber_band`](Self::rubber_band) to overshoot the edge and
+/// spring back instead.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Fling a toast off to the side
+/// let anim = DecayAnimationBuilder::new(KeyPath::PositionX)
+///     .from(200.0)
+///     .velocity(1500.0)
+///     .build();
+///
+/// // A ticker that stays within its track and bounces at the ends
+/// let anim = DecayAnimationBuilder::new(KeyPath::PositionX)
+///     .from(offset)
+///     .velocity(-800.0)
+///     .deceleration(DecelerationRate::Fast)
+///     .bounds(0.0, 600.0)
+///     .rubber_band()
+///     .build();
+/// ```
+pub struct DecayAnimationBuilder {
+    key_path: KeyPath,
+    from: f64,
+    velocity: f64,
+    deceleration: DecelerationRate,
+    bounds: Option<(f64, f64)>,
+    rubber_band: Option<Spring>,
+    timing: Timing,
+    callbacks: Callbacks,
+}
+
+impl DecayAnimationBuilder {
+    /// Creates a new decay animation builder for the specified property.
+    #[must_use]
+    pub fn new(key_path: KeyPath) -> Self {
+        Self {
+            key_path,
+            from: 0.0,
+            velocity: 0.0,
+            deceleration: DecelerationRate::default(),
+            bounds: None,
+            rubber_band: None,
+            timing: Timing {
+                easing: Easing::Linear,
+                ..Timing::default()
+            },
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    /// Sets the starting value.
+    #[must_use]
+    pub fn from(mut self, value: f64) -> Self {
+        self.from = value;
+        self
+    }
+
+    /// Sets the initial velocity, in units per second.
+    #[must_use]
+    pub fn velocity(mut self, velocity: f64) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Sets how quickly the motion slows down.
+    #[must_use]
+    pub fn deceleration(mut self, rate: DecelerationRate) -> Self {
+        self.deceleration = rate;
+        self
+    }
+
+    /// Keeps the value between `min` and `max`.
+    ///
+    /// Without [`rubber_band`](Self::rubber_band) the motion stops dead at
+    /// the edge it reaches.
+    #[must_use]
+    pub fn bounds(mut self, min: f64, max: f64) -> Self {
+        self.bounds = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Overshoots the bounds and springs back to the edge.
+    ///
+    /// Uses a critically damped spring, so the value returns without
+    /// oscillating. Has no effect without [`bounds`](Self::bounds).
+    #[must_use]
+    pub fn rubber_band(self) -> Self {
+        self.rubber_band_spring(Spring::new(200.0, 2.0 * 200.0_f64.sqrt()))
+    }
+
+    /// Overshoots the bounds and springs back to the edge with `spring`.
+    #[must_use]
+    pub fn rubber_band_spring(mut self, spring: Spring) -> Self {
+        self.rubber_band = Some(spring);
+        self
+    }
+
+    /// Waits before starting the motion.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.timing.delay = delay;
+        self
+    }
+
+    /// Sets the playback rate (1.0 = normal).
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.timing.speed = speed;
+        self
+    }
+
+    /// Sets what the animation shows outside its active time.
+    #[must_use]
+    pub fn fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.timing.fill_mode = Some(fill_mode);
+        self
+    }
+
+    /// Opts in to snap-back behavior (remove animation on completion).
+    #[must_use]
+    pub fn remove_on_completion(mut self) -> Self {
+        self.timing.remove_on_completion = true;
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the motion starts.
+    #[must_use]
+    pub fn on_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.callbacks.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the motion stops.
+    ///
+    /// The closure receives `true` if the motion ran to completion.
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.callbacks.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the value the motion comes to rest at.
+    pub fn resting_value(&self) -> f64 {
+        let free = self.from - self.velocity / self.deceleration.per_second_log();
+        match self.bounds {
+            // Starting outside the bounds always returns to the nearest edge.
+            Some((min, max)) if !(min..=max).contains(&self.from) => self.from.clamp(min, max),
+            Some((min, max)) => free.clamp(min, max),
+            None => free,
+        }
+    }
+
+    /// Returns how long the motion takes to come to rest.
+    pub fn settle_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.motion().duration())
+    }
+
+    /// Duration of the built animation.
+    ///
+    /// A motion that starts at rest still gets a short duration, since Core
+    /// Animation replaces a zero duration with its default.
+    fn animation_duration(&self) -> Duration {
+        self.settle_duration().max(NO_MOTION_DURATION)
+    }
+
+    /// Splits the motion into a free glide and an optional bounce.
+    fn motion(&self) -> DecayMotion {
+        let k = self.deceleration.per_second_log();
+        let glide_end = glide_duration(self.velocity, k);
+
+        let Some((min, max)) = self.bounds else {
+            return DecayMotion::glide(self.from, self.velocity, k, glide_end);
+        };
+
+        // Starting outside the bounds springs straight back to the edge.
+        let start = self.from.clamp(min, max);
+        if start != self.from {
+            let motion = DecayMotion::glide(start, 0.0, k, 0.0);
+            return match self.rubber_band {
+                Some(spring) => motion.then_bounce(start, self.from - start, self.velocity, spring),
+                None => motion,
+            };
+        }
+
+        let edge = if self.velocity > 0.0 { max } else { min };
+        let free_rest = self.from - self.velocity / k;
+        let crosses =
+            (self.velocity > 0.0 && free_rest > max) || (self.velocity < 0.0 && free_rest < min);
+        if !crosses {
+            return DecayMotion::glide(self.from, self.velocity, k, glide_end);
+        }
+
+        // Time at which the glide reaches the edge:
+        // from + v (e^{kt} - 1) / k = edge
+        let hit = ((edge - self.from) * k / self.velocity + 1.0).ln() / k;
+        let motion = DecayMotion::glide(self.from, self.velocity, k, hit);
+        match self.rubber_band {
+            Some(spring) => {
+                let velocity = self.velocity * (k * hit).exp();
+                motion.then_bounce(edge, 0.0, velocity, spring)
+            }
+            None => motion,
+        }
+    }
+
+    /// Returns the sampled values of the motion, evenly spaced in time.
+    fn sample(&self) -> Vec<f64> {
+        let motion = self.motion();
+        let duration = motion.duration();
+        let steps = (duration * SAMPLE_RATE).ceil().max(1.0) as usize;
+        (0..=steps)
+            .map(|step| motion.value_at(duration * step as f64 / steps as f64))
+            .collect()
+    }
+
+    /// Builds and returns the configured `CAKeyframeAnimation`.
+    ///
+    /// Reduced motion applies as for
+    /// [`CAKeyframeAnimationBuilder::build`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a callback was set and this is not called from the main
+    /// thread.
+    #[must_use]
+    pub fn build(mut self) -> Retained<CAKeyframeAnimation> {
+        let mut keyframes = CAKeyframeAnimationBuilder::new(self.key_path)
+            .values(self.sample())
+            .calculation_mode(CalculationMode::Linear);
+        self.timing.duration = self.animation_duration();
+        keyframes.timing = self.timing;
+        keyframes.callbacks = self.callbacks;
+        keyframes.build()
+    }
+}
+
+/// Seconds until a glide at `velocity` is within [`REST_THRESHOLD`] of rest.
+fn glide_duration(velocity: f64, k: f64) -> f64 {
+    // Remaining distance is |v e^{kt} / k|.
+    let remaining = (velocity / k).abs();
+    if remaining <= REST_THRESHOLD {
+        return 0.0;
+    }
+    (REST_THRESHOLD / remaining).ln() / k
+}
+
+/// A decaying glide, optionally followed by a spring back to an edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DecayMotion {
+    from: f64,
+    velocity: f64,
+    k: f64,
+    glide: f64,
+    bounce: Option<Bounce>,
+}
+
+/// Spring motion back to `edge`, starting `offset` away at `velocity`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounce {
+    edge: f64,
+    offset: f64,
+    velocity: f64,
+    spring: Spring,
+    duration: f64,
+}
+
+impl Bounce {
+    fn new(edge: f64, offset: f64, velocity: f64, spring: Spring) -> Self {
+        let mut bounce = Self {
+            edge,
+            offset,
+            velocity,
+            spring,
+            duration: 0.0,
+        };
+        // Find the last moment the bounce is visibly away from the edge.
+        let step = 1.0 / SAMPLE_RATE;
+        let mut t = 0.0;
+        while t < MAX_BOUNCE {
+            t += step;
+            if bounce.displacement(t).abs() >= REST_THRESHOLD {
+                bounce.duration = t + step;
+            }
+        }
+        bounce
+    }
+
+    /// Distance from the edge at `t` seconds into the bounce.
+    ///
+    /// Combines the spring's response to the initial offset and to the
+    /// initial velocity, which add up since the spring is linear.
+    fn displacement(&self, t: f64) -> f64 {
+        let settle = self.spring.progress(0.0, t);
+        let push = self.spring.progress(1.0, t) - settle;
+        self.offset * (1.0 - settle) + self.velocity * push
+    }
+}
+
+impl DecayMotion {
+    fn glide(from: f64, velocity: f64, k: f64, glide: f64) -> Self {
+        Self {
+            from,
+            velocity,
+            k,
+            glide,
+            bounce: None,
+        }
+    }
+
+    fn then_bounce(mut self, edge: f64, offset: f64, velocity: f64, spring: Spring) -> Self {
+        self.bounce = Some(Bounce::new(edge, offset, velocity, spring));
+        self
+    }
+
+    fn duration(&self) -> f64 {
+        self.glide + self.bounce.map_or(0.0, |bounce| bounce.duration)
+    }
+
+    fn value_at(&self, t: f64) -> f64 {
+        match self.bounce {
+            Some(bounce) if t >= self.duration() => bounce.edge,
+            Some(bounce) if t >= self.glide => bounce.edge + bounce.displacement(t - self.glide),
+            _ => {
+                let t = t.min(self.glide);
+                self.from + self.velocity * ((self.k * t).exp() - 1.0) / self.k
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionX);
+        assert_eq!(builder.key_path, KeyPath::PositionX);
+        assert_eq!(builder.deceleration, DecelerationRate::Normal);
+        assert!(builder.bounds.is_none());
+        assert!(builder.rubber_band.is_none());
+        assert_eq!(builder.timing.easing, Easing::Linear);
+        assert_eq!(builder.settle_duration(), Duration::ZERO);
+        assert_eq!(builder.sample(), vec![0.0, 0.0]);
+        // Core Animation would play a zero duration for 0.25 seconds.
+        assert_eq!(builder.animation_duration(), NO_MOTION_DURATION);
+    }
+
+    #[test]
+    fn test_resting_value_matches_uikit() {
+        // UIKit projects the resting offset as v / 1000 * r / (1 - r); the
+        // continuous model agrees to within a fraction of a point.
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionX)
+            .from(100.0)
+            .velocity(1000.0);
+        let uikit = 100.0 + 1000.0 / 1000.0 * 0.998 / (1.0 - 0.998);
+        assert!((builder.resting_value() - uikit).abs() < 1.0);
+
+        let fast = builder.deceleration(DecelerationRate::Fast);
+        assert!((fast.resting_value() - 200.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_glide_ends_near_rest() {
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionY)
+            .from(0.0)
+            .velocity(-2000.0);
+        let values = builder.sample();
+        assert_eq!(values[0], 0.0);
+        let last = *values.last().unwrap();
+        assert!((last - builder.resting_value()).abs() <= REST_THRESHOLD + 1e-9);
+        assert!(values.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(builder.settle_duration() > Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_bounds_stop_at_edge() {
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionX)
+            .from(0.0)
+            .velocity(1000.0)
+            .bounds(0.0, 100.0);
+        assert_eq!(builder.resting_value(), 100.0);
+        let values = builder.sample();
+        assert_close(*values.last().unwrap(), 100.0);
+        assert!(values.iter().all(|&value| value <= 100.0 + 1e-9));
+
+        // Hitting the edge cuts the glide short
+        let free = DecayAnimationBuilder::new(KeyPath::PositionX).velocity(1000.0);
+        assert!(builder.settle_duration() < free.settle_duration());
+    }
+
+    #[test]
+    fn test_rubber_band_overshoots_and_returns() {
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionX)
+            .from(0.0)
+            .velocity(1000.0)
+            .bounds(0.0, 100.0)
+            .rubber_band();
+        let values = builder.sample();
+        assert!(values.iter().any(|&value| value > 100.0 + 1.0));
+        assert_eq!(*values.last().unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_start_outside_bounds_springs_back() {
+        let builder = DecayAnimationBuilder::new(KeyPath::PositionX)
+            .from(-40.0)
+            .bounds(0.0, 100.0)
+            .rubber_band();
+        let values = builder.sample();
+        assert_close(values[0], -40.0);
+        assert_eq!(*values.last().unwrap(), 0.0);
+
+        let hard = DecayAnimationBuilder::new(KeyPath::PositionX)
+            .from(-40.0)
+            .bounds(0.0, 100.0);
+        assert_eq!(hard.sample(), vec![0.0, 0.0]);
+    }
+}
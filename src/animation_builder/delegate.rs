@@ -0,0 +1,114 @@
+//! `CAAnimationDelegate` that forwards start/stop events to Rust closures.
+
+use std::cell::RefCell;
+
+use dispatch2::DispatchQueue;
+use objc2::rc::Retained;
+use objc2::runtime::{NSObject, NSObjectProtocol, ProtocolObject};
+use objc2::{define_class, msg_send, DefinedClass, MainThreadMarker, MainThreadOnly, Message};
+use objc2_quartz_core::{CAAnimation, CAAnimationDelegate};
+
+/// Start and completion closures collected by an animation builder.
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    pub(crate) on_start: Option<Box<dyn FnMut()>>,
+    pub(crate) on_complete: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl Callbacks {
+    /// Installs a delegate on `anim` if any closure was set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a closure was set and this is not called from the main
+    /// thread, since the closures are only ever run there.
+    pub(crate) fn apply(self, anim: &CAAnimation) {
+        if self.on_start.is_none() && self.on_complete.is_none() {
+            return;
+        }
+        let mtm = MainThreadMarker::new()
+            .expect("animations with on_start/on_complete must be built on the main thread");
+        let delegate = AnimationDelegate::new(mtm, self);
+        anim.setDelegate(Some(ProtocolObject::from_ref(&*delegate)));
+    }
+}
+
+define_class!(
+    // SAFETY:
+    // - NSObject has no subclassing requirements.
+    // - `AnimationDelegate` does not implement `Drop`.
+    #[unsafe(super(NSObject))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "CoreAnimationRsAnimationDelegate"]
+    #[ivars = RefCell<Callbacks>]
+    struct AnimationDelegate;
+
+    unsafe impl NSObjectProtocol for AnimationDelegate {}
+
+    unsafe impl CAAnimationDelegate for AnimationDelegate {
+        #[unsafe(method(animationDidStart:))]
+        fn animation_did_start(&self, _anim: &CAAnimation) {
+            self.on_main(|delegate| delegate.started());
+        }
+
+        #[unsafe(method(animationDidStop:finished:))]
+        fn animation_did_stop(&self, _anim: &CAAnimation, finished: bool) {
+            self.on_main(move |delegate| delegate.stopped(finished));
+        }
+    }
+);
+
+impl AnimationDelegate {
+    fn new(mtm: MainThreadMarker, callbacks: Callbacks) -> Retained<Self> {
+        let this = Self::alloc(mtm).set_ivars(RefCell::new(callbacks));
+        // SAFETY: `init` is NSObject's designated initializer.
+        unsafe { msg_send![super(this), init] }
+    }
+
+    /// Runs `f` now if on the main thread, otherwise on the main queue.
+    ///
+    /// Core Animation normally messages delegates on the main thread, but
+    /// this keeps the guarantee even if it does not.
+    fn on_main(&self, f: impl FnOnce(&Self) + Send + 'static) {
+        if MainThreadMarker::new().is_some() {
+            f(self);
+        } else {
+            let this = SendDelegate(self.retain());
+            DispatchQueue::main().exec_async(move || {
+                let this = this;
+                f(&this.0);
+            });
+        }
+    }
+
+    // The closures are taken out of the cell while they run, so one that
+    // triggers another delegate message (by removing or re-adding the
+    // animation) does not find the cell still borrowed.
+
+    fn started(&self) {
+        let on_start = self.ivars().borrow_mut().on_start.take();
+        if let Some(mut on_start) = on_start {
+            on_start();
+            self.ivars().borrow_mut().on_start.get_or_insert(on_start);
+        }
+    }
+
+    fn stopped(&self, finished: bool) {
+        let on_complete = self.ivars().borrow_mut().on_complete.take();
+        if let Some(mut on_complete) = on_complete {
+            on_complete(finished);
+            self.ivars()
+                .borrow_mut()
+                .on_complete
+                .get_or_insert(on_complete);
+        }
+    }
+}
+
+/// Carries the delegate to the main queue.
+struct SendDelegate(Retained<AnimationDelegate>);
+
+// SAFETY: Retaining and releasing an Objective-C object is thread-safe, and
+// the delegate (including its closures) is only used once it is back on the
+// main queue.
+unsafe impl Send for SendDelegate {}
@@ -0,0 +1,1042 @@
+//! Samples animations in Rust, without Core Animation.
+
+use std::time::Duration;
+
+use objc2_core_foundation::CGPoint;
+
+use super::{
+    AnimationValue, CABasicAnimationBuilder, CAKeyframeAnimationBuilder, CalculationMode, Easing,
+    Repeat, SampleValue, Timing,
+};
+
+/// Computes the value of a configured animation at any point in time.
+///
+/// Follows Core Animation's timing rules for duration, easing, repeats,
+/// autoreverse, `phase_offset`, delay, speed, fill mode and removal, as
+/// well as relative, additive
+/// and cumulative values, so tests can assert on
+/// mid-animation states and previews can run without a display.
+///
+/// Times are measured from when the animation is added to its layer.
+///
+/// # Examples
+///
+/// ```ignore
+/// let eval = CABasicAnimationBuilder::new(KeyPath::Opacity)
+///     .values(0.0, 1.0)
+///     .duration(1.seconds())
+///     .easing(Easing::Linear)
+///     .evaluator();
+///
+/// assert_eq!(eval.value_at(250.millis()), Some(AnimationValue::Scalar(0.25)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvaluator {
+    from: Option<AnimationValue>,
+    to: Option<AnimationValue>,
+    by: Option<AnimationValue>,
+    additive: bool,
+    cumulative: bool,
+    model: Option<AnimationValue>,
+    timing: Timing,
+}
+
+impl AnimationEvaluator {
+    /// Sets the layer's own (model) value of the property.
+    ///
+    /// Core Animation uses it in place of a missing from or to value, adds
+    /// additive animations on top of it, and shows it whenever the
+    /// animation is not in effect.
+    #[must_use]
+    pub fn model_value(mut self, value: impl Into<AnimationValue>) -> Self {
+        self.model = Some(value.into());
+        self
+    }
+
+    /// Returns when the animation ends, including its delay, or `None` if
+    /// it never does.
+    pub fn active_duration(&self) -> Option<Duration> {
+        self.timing.active_duration()
+    }
+
+    /// Returns the eased progress from the from value (0.0) to the to
+    /// value (1.0) at `time`.
+    ///
+    /// Returns `None` whenever the layer's own value shows instead.
+    pub fn progress_at(&self, time: Duration) -> Option<f64> {
+        self.timing
+            .sample(time.as_secs_f64())
+            .map(|sample| sample.progress)
+    }
+
+    /// Returns the property value shown at `time`.
+    ///
+    /// Returns the [model value](Self::model_value) whenever the animation
+    /// is not in effect (before a delay without backwards fill, after the
+    /// end without forwards fill or once removed), and `None` if a value is
+    /// needed but was never set.
+    pub fn value_at(&self, time: Duration) -> Option<AnimationValue> {
+        let Some(sample) = self.timing.sample(time.as_secs_f64()) else {
+            return self.model.clone();
+        };
+        let (from, to) = self.endpoints()?;
+        let mut value = interpolate(&from, &to, sample.progress);
+
+        // With autoreverse every cycle ends where it started, so there is
+        // nothing to accumulate.
+        if self.cumulative && !self.timing.autoreverses && sample.cycle > 0.0 {
+            let delta = scalar(&to)? - scalar(&from)?;
+            value = AnimationValue::Scalar(scalar(&value)? + delta * sample.cycle);
+        }
+        if self.additive {
+            let base = scalar(self.model.as_ref()?)?;
+            value = AnimationValue::Scalar(base + scalar(&value)?);
+        }
+        Some(value)
+    }
+
+    /// Resolves the start and end values from the from/to/by combination,
+    /// following `CAPropertyAnimation`.
+    ///
+    /// Missing values come from the model value, or are zero for additive
+    /// animations, whose values are offsets.
+    fn endpoints(&self) -> Option<(AnimationValue, AnimationValue)> {
+        let current = || {
+            if self.additive {
+                Some(AnimationValue::Scalar(0.0))
+            } else {
+                self.model.clone()
+            }
+        };
+        let shifted = |value: &AnimationValue, sign: f64| -> Option<AnimationValue> {
+            let by = scalar(self.by.as_ref()?)?;
+            Some(AnimationValue::Scalar(scalar(value)? + sign * by))
+        };
+
+        match (&self.from, &self.to, &self.by) {
+            (Some(from), Some(to), _) => Some((from.clone(), to.clone())),
+            (Some(from), None, Some(_)) => Some((from.clone(), shifted(from, 1.0)?)),
+            (None, Some(to), Some(_)) => Some((shifted(to, -1.0)?, to.clone())),
+            (None, None, Some(_)) => {
+                let from = current()?;
+                let to = shifted(&from, 1.0)?;
+                Some((from, to))
+            }
+            (Some(from), None, None) => Some((from.clone(), current()?)),
+            (None, Some(to), None) => Some((current()?, to.clone())),
+            (None, None, None) => {
+                let current = current()?;
+                Some((current.clone(), current))
+            }
+        }
+    }
+}
+
+impl CABasicAnimationBuilder {
+    /// Returns an evaluator for the animation as currently configured.
+    ///
+    /// Holds are left out; evaluate the result of
+    /// [`into_keyframes`](Self::into_keyframes) to include them.
+    pub fn evaluator(&self) -> AnimationEvaluator {
+        AnimationEvaluator {
+            from: self.from_value.clone(),
+            to: self.to_value.clone(),
+            by: self.by_value.clone(),
+            additive: self.additive,
+            cumulative: self.cumulative,
+            model: None,
+            timing: self.timing.clone(),
+        }
+    }
+}
+
+/// Computes the value of a keyframe animation at any point in time.
+///
+/// Follows the same timing rules as [`AnimationEvaluator`], and moves
+/// between the keyframes like Core Animation does in each
+/// [`CalculationMode`], including the Kochanek–Bartels spline of the cubic
+/// modes. Use it to preview the curve or to test it.
+///
+/// Numbers and points can be evaluated. Points move along each axis
+/// separately, and paced modes measure the straight-line distance
+/// between them.
+///
+/// # Examples
+///
+/// ```ignore
+/// let eval = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+///     .values([0.0, 100.0, 40.0])
+///     .calculation_mode(CalculationMode::Cubic)
+///     .duration(1.seconds())
+///     .easing(Easing::Linear)
+///     .evaluator();
+///
+/// // Plot one pass of the curve
+/// let points: Vec<AnimationValue> = (0..=100)
+///     .filter_map(|i| eval.value_at_progress(i as f64 / 100.0))
+///     .collect();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyframeEvaluator {
+    values: Vec<AnimationValue>,
+    key_times: Vec<f64>,
+    easings: Vec<Easing>,
+    calculation_mode: CalculationMode,
+    tensions: Vec<f64>,
+    continuities: Vec<f64>,
+    biases: Vec<f64>,
+    additive: bool,
+    cumulative: bool,
+    model: Option<AnimationValue>,
+    timing: Timing,
+}
+
+impl KeyframeEvaluator {
+    /// Sets the layer's own (model) value of the property.
+    ///
+    /// Additive animations are added on top of it, and it shows whenever
+    /// the animation is not in effect.
+    #[must_use]
+    pub fn model_value(mut self, value: impl Into<AnimationValue>) -> Self {
+        self.model = Some(value.into());
+        self
+    }
+
+    /// Returns when the animation ends, including its delay, or `None` if
+    /// it never does.
+    pub fn active_duration(&self) -> Option<Duration> {
+        self.timing.active_duration()
+    }
+
+    /// Returns the property value shown at `time`.
+    ///
+    /// Returns the [model value](Self::model_value) whenever the animation
+    /// is not in effect, and `None` if a value is needed but was never set
+    /// or the keyframes are neither numbers nor points.
+    pub fn value_at(&self, time: Duration) -> Option<AnimationValue> {
+        let Some(sample) = self.timing.sample(time.as_secs_f64()) else {
+            return self.model.clone();
+        };
+        let value = self.value_at_progress(sample.progress)?;
+        let mut coordinates = axes(&value)?;
+
+        if self.cumulative && !self.timing.autoreverses && sample.cycle > 0.0 {
+            let first = axes_like(self.values.first()?, &coordinates)?;
+            let last = axes_like(self.values.last()?, &coordinates)?;
+            for (axis, (first, last)) in coordinates.iter_mut().zip(first.iter().zip(&last)) {
+                *axis += (last - first) * sample.cycle;
+            }
+        }
+        if self.additive {
+            let model = axes_like(self.model.as_ref()?, &coordinates)?;
+            for (axis, model) in coordinates.iter_mut().zip(&model) {
+                *axis += model;
+            }
+        }
+        Some(with_axes(&value, &coordinates))
+    }
+
+    /// Returns the value at `progress` through one pass of the keyframes,
+    /// from 0.0 to 1.0.
+    ///
+    /// Leaves out everything but the keyframes themselves: the overall
+    /// easing, repeats, autoreverse and additive or cumulative values.
+    /// Returns `None` if there are no keyframes or they are not all numbers
+    /// or all points.
+    pub fn value_at_progress(&self, progress: f64) -> Option<AnimationValue> {
+        let first = self.values.first()?;
+        let points: Vec<Vec<f64>> = self
+            .values
+            .iter()
+            .map(|value| axes(value).filter(|_| same_kind(value, first)))
+            .collect::<Option<_>>()?;
+        let count = points.len();
+        if count < 2 {
+            return Some(first.clone());
+        }
+        // The values along each axis, for the splines.
+        let values: Vec<Vec<f64>> = (0..points[0].len())
+            .map(|axis| points.iter().map(|point| point[axis]).collect())
+            .collect();
+        let progress = progress.clamp(0.0, 1.0);
+        let even = |segments: usize| -> Vec<f64> {
+            (0..count).map(|i| i as f64 / segments as f64).collect()
+        };
+        let key_times = if self.key_times.len() == count {
+            self.key_times.clone()
+        } else {
+            even(count - 1)
+        };
+
+        let value = match self.calculation_mode {
+            CalculationMode::Discrete => {
+                // Each value shows from its key time until the next one.
+                let times = if matches!(self.key_times.len(), n if n == count || n == count + 1) {
+                    self.key_times[..count].to_vec()
+                } else {
+                    even(count)
+                };
+                let index = times.iter().rposition(|&time| time <= progress);
+                return Some(self.values[index.unwrap_or(0)].clone());
+            }
+            CalculationMode::Linear => {
+                let (index, fraction) = self.segment(&key_times, progress);
+                lerp(&points[index], &points[index + 1], fraction)
+            }
+            CalculationMode::Paced => {
+                let (index, fraction) = segment(&paced_times(&points), progress);
+                lerp(&points[index], &points[index + 1], fraction)
+            }
+            CalculationMode::Cubic => {
+                let (index, fraction) = self.segment(&key_times, progress);
+                self.splines(&values, &key_times, index, fraction)
+            }
+            CalculationMode::CubicPaced => {
+                let key_times = even(count - 1);
+                let (index, fraction) = self.arc_length_segment(&values, &key_times, progress);
+                self.splines(&values, &key_times, index, fraction)
+            }
+        };
+        Some(with_axes(first, &value))
+    }
+
+    /// Finds the segment at `progress` and the eased fraction through it.
+    fn segment(&self, key_times: &[f64], progress: f64) -> (usize, f64) {
+        let (index, fraction) = segment(key_times, progress);
+        match self.easings.get(index) {
+            Some(easing) if self.easings.len() + 1 == key_times.len() => {
+                (index, easing.ease(fraction))
+            }
+            _ => (index, fraction),
+        }
+    }
+
+    /// Finds the segment and fraction through it at which the splines of
+    /// all axes have covered `progress` of their length.
+    fn arc_length_segment(
+        &self,
+        values: &[Vec<f64>],
+        key_times: &[f64],
+        progress: f64,
+    ) -> (usize, f64) {
+        const STEPS: usize = 32;
+        let segments = key_times.len() - 1;
+        let mut lengths = Vec::with_capacity(segments * STEPS + 1);
+        let mut total = 0.0;
+        let mut previous = self.splines(values, key_times, 0, 0.0);
+        lengths.push(0.0);
+        for index in 0..segments {
+            for step in 1..=STEPS {
+                let point = self.splines(values, key_times, index, step as f64 / STEPS as f64);
+                total += distance(&previous, &point);
+                lengths.push(total);
+                previous = point;
+            }
+        }
+        if total <= 0.0 {
+            return segment(key_times, progress);
+        }
+
+        let target = progress * total;
+        let sample = lengths
+            .iter()
+            .rposition(|&length| length <= target)
+            .unwrap_or(0)
+            .min(lengths.len() - 2);
+        let span = lengths[sample + 1] - lengths[sample];
+        let within = if span > 0.0 {
+            (target - lengths[sample]) / span
+        } else {
+            0.0
+        };
+        let position = (sample as f64 + within) / STEPS as f64;
+        let index = (position.floor() as usize).min(segments - 1);
+        (index, position - index as f64)
+    }
+
+    /// Evaluates the spline of each axis, see [`spline`](Self::spline).
+    fn splines(
+        &self,
+        values: &[Vec<f64>],
+        key_times: &[f64],
+        index: usize,
+        fraction: f64,
+    ) -> Vec<f64> {
+        values
+            .iter()
+            .map(|axis| self.spline(axis, key_times, index, fraction))
+            .collect()
+    }
+
+    /// Evaluates the Kochanek–Bartels spline between keyframes `index` and
+    /// `index + 1`, at `fraction` through the segment.
+    ///
+    /// The curve ends continue in the direction of their only segment, so
+    /// two keyframes give a straight line.
+    fn spline(&self, values: &[f64], key_times: &[f64], index: usize, fraction: f64) -> f64 {
+        let count = values.len();
+        let (start, end) = (values[index], values[index + 1]);
+        let span = end - start;
+        let before = if index > 0 {
+            start - values[index - 1]
+        } else {
+            span
+        };
+        let after = if index + 2 < count {
+            values[index + 2] - end
+        } else {
+            span
+        };
+
+        let parameter = |list: &[f64], index: usize| list.get(index).copied().unwrap_or(0.0);
+        let tcb = |index: usize| {
+            (
+                parameter(&self.tensions, index),
+                parameter(&self.continuities, index),
+                parameter(&self.biases, index),
+            )
+        };
+        let (t, c, b) = tcb(index);
+        let outgoing =
+            (1.0 - t) * ((1.0 + b) * (1.0 + c) * before + (1.0 - b) * (1.0 - c) * span) / 2.0;
+        let (t, c, b) = tcb(index + 1);
+        let incoming =
+            (1.0 - t) * ((1.0 + b) * (1.0 - c) * span + (1.0 - b) * (1.0 + c) * after) / 2.0;
+
+        // Scale the tangents for segments of different lengths, so the
+        // speed stays continuous across keyframes.
+        let length = key_times[index + 1] - key_times[index];
+        let scale = |neighbors: f64| {
+            if neighbors > 0.0 {
+                2.0 * length / neighbors
+            } else {
+                1.0
+            }
+        };
+        let outgoing = if index > 0 {
+            outgoing * scale(key_times[index + 1] - key_times[index - 1])
+        } else {
+            outgoing
+        };
+        let incoming = if index + 2 < count {
+            incoming * scale(key_times[index + 2] - key_times[index])
+        } else {
+            incoming
+        };
+
+        let s = fraction;
+        let s2 = s * s;
+        let s3 = s2 * s;
+        (2.0 * s3 - 3.0 * s2 + 1.0) * start
+            + (s3 - 2.0 * s2 + s) * outgoing
+            + (-2.0 * s3 + 3.0 * s2) * end
+            + (s3 - s2) * incoming
+    }
+}
+
+impl CAKeyframeAnimationBuilder {
+    /// Returns an evaluator for the animation as currently configured.
+    pub fn evaluator(&self) -> KeyframeEvaluator {
+        KeyframeEvaluator {
+            values: self.values.clone(),
+            key_times: self.key_times.clone(),
+            easings: self.easings.clone(),
+            calculation_mode: self.calculation_mode,
+            tensions: self.tensions.clone(),
+            continuities: self.continuities.clone(),
+            biases: self.biases.clone(),
+            additive: self.additive,
+            cumulative: self.cumulative,
+            model: None,
+            timing: self.timing.clone(),
+        }
+    }
+}
+
+/// Finds the segment of `key_times` that contains `progress`, and the
+/// fraction through it.
+///
+/// Where two keyframes share a key time, the later one wins.
+fn segment(key_times: &[f64], progress: f64) -> (usize, f64) {
+    let last = key_times.len() - 2;
+    let index = key_times
+        .iter()
+        .rposition(|&time| time <= progress)
+        .unwrap_or(0)
+        .min(last);
+    let length = key_times[index + 1] - key_times[index];
+    let fraction = if length > 0.0 {
+        ((progress - key_times[index]) / length).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    (index, fraction)
+}
+
+/// Key times that give every segment a share of time proportional to its
+/// distance, for constant speed.
+fn paced_times(values: &[Vec<f64>]) -> Vec<f64> {
+    let total: f64 = values
+        .windows(2)
+        .map(|pair| distance(&pair[0], &pair[1]))
+        .sum();
+    let mut covered = 0.0;
+    let mut times = vec![0.0];
+    for (index, pair) in values.windows(2).enumerate() {
+        covered += distance(&pair[0], &pair[1]);
+        times.push(if total > 0.0 {
+            covered / total
+        } else {
+            (index + 1) as f64 / (values.len() - 1) as f64
+        });
+    }
+    times
+}
+
+impl Timing {
+    /// Length of one pass in one direction, in seconds.
+    fn pass_seconds(&self) -> f64 {
+        self.duration.as_secs_f64()
+    }
+
+    /// Total running time in seconds, or `None` for forever.
+    pub(crate) fn active_seconds(&self) -> Option<f64> {
+        let pass = self.pass_seconds();
+        let cycle = if self.autoreverses { pass * 2.0 } else { pass };
+        match self.repeat {
+            Repeat::Forever => None,
+            Repeat::Count(n) if n == f32::INFINITY => None,
+            Repeat::Times(n) if n > 0 => Some(f64::from(n) * cycle),
+            Repeat::Count(n) if n > 0.0 => Some(f64::from(n) * cycle),
+            Repeat::For(duration) if !duration.is_zero() => Some(duration.as_secs_f64()),
+            // A repeatCount and repeatDuration of zero play once, like `Once`.
+            _ => Some(cycle),
+        }
+    }
+
+    /// When the animation ends, including its delay, or `None` if it never
+    /// does.
+    fn active_duration(&self) -> Option<Duration> {
+        let speed = f64::from(self.speed);
+        if speed <= 0.0 {
+            return None;
+        }
+        self.active_seconds()
+            .map(|secs| self.delay + Duration::from_secs_f64((secs / speed).max(0.0)))
+    }
+
+    /// Samples the timing `time` seconds after the animation was added.
+    ///
+    /// The delay comes first, then `speed` scales the local time.
+    /// `phase_offset` maps to `timeOffset`, which shifts the local time and
+    /// wraps it around the active duration, so a finished animation holds
+    /// the value at its offset.
+    ///
+    /// Returns `None` whenever the animation is not in effect.
+    pub(crate) fn sample(&self, time: f64) -> Option<Sample> {
+        let pass = self.pass_seconds();
+        let cycle = if self.autoreverses { pass * 2.0 } else { pass };
+        let offset = self.phase_offset * cycle;
+        let fill_mode = self.fill_mode();
+
+        let time = time - self.delay.as_secs_f64();
+        if time < 0.0 && !fill_mode.fills_backwards() {
+            return None;
+        }
+        let time = (time * f64::from(self.speed)).max(0.0);
+
+        let local = match self.active_seconds() {
+            Some(active) => {
+                if time >= active && (self.remove_on_completion || !fill_mode.fills_forwards()) {
+                    return None;
+                }
+                let local = time.min(active) + offset;
+                if local > active {
+                    local - active
+                } else {
+                    local
+                }
+            }
+            None => time + offset,
+        };
+
+        if pass <= 0.0 {
+            // Zero-length animations jump straight to their end state.
+            let progress = if self.autoreverses { 0.0 } else { 1.0 };
+            return Some(Sample {
+                progress,
+                cycle: 0.0,
+            });
+        }
+
+        // Which one-directional pass we are in; the exact end of a pass
+        // belongs to that pass, not the next one.
+        let mut index = (local / pass).floor();
+        let mut fraction = local / pass - index;
+        if fraction == 0.0 && index > 0.0 {
+            index -= 1.0;
+            fraction = 1.0;
+        }
+
+        let passes_per_cycle = if self.autoreverses { 2.0 } else { 1.0 };
+        let backwards = self.autoreverses && index % 2.0 == 1.0;
+        let fraction = if backwards { 1.0 - fraction } else { fraction };
+        Some(Sample {
+            progress: self.easing.ease(fraction),
+            cycle: (index / passes_per_cycle).floor(),
+        })
+    }
+}
+
+/// Timing state at one instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Sample {
+    /// Eased progress from the start value (0.0) to the end value (1.0).
+    pub(crate) progress: f64,
+    /// Number of completed repeat cycles.
+    pub(crate) cycle: f64,
+}
+
+fn scalar(value: &AnimationValue) -> Option<f64> {
+    match value {
+        AnimationValue::Scalar(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// The coordinates of a number or point, one per axis.
+fn axes(value: &AnimationValue) -> Option<Vec<f64>> {
+    match value {
+        AnimationValue::Scalar(v) => Some(vec![*v]),
+        AnimationValue::Point(p) => Some(vec![p.x, p.y]),
+        _ => None,
+    }
+}
+
+/// The coordinates of `value`, if it has as many axes as `like`.
+fn axes_like(value: &AnimationValue, like: &[f64]) -> Option<Vec<f64>> {
+    axes(value).filter(|axes| axes.len() == like.len())
+}
+
+/// A value of the same kind as `like`, at the given coordinates.
+fn with_axes(like: &AnimationValue, axes: &[f64]) -> AnimationValue {
+    match like {
+        AnimationValue::Point(_) => AnimationValue::Point(CGPoint::new(axes[0], axes[1])),
+        _ => AnimationValue::Scalar(axes[0]),
+    }
+}
+
+fn same_kind(a: &AnimationValue, b: &AnimationValue) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+fn lerp(from: &[f64], to: &[f64], fraction: f64) -> Vec<f64> {
+    from.iter()
+        .zip(to)
+        .map(|(from, to)| from + (to - from) * fraction)
+        .collect()
+}
+
+/// The straight-line distance between two sets of coordinates.
+fn distance(from: &[f64], to: &[f64]) -> f64 {
+    from.iter()
+        .zip(to)
+        .fold(0.0, |length, (from, to)| length.hypot(to - from))
+}
+
+/// Blends two values the way Core Animation interpolates them.
+fn interpolate(from: &AnimationValue, to: &AnimationValue, progress: f64) -> AnimationValue {
+    match (from, to) {
+        (AnimationValue::Scalar(a), AnimationValue::Scalar(b)) => {
+            AnimationValue::Scalar(a + (b - a) * progress)
+        }
+        (AnimationValue::Path(a), AnimationValue::Path(b)) => a
+            .interpolate(b, progress)
+            .map(AnimationValue::Path)
+            .unwrap_or_else(|| snap(from, to, progress)),
+        (AnimationValue::Point(a), AnimationValue::Point(b)) => {
+            AnimationValue::Point(a.lerp(*b, progress))
+        }
+        (AnimationValue::Color(a), AnimationValue::Color(b)) => {
+            AnimationValue::Color(a.lerp(*b, progress))
+        }
+        _ => snap(from, to, progress),
+    }
+}
+
+/// Values that cannot be blended switch over at the end.
+fn snap(from: &AnimationValue, to: &AnimationValue, progress: f64) -> AnimationValue {
+    if progress >= 1.0 {
+        to.clone()
+    } else {
+        from.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation_builder::{FillMode, KeyPath};
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn scalar(eval: &AnimationEvaluator, time: Duration) -> f64 {
+        match eval.value_at(time) {
+            Some(AnimationValue::Scalar(v)) => v,
+            other => panic!("expected a scalar, got {other:?}"),
+        }
+    }
+
+    fn linear() -> CABasicAnimationBuilder {
+        CABasicAnimationBuilder::new(KeyPath::Opacity)
+            .values(0.0, 1.0)
+            .duration(ms(1000))
+            .easing(Easing::Linear)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_linear_once_holds_end() {
+        let eval = linear().evaluator();
+        assert_close(scalar(&eval, ms(0)), 0.0);
+        assert_close(scalar(&eval, ms(250)), 0.25);
+        assert_close(scalar(&eval, ms(1000)), 1.0);
+        assert_close(scalar(&eval, ms(5000)), 1.0);
+        assert_eq!(eval.active_duration(), Some(ms(1000)));
+    }
+
+    #[test]
+    fn test_easing_applies() {
+        let eval = linear().easing(Easing::In).evaluator();
+        assert!(scalar(&eval, ms(500)) < 0.5);
+    }
+
+    #[test]
+    fn test_autoreverse_and_repeat() {
+        let eval = linear().autoreverses().repeat(Repeat::Times(2)).evaluator();
+        assert_eq!(eval.active_duration(), Some(ms(4000)));
+        assert_close(scalar(&eval, ms(500)), 0.5);
+        assert_close(scalar(&eval, ms(1000)), 1.0);
+        assert_close(scalar(&eval, ms(1500)), 0.5);
+        assert_close(scalar(&eval, ms(2250)), 0.25);
+        assert_close(scalar(&eval, ms(4000)), 0.0);
+        assert_close(scalar(&eval, ms(9000)), 0.0);
+    }
+
+    #[test]
+    fn test_forever() {
+        let eval = linear().repeat(Repeat::Forever).evaluator();
+        assert_eq!(eval.active_duration(), None);
+        assert_close(scalar(&eval, ms(10_250)), 0.25);
+    }
+
+    #[test]
+    fn test_fractional_and_duration_repeats() {
+        // Two and a half there-and-back cycles end at the far end
+        let eval = linear()
+            .autoreverses()
+            .repeat(Repeat::Count(2.5))
+            .evaluator();
+        assert_eq!(eval.active_duration(), Some(ms(5000)));
+        assert_close(scalar(&eval, ms(4500)), 0.5);
+        assert_close(scalar(&eval, ms(9000)), 1.0);
+
+        let eval = linear().repeat(Repeat::For(ms(2750))).evaluator();
+        assert_eq!(eval.active_duration(), Some(ms(2750)));
+        assert_close(scalar(&eval, ms(2500)), 0.5);
+        assert_close(scalar(&eval, ms(9000)), 0.75);
+    }
+
+    #[test]
+    fn test_phase_offset() {
+        let eval = linear()
+            .repeat(Repeat::Forever)
+            .phase_offset(0.5)
+            .evaluator();
+        assert_close(scalar(&eval, ms(0)), 0.5);
+        assert_close(scalar(&eval, ms(250)), 0.75);
+        assert_close(scalar(&eval, ms(750)), 0.25);
+
+        // With autoreverse the offset covers the full there-and-back cycle
+        let eval = linear().autoreverses().phase_offset(0.25).evaluator();
+        assert_close(scalar(&eval, ms(0)), 0.5);
+    }
+
+    #[test]
+    fn test_remove_on_completion() {
+        let eval = linear().remove_on_completion().evaluator();
+        assert_eq!(eval.progress_at(ms(1000)), None);
+        assert_eq!(eval.value_at(ms(1000)), None);
+        let eval = eval.model_value(0.3);
+        assert_eq!(eval.value_at(ms(2000)), Some(AnimationValue::Scalar(0.3)));
+    }
+
+    #[test]
+    fn test_delay_and_fill_mode() {
+        let eval = linear().delay(ms(500)).evaluator().model_value(0.7);
+        assert_eq!(eval.active_duration(), Some(ms(1500)));
+        // Forwards fill shows the model value during the delay
+        assert_close(scalar(&eval, ms(250)), 0.7);
+        assert_close(scalar(&eval, ms(750)), 0.25);
+        assert_close(scalar(&eval, ms(2000)), 1.0);
+
+        let eval = linear()
+            .delay(ms(500))
+            .fill_mode(FillMode::Both)
+            .evaluator()
+            .model_value(0.7);
+        assert_close(scalar(&eval, ms(250)), 0.0);
+        assert_close(scalar(&eval, ms(2000)), 1.0);
+
+        let eval = linear()
+            .fill_mode(FillMode::Removed)
+            .evaluator()
+            .model_value(0.7);
+        assert_close(scalar(&eval, ms(500)), 0.5);
+        assert_close(scalar(&eval, ms(2000)), 0.7);
+    }
+
+    #[test]
+    fn test_speed() {
+        let eval = linear().speed(2.0).delay(ms(100)).evaluator();
+        assert_eq!(eval.active_duration(), Some(ms(600)));
+        assert_close(scalar(&eval, ms(350)), 0.5);
+        assert_close(scalar(&eval, ms(600)), 1.0);
+    }
+
+    #[test]
+    fn test_by_value() {
+        let eval = linear().by(10.0).evaluator();
+        assert_eq!(eval.value_at(ms(500)), None);
+        let eval = eval.model_value(100.0);
+        assert_close(scalar(&eval, ms(500)), 105.0);
+        assert_close(scalar(&eval, ms(1000)), 110.0);
+    }
+
+    #[test]
+    fn test_from_current_to() {
+        let eval = linear().from_current_to(1.0).evaluator().model_value(0.5);
+        assert_close(scalar(&eval, ms(0)), 0.5);
+        assert_close(scalar(&eval, ms(500)), 0.75);
+    }
+
+    #[test]
+    fn test_additive() {
+        let eval = linear()
+            .values(-1.0, 1.0)
+            .additive()
+            .evaluator()
+            .model_value(10.0);
+        assert_close(scalar(&eval, ms(0)), 9.0);
+        assert_close(scalar(&eval, ms(750)), 10.5);
+
+        // Additive by-animations offset from the model value once
+        let eval = linear().by(2.0).additive().evaluator().model_value(10.0);
+        assert_close(scalar(&eval, ms(1000)), 12.0);
+    }
+
+    #[test]
+    fn test_cumulative() {
+        let eval = linear()
+            .values(0.0, 1.0)
+            .cumulative()
+            .repeat(Repeat::Times(3))
+            .evaluator();
+        assert_close(scalar(&eval, ms(500)), 0.5);
+        assert_close(scalar(&eval, ms(1500)), 1.5);
+        assert_close(scalar(&eval, ms(2250)), 2.25);
+        assert_close(scalar(&eval, ms(3000)), 3.0);
+
+        // Autoreversing cycles end where they started
+        let eval = linear()
+            .cumulative()
+            .autoreverses()
+            .repeat(Repeat::Times(2))
+            .evaluator();
+        assert_close(scalar(&eval, ms(2500)), 0.5);
+    }
+
+    #[test]
+    fn test_missing_values_use_model() {
+        let eval = CABasicAnimationBuilder::new(KeyPath::Opacity)
+            .duration(ms(1000))
+            .easing(Easing::Linear)
+            .evaluator();
+        assert_eq!(eval.value_at(ms(500)), None);
+        let eval = eval.model_value(0.4);
+        assert_close(scalar(&eval, ms(500)), 0.4);
+    }
+
+    fn keyframes(values: impl IntoIterator<Item = f64>) -> CAKeyframeAnimationBuilder {
+        CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+            .values(values)
+            .duration(ms(1000))
+            .easing(Easing::Linear)
+    }
+
+    fn at(builder: &CAKeyframeAnimationBuilder, progress: f64) -> f64 {
+        match builder.evaluator().value_at_progress(progress) {
+            Some(AnimationValue::Scalar(v)) => v,
+            other => panic!("expected a scalar, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_keyframes_linear() {
+        let builder = keyframes([0.0, 10.0, 40.0]);
+        assert_close(at(&builder, 0.25), 5.0);
+        assert_close(at(&builder, 0.75), 25.0);
+
+        let builder = builder
+            .key_times([0.0, 0.8, 1.0])
+            .easings([Easing::Linear, Easing::In]);
+        assert_close(at(&builder, 0.4), 5.0);
+        assert!(at(&builder, 0.9) < 25.0);
+        assert_close(at(&builder, 1.0), 40.0);
+    }
+
+    #[test]
+    fn test_keyframes_discrete() {
+        let builder = keyframes([0.0, 10.0, 20.0]).calculation_mode(CalculationMode::Discrete);
+        assert_eq!(at(&builder, 0.0), 0.0);
+        assert_eq!(at(&builder, 0.34), 10.0);
+        assert_eq!(at(&builder, 0.99), 20.0);
+        assert_eq!(at(&builder, 1.0), 20.0);
+
+        // One key time more than values: each value shows until the next
+        let builder = builder.key_times([0.0, 0.5, 0.75, 1.0]);
+        assert_eq!(at(&builder, 0.6), 10.0);
+        assert_eq!(at(&builder, 0.8), 20.0);
+    }
+
+    #[test]
+    fn test_keyframes_paced() {
+        // Distances 10 and 30 get a quarter and three quarters of the time
+        let builder = keyframes([0.0, 10.0, 40.0])
+            .key_times([0.0, 0.9, 1.0])
+            .calculation_mode(CalculationMode::Paced);
+        assert_close(at(&builder, 0.125), 5.0);
+        assert_close(at(&builder, 0.25), 10.0);
+        assert_close(at(&builder, 0.5), 20.0);
+    }
+
+    #[test]
+    fn test_keyframes_cubic() {
+        let builder = keyframes([0.0, 10.0, 0.0]).calculation_mode(CalculationMode::Cubic);
+        // Passes through every value
+        assert_close(at(&builder, 0.0), 0.0);
+        assert_close(at(&builder, 0.5), 10.0);
+        assert_close(at(&builder, 1.0), 0.0);
+        // Rounder than straight segments, and symmetric
+        assert_close(at(&builder, 0.25), 6.25);
+        assert_close(at(&builder, 0.75), 6.25);
+
+        // Full tension stops at every keyframe
+        let tense = builder.tensions([1.0, 1.0, 1.0]);
+        assert_close(at(&tense, 0.125), 1.5625);
+
+        // Two keyframes make a straight line
+        let line = keyframes([0.0, 10.0]).calculation_mode(CalculationMode::Cubic);
+        assert_close(at(&line, 0.3), 3.0);
+    }
+
+    #[test]
+    fn test_keyframes_cubic_bias_and_continuity() {
+        let plateau = || keyframes([0.0, 10.0, 10.0, 0.0]).calculation_mode(CalculationMode::Cubic);
+        let smooth = at(&plateau(), 0.5);
+        assert_close(smooth, 11.25);
+        // Positive bias at the second keyframe carries its motion further
+        let biased = plateau().biases([0.0, 1.0, 0.0, 0.0]);
+        assert!(at(&biased, 0.5) > smooth);
+        // A continuity of -1 turns both keyframes into corners
+        let cornered = plateau().continuities([0.0, -1.0, -1.0, 0.0]);
+        assert_close(at(&cornered, 0.5), 10.0);
+    }
+
+    #[test]
+    fn test_keyframes_cubic_paced() {
+        let builder = keyframes([0.0, 10.0, 30.0]).calculation_mode(CalculationMode::CubicPaced);
+        assert_close(at(&builder, 0.0), 0.0);
+        assert_close(at(&builder, 1.0), 30.0);
+        // A monotonic curve at constant speed covers distance evenly
+        for progress in [0.1, 0.25, 0.5, 0.8] {
+            assert!((at(&builder, progress) - 30.0 * progress).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_keyframes_timing() {
+        let eval = keyframes([0.0, 1.0, 3.0])
+            .cumulative()
+            .repeat(Repeat::Times(2))
+            .evaluator();
+        assert_eq!(eval.active_duration(), Some(ms(2000)));
+        assert_eq!(eval.value_at(ms(250)), Some(AnimationValue::Scalar(0.5)));
+        assert_eq!(eval.value_at(ms(1500)), Some(AnimationValue::Scalar(4.0)));
+
+        let eval = keyframes([0.0, 1.0])
+            .additive()
+            .remove_on_completion()
+            .evaluator();
+        assert_eq!(eval.value_at(ms(500)), None);
+        let eval = eval.model_value(10.0);
+        assert_eq!(eval.value_at(ms(500)), Some(AnimationValue::Scalar(10.5)));
+        assert_eq!(eval.value_at(ms(2000)), Some(AnimationValue::Scalar(10.0)));
+    }
+
+    fn points(values: &[(f64, f64)]) -> CAKeyframeAnimationBuilder {
+        let mut builder = keyframes([]);
+        builder.values = values
+            .iter()
+            .map(|&(x, y)| AnimationValue::Point(CGPoint::new(x, y)))
+            .collect();
+        builder
+    }
+
+    fn point_at(builder: &CAKeyframeAnimationBuilder, progress: f64) -> CGPoint {
+        match builder.evaluator().value_at_progress(progress) {
+            Some(AnimationValue::Point(point)) => point,
+            other => panic!("expected a point, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_keyframes_points() {
+        let corner = || points(&[(0.0, 0.0), (30.0, 40.0), (30.0, 50.0)]);
+        // Each axis moves on its own
+        assert_eq!(point_at(&corner(), 0.25), CGPoint::new(15.0, 20.0));
+        assert_eq!(point_at(&corner(), 1.0), CGPoint::new(30.0, 50.0));
+
+        // Distances 50 and 10 get five sixths and one sixth of the time
+        let paced = corner().calculation_mode(CalculationMode::Paced);
+        let halfway = point_at(&paced, 5.0 / 12.0);
+        assert_close(halfway.x, 15.0);
+        assert_close(halfway.y, 20.0);
+
+        let cubic = corner().calculation_mode(CalculationMode::Cubic);
+        assert_eq!(point_at(&cubic, 0.5), CGPoint::new(30.0, 40.0));
+        let cubic_paced = corner().calculation_mode(CalculationMode::CubicPaced);
+        let end = point_at(&cubic_paced, 1.0);
+        assert_close(end.x, 30.0);
+        assert_close(end.y, 50.0);
+
+        let eval = points(&[(0.0, 0.0), (10.0, -10.0)])
+            .additive()
+            .evaluator()
+            .model_value(CGPoint::new(100.0, 100.0));
+        assert_eq!(
+            eval.value_at(ms(500)),
+            Some(AnimationValue::Point(CGPoint::new(105.0, 95.0)))
+        );
+
+        // Numbers and points do not mix
+        let mut mixed = keyframes([0.0]);
+        mixed.values.push(AnimationValue::Point(CGPoint::ZERO));
+        assert_eq!(mixed.evaluator().value_at_progress(0.5), None);
+    }
+}
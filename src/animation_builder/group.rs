@@ -0,0 +1,197 @@
+//! Several animations played together using `CAAnimationGroup`.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_foundation::NSArray;
+use objc2_quartz_core::{CAAnimation, CAAnimationGroup};
+
+use super::{
+    CABasicAnimationBuilder, CAKeyframeAnimationBuilder, Callbacks, Easing, FillMode, KeyPath,
+    Repeat, Timing,
+};
+use crate::settings::restore_speed;
+
+/// Builder for configuring `CAAnimationGroup` instances.
+///
+/// The group has its own timing: children run on the group's clock and are
+/// cut off when the group's duration ends, so give them a duration no longer
+/// than the group's. Repeats, delays and callbacks belong on the group.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Fade in while growing, as one animation
+/// let anim = CAAnimationGroupBuilder::new()
+///     .basic(KeyPath::Opacity, |a| a.values(0.0, 1.0).duration(400.millis()))
+///     .basic(KeyPath::TransformScale, |a| a.values(0.8, 1.0).duration(400.millis()))
+///     .duration(400.millis())
+///     .build();
+/// ```
+pub struct CAAnimationGroupBuilder {
+    pub(crate) animations: Vec<Retained<CAAnimation>>,
+    pub(crate) timing: Timing,
+    pub(crate) callbacks: Callbacks,
+}
+
+impl CAAnimationGroupBuilder {
+    /// Creates an empty group.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            animations: Vec::new(),
+            timing: Timing {
+                easing: Easing::Linear,
+                ..Timing::default()
+            },
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    /// Adds a from→to animation to the group.
+    #[must_use]
+    pub fn basic<F>(self, key_path: KeyPath, configure: F) -> Self
+    where
+        F: FnOnce(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
+    {
+        let anim = configure(CABasicAnimationBuilder::new(key_path)).build_animation();
+        self.animation(anim)
+    }
+
+    /// Adds a keyframe animation to the group.
+    #[must_use]
+    pub fn keyframes<F>(self, key_path: KeyPath, configure: F) -> Self
+    where
+        F: FnOnce(CAKeyframeAnimationBuilder) -> CAKeyframeAnimationBuilder,
+    {
+        let anim = configure(CAKeyframeAnimationBuilder::new(key_path)).build();
+        self.animation(Retained::into_super(Retained::into_super(anim)))
+    }
+
+    /// Adds an already built animation to the group.
+    #[must_use]
+    pub fn animation(mut self, animation: Retained<CAAnimation>) -> Self {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Sets the duration of the group.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.timing.duration = duration;
+        self
+    }
+
+    /// Sets an easing curve applied to the group's clock (default: `Linear`).
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.timing.easing = easing;
+        self
+    }
+
+    /// Plays the group forward, then back.
+    #[must_use]
+    pub fn autoreverses(mut self) -> Self {
+        self.timing.autoreverses = true;
+        self
+    }
+
+    /// Sets the repeat behavior.
+    #[must_use]
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.timing.repeat = repeat;
+        self
+    }
+
+    /// Sets the phase offset (fraction of the cycle to skip, 0.0 to 1.0).
+    #[must_use]
+    pub fn phase_offset(mut self, offset: f64) -> Self {
+        self.timing.phase_offset = offset;
+        self
+    }
+
+    /// Waits before starting the group.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.timing.delay = delay;
+        self
+    }
+
+    /// Sets the playback rate (1.0 = normal).
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.timing.speed = speed;
+        self
+    }
+
+    /// Sets what the group shows outside its active time.
+    #[must_use]
+    pub fn fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.timing.fill_mode = Some(fill_mode);
+        self
+    }
+
+    /// Opts in to snap-back behavior (remove animation on completion).
+    #[must_use]
+    pub fn remove_on_completion(mut self) -> Self {
+        self.timing.remove_on_completion = true;
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the group starts.
+    #[must_use]
+    pub fn on_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.callbacks.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the group stops.
+    ///
+    /// The closure receives `true` if the group ran to completion.
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.callbacks.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Builds and returns the configured `CAAnimationGroup`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a callback was set and this is not called from the main
+    /// thread.
+    #[must_use]
+    pub fn build(self) -> Retained<CAAnimationGroup> {
+        let group = CAAnimationGroup::animation();
+        // The group carries the global speed, so its animations must not
+        // be slowed down a second time.
+        for anim in &self.animations {
+            restore_speed(anim);
+        }
+        group.setAnimations(Some(&NSArray::from_retained_slice(&self.animations)));
+
+        self.timing.apply(&group);
+        self.callbacks.apply(&group);
+
+        group
+    }
+}
+
+impl Default for CAAnimationGroupBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let builder = CAAnimationGroupBuilder::new();
+        assert!(builder.animations.is_empty());
+        assert_eq!(builder.timing.easing, Easing::Linear);
+        assert_eq!(builder.timing.duration, Duration::from_millis(250));
+    }
+}
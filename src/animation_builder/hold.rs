@@ -0,0 +1,238 @@
+//! Pauses at the ends of a basic animation, played as keyframes.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_quartz_core::CAAnimation;
+
+use super::{
+    AnimationError, AnimationValue, CABasicAnimationBuilder, CAKeyframeAnimationBuilder, Easing,
+    Timing,
+};
+use crate::settings::{AnimationSettings, MotionPolicy};
+
+/// One cycle of a basic animation with its holds, laid out as keyframes.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HeldKeyframes {
+    pub(crate) values: Vec<f64>,
+    pub(crate) key_times: Vec<f64>,
+    pub(crate) easings: Vec<Easing>,
+    /// The original timing, with the duration of the whole cycle and
+    /// without autoreverse, which the keyframes already contain.
+    pub(crate) timing: Timing,
+}
+
+impl CABasicAnimationBuilder {
+    /// Whether a hold is set, so the animation needs keyframes.
+    pub(crate) fn has_holds(&self) -> bool {
+        !self.hold_at_start.is_zero() || !self.hold_at_end.is_zero()
+    }
+
+    /// Lays out one cycle as keyframes: hold at `from`, ease to `to`, hold
+    /// at `to`, and ease back with the reversed curve if the animation
+    /// autoreverses. Holds of zero length are left out.
+    ///
+    /// Returns `None` unless from and to are both numbers.
+    pub(crate) fn held_keyframes(&self) -> Option<HeldKeyframes> {
+        let (Some(AnimationValue::Scalar(from)), Some(AnimationValue::Scalar(to))) =
+            (&self.from_value, &self.to_value)
+        else {
+            return None;
+        };
+        let pass = self.timing.duration;
+        let easing = self.timing.easing;
+
+        let mut time = Duration::ZERO;
+        let mut times = vec![time];
+        let mut values = vec![*from];
+        let mut easings = Vec::new();
+        let mut step = |length: Duration, value: f64, easing: Easing| {
+            if !length.is_zero() {
+                time += length;
+                times.push(time);
+                values.push(value);
+                easings.push(easing);
+            }
+        };
+        step(self.hold_at_start, *from, Easing::Linear);
+        step(pass, *to, easing);
+        step(self.hold_at_end, *to, Easing::Linear);
+        if self.timing.autoreverses {
+            step(pass, *from, easing.reversed());
+        }
+
+        let total = time.as_secs_f64();
+        Some(HeldKeyframes {
+            key_times: times.iter().map(|t| t.as_secs_f64() / total).collect(),
+            values,
+            easings,
+            timing: Timing {
+                duration: time,
+                easing: Easing::Linear,
+                autoreverses: false,
+                ..self.timing.clone()
+            },
+        })
+    }
+
+    /// Converts to a keyframe animation that plays the same, including
+    /// [`hold_at_end`](Self::hold_at_end) and
+    /// [`hold_at_start`](Self::hold_at_start).
+    ///
+    /// One keyframe cycle covers the way there, the way back and both
+    /// holds, so repeats, phase offsets, delay and speed keep their meaning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid (see
+    /// [`try_build`](Self::try_build)), or if from and to are not both
+    /// numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let breathe = CABasicAnimationBuilder::new(KeyPath::TransformScale)
+    ///     .values(1.0, 1.2)
+    ///     .duration(400.millis())
+    ///     .autoreverses()
+    ///     .hold_at_end(300.millis())
+    ///     .into_keyframes()?
+    ///     .build();
+    /// ```
+    pub fn into_keyframes(self) -> Result<CAKeyframeAnimationBuilder, AnimationError> {
+        self.validate()?;
+        let held = self
+            .held_keyframes()
+            .ok_or(AnimationError::HoldWithoutValues)?;
+
+        let mut keyframes = CAKeyframeAnimationBuilder::new(self.key_path)
+            .values(held.values)
+            .key_times(held.key_times)
+            .easings(held.easings);
+        keyframes.additive = self.additive;
+        keyframes.cumulative = self.cumulative;
+        keyframes.timing = held.timing;
+        keyframes.callbacks = self.callbacks;
+        Ok(keyframes)
+    }
+
+    /// Builds a `CABasicAnimation`, or a `CAKeyframeAnimation` if holds
+    /// are set.
+    ///
+    /// This is what the layer builders' `.animate()` uses. Under
+    /// [`MotionPolicy::Reduced`], large moves, scales and rotations become a
+    /// `CAAnimationGroup` instead, crossfading to the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`build`](Self::build).
+    #[must_use]
+    pub fn build_animation(self) -> Retained<CAAnimation> {
+        if AnimationSettings::motion_policy() == MotionPolicy::Reduced {
+            if let Some((from, to)) = self.large_motion() {
+                self.validate()
+                    .unwrap_or_else(|err| panic!("invalid animation: {err}"));
+                return Retained::into_super(self.crossfade(from, to).build());
+            }
+        }
+        if !self.has_holds() {
+            return Retained::into_super(Retained::into_super(self.build()));
+        }
+        let keyframes = self
+            .into_keyframes()
+            .unwrap_or_else(|err| panic!("invalid animation: {err}"));
+        Retained::into_super(Retained::into_super(keyframes.build()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation_builder::{KeyPath, Repeat};
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn assert_times(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+    }
+
+    fn breathe() -> CABasicAnimationBuilder {
+        CABasicAnimationBuilder::new(KeyPath::TransformScale)
+            .values(1.0, 1.2)
+            .duration(ms(400))
+            .easing(Easing::Out)
+            .autoreverses()
+    }
+
+    #[test]
+    fn test_autoreverse_holds() {
+        let builder = breathe()
+            .hold_at_end(ms(500))
+            .hold_at_start(ms(300))
+            .repeat(Repeat::Forever);
+        assert!(builder.has_holds());
+
+        let held = builder.held_keyframes().unwrap();
+        assert_eq!(held.values, [1.0, 1.0, 1.2, 1.2, 1.0]);
+        assert_times(&held.key_times, &[0.0, 0.1875, 0.4375, 0.75, 1.0]);
+        assert_eq!(
+            held.easings,
+            [
+                Easing::Linear,
+                Easing::Out,
+                Easing::Linear,
+                Easing::Out.reversed()
+            ]
+        );
+        assert_eq!(held.timing.duration, ms(1600));
+        assert_eq!(held.timing.easing, Easing::Linear);
+        assert!(!held.timing.autoreverses);
+        assert_eq!(held.timing.repeat, Repeat::Forever);
+    }
+
+    #[test]
+    fn test_holds_without_autoreverse() {
+        let builder = CABasicAnimationBuilder::new(KeyPath::Opacity)
+            .values(0.0, 1.0)
+            .duration(ms(500))
+            .hold_at_end(ms(500));
+        let held = builder.held_keyframes().unwrap();
+        assert_eq!(held.values, [0.0, 1.0, 1.0]);
+        assert_times(&held.key_times, &[0.0, 0.5, 1.0]);
+        assert_eq!(held.easings, [Easing::InOut, Easing::Linear]);
+        assert_eq!(held.timing.duration, ms(1000));
+    }
+
+    #[test]
+    fn test_into_keyframes() {
+        let keyframes = breathe()
+            .hold_at_end(ms(200))
+            .repeat(Repeat::Count(2.5))
+            .speed(2.0)
+            .cumulative()
+            .into_keyframes()
+            .unwrap();
+        assert_eq!(keyframes.key_path, KeyPath::TransformScale);
+        assert_eq!(keyframes.values.len(), 4);
+        assert_times(&keyframes.key_times, &[0.0, 0.4, 0.6, 1.0]);
+        assert_eq!(keyframes.timing.duration, ms(1000));
+        assert_eq!(keyframes.timing.repeat, Repeat::Count(2.5));
+        assert_eq!(keyframes.timing.speed, 2.0);
+        assert!(keyframes.cumulative);
+
+        assert!(!breathe().has_holds());
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::Opacity)
+                .by(0.5)
+                .hold_at_end(ms(200))
+                .into_keyframes()
+                .err(),
+            Some(AnimationError::HoldWithoutValues)
+        );
+    }
+}
@@ -0,0 +1,355 @@
+//! Multi-value animations using `CAKeyframeAnimation`.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_foundation::{NSArray, NSNumber};
+use objc2_quartz_core::{CAKeyframeAnimation, CAMediaTimingFunction};
+
+use super::{
+    AnimationValue, CalculationMode, Callbacks, Easing, FillMode, KeyPath, Repeat, Timing,
+};
+
+/// Builder for configuring `CAKeyframeAnimation` instances.
+///
+/// Animates a property through a list of values. Without
+/// [`key_times`](Self::key_times), the values are spread evenly over the
+/// duration.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Drop, bounce twice, settle
+/// let anim = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+///     .values([300.0, 100.0, 160.0, 100.0, 120.0, 100.0])
+///     .key_times([0.0, 0.4, 0.55, 0.7, 0.85, 1.0])
+///     .duration(800.millis())
+///     .easing(Easing::Linear)
+///     .build();
+/// ```
+pub struct CAKeyframeAnimationBuilder {
+    pub(crate) key_path: KeyPath,
+    pub(crate) values: Vec<AnimationValue>,
+    pub(crate) key_times: Vec<f64>,
+    pub(crate) easings: Vec<Easing>,
+    pub(crate) calculation_mode: CalculationMode,
+    pub(crate) tensions: Vec<f64>,
+    pub(crate) continuities: Vec<f64>,
+    pub(crate) biases: Vec<f64>,
+    pub(crate) additive: bool,
+    pub(crate) cumulative: bool,
+    pub(crate) timing: Timing,
+    pub(crate) callbacks: Callbacks,
+}
+
+impl CAKeyframeAnimationBuilder {
+    /// Creates a new keyframe animation builder for the specified property.
+    #[must_use]
+    pub fn new(key_path: KeyPath) -> Self {
+        Self {
+            key_path,
+            values: Vec::new(),
+            key_times: Vec::new(),
+            easings: Vec::new(),
+            calculation_mode: CalculationMode::default(),
+            tensions: Vec::new(),
+            continuities: Vec::new(),
+            biases: Vec::new(),
+            additive: false,
+            cumulative: false,
+            timing: Timing::default(),
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    /// Sets the keyframe values.
+    #[must_use]
+    pub fn values(mut self, values: impl IntoIterator<Item = f64>) -> Self {
+        self.values = values.into_iter().map(AnimationValue::Scalar).collect();
+        self
+    }
+
+    /// Sets when each value is reached, as fractions of the duration.
+    ///
+    /// Needs one entry per value, starting at 0.0, ending at 1.0 and never
+    /// decreasing. With [`CalculationMode::Discrete`], give one entry more
+    /// than there are values: each value shows from its key time until the
+    /// next one.
+    #[must_use]
+    pub fn key_times(mut self, key_times: impl IntoIterator<Item = f64>) -> Self {
+        self.key_times = key_times.into_iter().collect();
+        self
+    }
+
+    /// Sets the easing of each segment between two values.
+    ///
+    /// Needs one entry less than there are values. Applied on top of the
+    /// overall [`easing`](Self::easing), which you usually want to set to
+    /// `Linear` when using this. Ignored by the discrete and paced modes.
+    #[must_use]
+    pub fn easings(mut self, easings: impl IntoIterator<Item = Easing>) -> Self {
+        self.easings = easings.into_iter().collect();
+        self
+    }
+
+    /// Sets how the animation moves between keyframes (default: `Linear`).
+    #[must_use]
+    pub fn calculation_mode(mut self, mode: CalculationMode) -> Self {
+        self.calculation_mode = mode;
+        self
+    }
+
+    /// Sets the tension of the curve at each keyframe, for the cubic modes.
+    ///
+    /// One entry per value, usually between -1.0 and 1.0. Positive values
+    /// tighten the curve towards straight segments, negative values make it
+    /// rounder. Missing entries are 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Smooth through the middle keyframe, sharp at the ends
+    /// builder
+    ///     .values([0.0, 80.0, 20.0])
+    ///     .calculation_mode(CalculationMode::Cubic)
+    ///     .tensions([1.0, -0.5, 1.0])
+    /// ```
+    #[must_use]
+    pub fn tensions(mut self, tensions: impl IntoIterator<Item = f64>) -> Self {
+        self.tensions = tensions.into_iter().collect();
+        self
+    }
+
+    /// Sets the continuity of the curve at each keyframe, for the cubic
+    /// modes.
+    ///
+    /// One entry per value, between -1.0 and 1.0. 0.0 keeps the motion
+    /// smooth; other values give the keyframe a corner, pointing inwards
+    /// when negative and outwards when positive. Missing entries are 0.0.
+    #[must_use]
+    pub fn continuities(mut self, continuities: impl IntoIterator<Item = f64>) -> Self {
+        self.continuities = continuities.into_iter().collect();
+        self
+    }
+
+    /// Sets the bias of the curve at each keyframe, for the cubic modes.
+    ///
+    /// One entry per value, between -1.0 and 1.0. Negative values make the
+    /// curve follow the next keyframe more closely, positive values the
+    /// previous one, e.g. to overshoot after it. Missing entries are 0.0.
+    #[must_use]
+    pub fn biases(mut self, biases: impl IntoIterator<Item = f64>) -> Self {
+        self.biases = biases.into_iter().collect();
+        self
+    }
+
+    /// Adds the animated values to the layer's value instead of replacing it.
+    #[must_use]
+    pub fn additive(mut self) -> Self {
+        self.additive = true;
+        self
+    }
+
+    /// Makes each repeat continue from where the previous one ended.
+    #[must_use]
+    pub fn cumulative(mut self) -> Self {
+        self.cumulative = true;
+        self
+    }
+
+    /// Sets the duration of one pass through all keyframes.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.timing.duration = duration;
+        self
+    }
+
+    /// Sets the easing curve applied to the whole pass.
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.timing.easing = easing;
+        self
+    }
+
+    /// Plays the keyframes forward, then back.
+    #[must_use]
+    pub fn autoreverses(mut self) -> Self {
+        self.timing.autoreverses = true;
+        self
+    }
+
+    /// Sets the repeat behavior.
+    #[must_use]
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.timing.repeat = repeat;
+        self
+    }
+
+    /// Sets the phase offset (fraction of the cycle to skip, 0.0 to 1.0).
+    #[must_use]
+    pub fn phase_offset(mut self, offset: f64) -> Self {
+        self.timing.phase_offset = offset;
+        self
+    }
+
+    /// Waits before starting the animation.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.timing.delay = delay;
+        self
+    }
+
+    /// Sets the playback rate (1.0 = normal).
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.timing.speed = speed;
+        self
+    }
+
+    /// Sets what the animation shows outside its active time.
+    #[must_use]
+    pub fn fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.timing.fill_mode = Some(fill_mode);
+        self
+    }
+
+    /// Opts in to snap-back behavior (remove animation on completion).
+    #[must_use]
+    pub fn remove_on_completion(mut self) -> Self {
+        self.timing.remove_on_completion = true;
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the animation starts.
+    #[must_use]
+    pub fn on_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.callbacks.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the animation stops.
+    ///
+    /// The closure receives `true` if the animation ran to completion.
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.callbacks.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Builds and returns the configured `CAKeyframeAnimation`.
+    ///
+    /// Under [`MotionPolicy::Reduced`](crate::settings::MotionPolicy::Reduced),
+    /// keyframes that move, scale or rotate the layer too far jump from
+    /// the first value to the last halfway through instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a callback was set and this is not called from the main
+    /// thread.
+    #[must_use]
+    pub fn build(mut self) -> Retained<CAKeyframeAnimation> {
+        self.reduce_motion();
+        let key_path_str = self.key_path.to_nsstring();
+        let anim = CAKeyframeAnimation::animationWithKeyPath(Some(&key_path_str));
+
+        let values: Vec<_> = self.values.iter().map(AnimationValue::to_object).collect();
+        let values = NSArray::from_retained_slice(&values);
+        // SAFETY: NSNumber and CGPath are valid keyframe value types.
+        unsafe {
+            anim.setValues(Some(&values));
+        }
+
+        if !self.key_times.is_empty() {
+            let key_times: Vec<_> = self
+                .key_times
+                .iter()
+                .map(|&time| NSNumber::new_f64(time))
+                .collect();
+            anim.setKeyTimes(Some(&NSArray::from_retained_slice(&key_times)));
+        }
+        if !self.easings.is_empty() {
+            let functions: Vec<Retained<CAMediaTimingFunction>> = self
+                .easings
+                .iter()
+                .map(|easing| easing.to_timing_function())
+                .collect();
+            anim.setTimingFunctions(Some(&NSArray::from_retained_slice(&functions)));
+        }
+
+        anim.setCalculationMode(self.calculation_mode.to_ca_mode());
+        let numbers = |values: &[f64]| {
+            let numbers: Vec<_> = values.iter().map(|&v| NSNumber::new_f64(v)).collect();
+            NSArray::from_retained_slice(&numbers)
+        };
+        if !self.tensions.is_empty() {
+            anim.setTensionValues(Some(&numbers(&self.tensions)));
+        }
+        if !self.continuities.is_empty() {
+            anim.setContinuityValues(Some(&numbers(&self.continuities)));
+        }
+        if !self.biases.is_empty() {
+            anim.setBiasValues(Some(&numbers(&self.biases)));
+        }
+        anim.setAdditive(self.additive);
+        anim.setCumulative(self.cumulative);
+
+        self.timing.apply(&anim);
+        self.callbacks.apply(&anim);
+
+        anim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::Opacity);
+        assert_eq!(builder.key_path, KeyPath::Opacity);
+        assert!(builder.values.is_empty());
+        assert!(builder.key_times.is_empty());
+        assert!(builder.easings.is_empty());
+        assert_eq!(builder.calculation_mode, CalculationMode::Linear);
+        assert!(builder.tensions.is_empty());
+        assert!(builder.continuities.is_empty());
+        assert!(builder.biases.is_empty());
+        assert!(!builder.additive);
+        assert_eq!(builder.timing, Timing::default());
+    }
+
+    #[test]
+    fn test_builder_chaining() {
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+            .values([0.0, 10.0, 0.0])
+            .key_times([0.0, 0.25, 1.0])
+            .easings([Easing::Out, Easing::In])
+            .additive()
+            .duration(Duration::from_secs(2))
+            .repeat(Repeat::Forever);
+        assert_eq!(
+            builder.values,
+            vec![
+                AnimationValue::Scalar(0.0),
+                AnimationValue::Scalar(10.0),
+                AnimationValue::Scalar(0.0)
+            ]
+        );
+        assert_eq!(builder.key_times, vec![0.0, 0.25, 1.0]);
+        assert_eq!(builder.easings, vec![Easing::Out, Easing::In]);
+        assert!(builder.additive);
+        assert_eq!(builder.timing.duration, Duration::from_secs(2));
+        assert_eq!(builder.timing.repeat, Repeat::Forever);
+
+        let builder = builder
+            .calculation_mode(CalculationMode::Cubic)
+            .tensions([0.5, 0.0, 0.5])
+            .continuities([0.0, -1.0, 0.0])
+            .biases([0.0, 0.25, 0.0]);
+        assert_eq!(builder.calculation_mode, CalculationMode::Cubic);
+        assert_eq!(builder.tensions, vec![0.5, 0.0, 0.5]);
+        assert_eq!(builder.continuities, vec![0.0, -1.0, 0.0]);
+        assert_eq!(builder.biases, vec![0.0, 0.25, 0.0]);
+    }
+}
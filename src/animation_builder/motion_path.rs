@@ -0,0 +1,383 @@
+//! Motion along a path using `CAKeyframeAnimation`.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_core_foundation::CGFloat;
+use objc2_core_graphics::CGPath;
+use objc2_quartz_core::{
+    kCAAnimationRotateAuto, kCAAnimationRotateAutoReverse, CAAnimationRotationMode,
+    CAKeyframeAnimation,
+};
+
+use super::{
+    CAKeyframeAnimationBuilder, CalculationMode, Callbacks, Easing, FillMode, KeyPath, Repeat,
+    Timing,
+};
+use crate::path::{BezierPath, PathMeasure};
+use crate::settings::{AnimationSettings, MotionPolicy};
+
+/// Points along the path checked against reduced motion.
+const PATH_SAMPLES: usize = 32;
+
+/// How a layer is rotated while it follows a path.
+///
+/// Maps to `rotationMode` on `CAKeyframeAnimation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RotationMode {
+    /// Keep the layer's own rotation (default).
+    #[default]
+    None,
+    /// Rotate the layer to face the direction of travel.
+    Auto,
+    /// Like `Auto`, but turned around by 180°.
+    AutoReverse,
+}
+
+impl RotationMode {
+    /// Returns the Core Animation rotation mode string, if any.
+    fn to_ca_rotation(self) -> Option<&'static CAAnimationRotationMode> {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                RotationMode::None => None,
+                RotationMode::Auto => Some(kCAAnimationRotateAuto),
+                RotationMode::AutoReverse => Some(kCAAnimationRotateAutoReverse),
+            }
+        }
+    }
+}
+
+/// Builder for animating `position` along a path.
+///
+/// Defaults differ from `CAKeyframeAnimation` in one place: motion is
+/// [`CalculationMode::Paced`], so the layer moves at constant speed no
+/// matter how the path's segments are distributed.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Orbit a circle, facing the direction of travel
+/// let anim = MotionPathBuilder::new()
+///     .path(&orbit)
+///     .rotation(RotationMode::Auto)
+///     .duration(2.seconds())
+///     .easing(Easing::Linear)
+///     .repeat(Repeat::Forever)
+///     .build();
+///
+/// // Travel only the second half of a curve
+/// let anim = MotionPathBuilder::new()
+///     .path(&curve)
+///     .start_offset(0.5)
+///     .end_offset(1.0)
+///     .build();
+/// ```
+pub struct MotionPathBuilder {
+    path: BezierPath,
+    rotation: RotationMode,
+    calculation_mode: CalculationMode,
+    start_offset: f64,
+    end_offset: f64,
+    timing: Timing,
+    callbacks: Callbacks,
+}
+
+impl MotionPathBuilder {
+    /// Creates a new motion path builder with an empty path.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            path: BezierPath::new(),
+            rotation: RotationMode::default(),
+            calculation_mode: CalculationMode::Paced,
+            start_offset: 0.0,
+            end_offset: 1.0,
+            timing: Timing::default(),
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    /// Sets the path to follow.
+    #[must_use]
+    pub fn path(mut self, path: &CGPath) -> Self {
+        self.path = BezierPath::from_cgpath(path);
+        self
+    }
+
+    /// Sets the path to follow from a [`BezierPath`].
+    #[must_use]
+    pub fn bezier_path(mut self, path: BezierPath) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Sets how the layer is rotated along the path.
+    #[must_use]
+    pub fn rotation(mut self, rotation: RotationMode) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets how time is distributed along the path.
+    ///
+    /// `Paced` (default) keeps the speed constant. `Linear` gives every
+    /// path segment the same time, so the layer speeds up on long segments.
+    #[must_use]
+    pub fn calculation_mode(mut self, mode: CalculationMode) -> Self {
+        self.calculation_mode = mode;
+        self
+    }
+
+    /// Sets where the motion starts, as a fraction of the path length.
+    ///
+    /// * `offset` - 0.0 = start of the path (default), 1.0 = end
+    ///
+    /// If the start offset is greater than the end offset, the layer
+    /// travels the path backwards.
+    #[must_use]
+    pub fn start_offset(mut self, offset: f64) -> Self {
+        self.start_offset = offset;
+        self
+    }
+
+    /// Sets where the motion ends, as a fraction of the path length.
+    ///
+    /// * `offset` - 0.0 = start of the path, 1.0 = end (default)
+    #[must_use]
+    pub fn end_offset(mut self, offset: f64) -> Self {
+        self.end_offset = offset;
+        self
+    }
+
+    /// Sets the duration of one pass along the path.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.timing.duration = duration;
+        self
+    }
+
+    /// Sets the easing curve applied to the whole pass.
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.timing.easing = easing;
+        self
+    }
+
+    /// Travels the path forward, then back.
+    #[must_use]
+    pub fn autoreverses(mut self) -> Self {
+        self.timing.autoreverses = true;
+        self
+    }
+
+    /// Sets the repeat behavior.
+    #[must_use]
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.timing.repeat = repeat;
+        self
+    }
+
+    /// Sets the phase offset (fraction of the cycle to skip, 0.0 to 1.0).
+    #[must_use]
+    pub fn phase_offset(mut self, offset: f64) -> Self {
+        self.timing.phase_offset = offset;
+        self
+    }
+
+    /// Opts in to snap-back behavior (remove animation on completion).
+    #[must_use]
+    pub fn remove_on_completion(mut self) -> Self {
+        self.timing.remove_on_completion = true;
+        self
+    }
+
+    /// Waits before starting the motion.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.timing.delay = delay;
+        self
+    }
+
+    /// Sets the playback rate (1.0 = normal).
+    #[must_use]
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.timing.speed = speed;
+        self
+    }
+
+    /// Sets what the motion shows outside its active time.
+    #[must_use]
+    pub fn fill_mode(mut self, fill_mode: FillMode) -> Self {
+        self.timing.fill_mode = Some(fill_mode);
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the motion starts.
+    #[must_use]
+    pub fn on_start(mut self, callback: impl FnMut() + 'static) -> Self {
+        self.callbacks.on_start = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a closure to run on the main thread when the motion stops.
+    ///
+    /// The closure receives `true` if the motion ran to completion.
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnMut(bool) + 'static) -> Self {
+        self.callbacks.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the part of the path the layer travels.
+    ///
+    /// The full path is used as-is; otherwise the section between the
+    /// offsets is cut out by arc length.
+    fn travel_path(&self) -> BezierPath {
+        let start = self.start_offset.clamp(0.0, 1.0);
+        let end = self.end_offset.clamp(0.0, 1.0);
+        if start == 0.0 && end == 1.0 {
+            return self.path.clone();
+        }
+
+        let measure = PathMeasure::new(&self.path);
+        let section = measure.extract(start * measure.length(), end * measure.length());
+        if start > end {
+            section.reversed()
+        } else {
+            section
+        }
+    }
+
+    /// Returns position keyframes spread evenly along `path`.
+    fn sampled_keyframes(path: &BezierPath) -> CAKeyframeAnimationBuilder {
+        let measure = PathMeasure::new(path);
+        let mut keyframes = CAKeyframeAnimationBuilder::new(KeyPath::Position);
+        keyframes.values = (0..=PATH_SAMPLES)
+            .map(|i| {
+                let distance = measure.length() * i as CGFloat / PATH_SAMPLES as CGFloat;
+                measure.position_at(distance).into()
+            })
+            .collect();
+        keyframes
+    }
+
+    /// Builds and returns the configured `CAKeyframeAnimation`.
+    ///
+    /// Under [`MotionPolicy::Reduced`], a path that strays too far jumps
+    /// from its start to its end halfway through instead, as
+    /// [`CAKeyframeAnimationBuilder::build`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a callback was set and this is not called from the main
+    /// thread.
+    #[must_use]
+    pub fn build(self) -> Retained<CAKeyframeAnimation> {
+        let travel = self.travel_path();
+        if AnimationSettings::motion_policy() == MotionPolicy::Reduced {
+            let mut keyframes = Self::sampled_keyframes(&travel);
+            if keyframes.large_motion().is_some() {
+                keyframes.timing = self.timing;
+                keyframes.callbacks = self.callbacks;
+                return keyframes.build();
+            }
+        }
+
+        let key_path_str = KeyPath::Position.to_nsstring();
+        let anim = CAKeyframeAnimation::animationWithKeyPath(Some(&key_path_str));
+
+        let path = travel.to_cgpath();
+        anim.setPath(Some(&path));
+        anim.setCalculationMode(self.calculation_mode.to_ca_mode());
+        anim.setRotationMode(self.rotation.to_ca_rotation());
+
+        self.timing.apply(&anim);
+        self.callbacks.apply(&anim);
+
+        anim
+    }
+}
+
+impl Default for MotionPathBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::PathElement;
+    use objc2_core_foundation::CGPoint;
+
+    fn line() -> BezierPath {
+        BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(100.0, 0.0))
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let builder = MotionPathBuilder::new();
+        assert!(builder.path.is_empty());
+        assert_eq!(builder.rotation, RotationMode::None);
+        assert_eq!(builder.calculation_mode, CalculationMode::Paced);
+        assert_eq!(builder.start_offset, 0.0);
+        assert_eq!(builder.end_offset, 1.0);
+        assert_eq!(builder.timing, Timing::default());
+    }
+
+    #[test]
+    fn test_full_path_is_unchanged() {
+        let builder = MotionPathBuilder::new().bezier_path(line());
+        assert_eq!(builder.travel_path(), line());
+    }
+
+    #[test]
+    fn test_offsets_cut_path() {
+        let builder = MotionPathBuilder::new()
+            .bezier_path(line())
+            .start_offset(0.25)
+            .end_offset(0.75);
+        let travel = builder.travel_path();
+        assert_eq!(
+            travel.elements()[0],
+            PathElement::MoveTo(CGPoint::new(25.0, 0.0))
+        );
+        assert!((PathMeasure::new(&travel).length() - 50.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reversed_offsets_travel_backwards() {
+        let builder = MotionPathBuilder::new()
+            .bezier_path(line())
+            .start_offset(1.0)
+            .end_offset(0.0);
+        let travel = builder.travel_path();
+        assert_eq!(
+            travel.elements()[0],
+            PathElement::MoveTo(CGPoint::new(100.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_sampled_keyframes_follow_the_path() {
+        let keyframes = MotionPathBuilder::sampled_keyframes(&line());
+        assert_eq!(keyframes.values.len(), PATH_SAMPLES + 1);
+        assert_eq!(keyframes.values[0], CGPoint::new(0.0, 0.0).into());
+        assert_eq!(
+            keyframes.values[PATH_SAMPLES],
+            CGPoint::new(100.0, 0.0).into()
+        );
+        assert!(keyframes.large_motion().is_some());
+
+        let nudge = BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(10.0, 0.0));
+        assert_eq!(
+            MotionPathBuilder::sampled_keyframes(&nudge).large_motion(),
+            None
+        );
+    }
+}
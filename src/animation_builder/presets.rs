@@ -0,0 +1,417 @@
+//! Ready-made attention and entrance effects.
+//!
+//! Each [`Preset`] is a small set of keyframe tracks, tuned to look right
+//! at the default settings and adjustable through [`PresetBuilder`]:
+//!
+//! ```ignore
+//! // Straight from a layer builder
+//! CAShapeLayerBuilder::new()
+//!     .circle(40.0)
+//!     .animate_preset("intro", Preset::BounceIn)
+//!     .build();
+//!
+//! // Tuned
+//! .animate_preset("error", Preset::Shake.intensity(2.0).duration(300.millis()))
+//!
+//! // As a plain animation
+//! let anim = Preset::Heartbeat.repeat(Repeat::Forever).build();
+//! layer.add_animation(&anim, "beat");
+//! ```
+//!
+//! Attention seekers (`Shake`, `Wiggle`, `Heartbeat`, `Pop`, `Jello`, `Tada`,
+//! `Flip`) start and end at the layer's resting state. Entrances
+//! (`BounceIn`, `FadeInUp`) start hidden and end visible.
+
+use std::f64::consts::{PI, TAU};
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_quartz_core::CAAnimation;
+
+use super::{CAAnimationGroupBuilder, Easing, FillMode, KeyPath, Repeat};
+
+/// A ready-made effect.
+///
+/// Converts into a [`PresetBuilder`] with default settings; call a setter
+/// such as [`intensity`](Self::intensity) to start tuning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// Quick horizontal shake, e.g. for a rejected input.
+    Shake,
+    /// Quick rotation back and forth.
+    Wiggle,
+    /// Two beats of growing and shrinking.
+    Heartbeat,
+    /// Grow in from small with a springy overshoot, fading in.
+    BounceIn,
+    /// Short bump in size.
+    Pop,
+    /// Fade in while rising into place.
+    FadeInUp,
+    /// Full turn around the vertical axis.
+    Flip,
+    /// Wobbly squash and stretch.
+    Jello,
+    /// Shrink, then wiggle while enlarged.
+    Tada,
+}
+
+impl Preset {
+    /// Default length of the effect.
+    pub fn default_duration(self) -> Duration {
+        let millis = match self {
+            Preset::Shake | Preset::Wiggle => 500,
+            Preset::Heartbeat => 1300,
+            Preset::BounceIn => 750,
+            Preset::Pop => 300,
+            Preset::FadeInUp => 500,
+            Preset::Flip => 1000,
+            Preset::Jello => 900,
+            Preset::Tada => 1000,
+        };
+        Duration::from_millis(millis)
+    }
+
+    /// Starts tuning with the given intensity. See [`PresetBuilder::intensity`].
+    #[must_use]
+    pub fn intensity(self, intensity: f64) -> PresetBuilder {
+        PresetBuilder::from(self).intensity(intensity)
+    }
+
+    /// Starts tuning with the given duration. See [`PresetBuilder::duration`].
+    #[must_use]
+    pub fn duration(self, duration: Duration) -> PresetBuilder {
+        PresetBuilder::from(self).duration(duration)
+    }
+
+    /// Starts tuning with the given repeat behavior.
+    #[must_use]
+    pub fn repeat(self, repeat: Repeat) -> PresetBuilder {
+        PresetBuilder::from(self).repeat(repeat)
+    }
+
+    /// Builds the effect with default settings.
+    #[must_use]
+    pub fn build(self) -> Retained<CAAnimation> {
+        PresetBuilder::from(self).build()
+    }
+
+    /// Keyframe tracks at intensity 1.0.
+    fn tracks(self) -> Vec<Track> {
+        let deg = PI / 180.0;
+        match self {
+            Preset::Shake => vec![Track::even(
+                KeyPath::PositionX,
+                [0.0, -10.0, 10.0, -10.0, 10.0, -6.0, 6.0, -2.0, 0.0],
+            )
+            .additive()],
+            Preset::Wiggle => vec![Track::even(
+                KeyPath::TransformRotation,
+                [0.0, -6.0, 6.0, -6.0, 6.0, -3.0, 0.0].map(|d| d * deg),
+            )
+            .additive()],
+            Preset::Heartbeat => vec![Track::new(
+                KeyPath::TransformScale,
+                [1.0, 1.3, 1.0, 1.3, 1.0, 1.0],
+                [0.0, 0.14, 0.28, 0.42, 0.7, 1.0],
+            )
+            .around(1.0)],
+            Preset::BounceIn => vec![
+                Track::new(
+                    KeyPath::TransformScale,
+                    [0.3, 1.1, 0.9, 1.03, 0.97, 1.0],
+                    [0.0, 0.2, 0.4, 0.6, 0.8, 1.0],
+                )
+                .around(1.0),
+                Track::new(KeyPath::Opacity, [0.0, 1.0, 1.0], [0.0, 0.6, 1.0]).fixed(),
+            ],
+            Preset::Pop => vec![Track::even(KeyPath::TransformScale, [1.0, 1.2, 1.0]).around(1.0)],
+            Preset::FadeInUp => vec![
+                Track::even(KeyPath::Opacity, [0.0, 1.0]).fixed(),
+                // Core Animation's y axis points up, so rising starts below.
+                Track::even(KeyPath::PositionY, [-20.0, 0.0]).additive(),
+            ],
+            Preset::Flip => {
+                vec![
+                    Track::even(KeyPath::Custom("transform.rotation.y"), [0.0, PI, TAU])
+                        .fixed()
+                        .additive(),
+                ]
+            }
+            Preset::Jello => vec![
+                Track::new(
+                    KeyPath::TransformScaleX,
+                    [1.0, 1.25, 0.75, 1.15, 0.95, 1.05, 1.0],
+                    [0.0, 0.3, 0.4, 0.5, 0.65, 0.75, 1.0],
+                )
+                .around(1.0),
+                Track::new(
+                    KeyPath::TransformScaleY,
+                    [1.0, 0.75, 1.25, 0.85, 1.05, 0.95, 1.0],
+                    [0.0, 0.3, 0.4, 0.5, 0.65, 0.75, 1.0],
+                )
+                .around(1.0),
+            ],
+            Preset::Tada => vec![
+                Track::even(
+                    KeyPath::TransformScale,
+                    [1.0, 0.9, 0.9, 1.1, 1.1, 1.1, 1.1, 1.1, 1.1, 1.1, 1.0],
+                )
+                .around(1.0),
+                Track::even(
+                    KeyPath::TransformRotation,
+                    [0.0, -3.0, -3.0, 3.0, -3.0, 3.0, -3.0, 3.0, -3.0, 3.0, 0.0].map(|d| d * deg),
+                )
+                .additive(),
+            ],
+        }
+    }
+}
+
+/// A tunable [`Preset`].
+///
+/// # Examples
+///
+/// ```ignore
+/// let anim = Preset::Pop
+///     .intensity(1.5)
+///     .duration(200.millis())
+///     .delay(1.seconds())
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresetBuilder {
+    preset: Preset,
+    intensity: f64,
+    duration: Option<Duration>,
+    repeat: Repeat,
+    delay: Duration,
+}
+
+impl From<Preset> for PresetBuilder {
+    fn from(preset: Preset) -> Self {
+        Self {
+            preset,
+            intensity: 1.0,
+            duration: None,
+            repeat: Repeat::Once,
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+impl PresetBuilder {
+    /// Scales how far the effect moves away from the resting state.
+    ///
+    /// * `intensity` - 1.0 = default, 2.0 = twice as strong, 0.5 = subtler
+    #[must_use]
+    pub fn intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets the length of one run of the effect.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Sets the repeat behavior.
+    #[must_use]
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Waits before starting the effect.
+    ///
+    /// Entrance effects hold their first frame while waiting, so the layer
+    /// stays hidden until it enters.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Keyframe tracks with the intensity applied.
+    fn tracks(&self) -> Vec<Track> {
+        self.preset
+            .tracks()
+            .into_iter()
+            .map(|track| track.scaled(self.intensity))
+            .collect()
+    }
+
+    /// Builds the effect as a single animation.
+    ///
+    /// Effects with several tracks become a `CAAnimationGroup`.
+    #[must_use]
+    pub fn build(self) -> Retained<CAAnimation> {
+        let duration = self.duration.unwrap_or(self.preset.default_duration());
+        let group = self
+            .tracks()
+            .into_iter()
+            .fold(CAAnimationGroupBuilder::new(), |group, track| {
+                group.keyframes(track.key_path, |k| {
+                    let k = k
+                        .values(track.values)
+                        .key_times(track.key_times)
+                        .easing(Easing::Linear)
+                        .easings(vec![Easing::InOut; track.segments])
+                        .duration(duration);
+                    if track.additive {
+                        k.additive()
+                    } else {
+                        k
+                    }
+                })
+            })
+            .duration(duration)
+            .repeat(self.repeat);
+
+        let group = if self.delay.is_zero() {
+            group
+        } else {
+            group.delay(self.delay).fill_mode(FillMode::Both)
+        };
+        Retained::into_super(group.build())
+    }
+}
+
+/// Keyframes for one property of a preset.
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    key_path: KeyPath,
+    values: Vec<f64>,
+    key_times: Vec<f64>,
+    segments: usize,
+    additive: bool,
+    /// Value that intensity scales away from, or `None` if the values are
+    /// not scaled at all.
+    rest: Option<f64>,
+}
+
+impl Track {
+    fn new<const N: usize>(key_path: KeyPath, values: [f64; N], key_times: [f64; N]) -> Self {
+        Self {
+            key_path,
+            values: values.to_vec(),
+            key_times: key_times.to_vec(),
+            segments: N.saturating_sub(1),
+            additive: false,
+            rest: Some(0.0),
+        }
+    }
+
+    /// A track whose values are evenly spaced in time.
+    fn even<const N: usize>(key_path: KeyPath, values: [f64; N]) -> Self {
+        let last = N.saturating_sub(1).max(1) as f64;
+        let key_times = std::array::from_fn(|i| i as f64 / last);
+        Self::new(key_path, values, key_times)
+    }
+
+    fn additive(mut self) -> Self {
+        self.additive = true;
+        self
+    }
+
+    /// Scale intensity around `rest` rather than zero.
+    fn around(mut self, rest: f64) -> Self {
+        self.rest = Some(rest);
+        self
+    }
+
+    /// Keep values as they are regardless of intensity.
+    fn fixed(mut self) -> Self {
+        self.rest = None;
+        self
+    }
+
+    fn scaled(mut self, intensity: f64) -> Self {
+        if let Some(rest) = self.rest {
+            for value in &mut self.values {
+                *value = rest + (*value - rest) * intensity;
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Preset; 9] = [
+        Preset::Shake,
+        Preset::Wiggle,
+        Preset::Heartbeat,
+        Preset::BounceIn,
+        Preset::Pop,
+        Preset::FadeInUp,
+        Preset::Flip,
+        Preset::Jello,
+        Preset::Tada,
+    ];
+
+    #[test]
+    fn test_tracks_are_well_formed() {
+        for preset in ALL {
+            for track in preset.tracks() {
+                assert_eq!(track.values.len(), track.key_times.len(), "{preset:?}");
+                assert_eq!(track.segments + 1, track.values.len(), "{preset:?}");
+                assert_eq!(track.key_times.first(), Some(&0.0), "{preset:?}");
+                assert!(
+                    track.key_times.windows(2).all(|w| w[0] <= w[1]),
+                    "{preset:?}"
+                );
+                assert_eq!(track.key_times.last(), Some(&1.0), "{preset:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_attention_seekers_return_to_rest() {
+        for preset in [
+            Preset::Shake,
+            Preset::Wiggle,
+            Preset::Heartbeat,
+            Preset::Pop,
+            Preset::Jello,
+            Preset::Tada,
+        ] {
+            for track in preset.tracks() {
+                let rest = if track.additive { 0.0 } else { 1.0 };
+                assert_eq!(track.values.first(), Some(&rest), "{preset:?}");
+                assert_eq!(track.values.last(), Some(&rest), "{preset:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_intensity_scales_around_rest() {
+        let pop = PresetBuilder::from(Preset::Pop).intensity(2.0).tracks();
+        assert_eq!(pop[0].values, vec![1.0, 1.4, 1.0]);
+
+        let shake = Preset::Shake.intensity(0.5).tracks();
+        assert_eq!(shake[0].values[1], -5.0);
+
+        // Opacity is not scaled, so entrances still end fully visible
+        let bounce = Preset::BounceIn.intensity(3.0).tracks();
+        assert_eq!(bounce[1].values, vec![0.0, 1.0, 1.0]);
+        assert_eq!(bounce[0].values.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn test_builder_settings() {
+        let builder = Preset::Tada
+            .duration(Duration::from_secs(2))
+            .repeat(Repeat::Times(2))
+            .delay(Duration::from_millis(100));
+        assert_eq!(builder.preset, Preset::Tada);
+        assert_eq!(builder.intensity, 1.0);
+        assert_eq!(builder.duration, Some(Duration::from_secs(2)));
+        assert_eq!(builder.repeat, Repeat::Times(2));
+        assert_eq!(builder.delay, Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,245 @@
+//! Crossfades and jumps in place of large motion, for
+//! [`MotionPolicy::Reduced`].
+
+use std::f64::consts::PI;
+
+use objc2::rc::Retained;
+use objc2_core_foundation::CGPoint;
+
+use super::{
+    AnimationValue, CAAnimationGroupBuilder, CABasicAnimationBuilder, CAKeyframeAnimationBuilder,
+    CalculationMode, Easing, KeyPath, SampleValue, Timing,
+};
+use crate::settings::{AnimationSettings, MotionPolicy};
+
+/// Largest move, in points, that reduced motion still animates.
+const MAX_DISTANCE: f64 = 20.0;
+
+/// Largest change of scale that reduced motion still animates.
+const MAX_SCALE: f64 = 0.1;
+
+/// Largest rotation, in radians, that reduced motion still animates.
+const MAX_ROTATION: f64 = PI / 12.0;
+
+impl KeyPath {
+    /// How far the property may change before reduced motion replaces the
+    /// animation with a crossfade, or `None` if it does not move the layer.
+    pub(crate) fn motion_limit(self) -> Option<f64> {
+        match self {
+            KeyPath::Position | KeyPath::PositionX | KeyPath::PositionY => Some(MAX_DISTANCE),
+            KeyPath::TransformScale | KeyPath::TransformScaleX | KeyPath::TransformScaleY => {
+                Some(MAX_SCALE)
+            }
+            KeyPath::TransformRotation => Some(MAX_ROTATION),
+            _ => None,
+        }
+    }
+}
+
+/// The widest gap between any two of `values`, or `None` if they are not
+/// all scalars or all points.
+fn spread(values: &[AnimationValue]) -> Option<f64> {
+    let mut spread = 0.0_f64;
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            let distance = match (a, b) {
+                (AnimationValue::Scalar(a), AnimationValue::Scalar(b)) => a.distance(*b),
+                (AnimationValue::Point(a), AnimationValue::Point(b)) => a.distance(*b),
+                _ => return None,
+            };
+            spread = spread.max(distance);
+        }
+    }
+    Some(spread)
+}
+
+impl CABasicAnimationBuilder {
+    /// The from and to values, if the animation moves, scales or rotates
+    /// the layer further than reduced motion allows.
+    ///
+    /// Animations from the current value are left alone, since how far
+    /// they go is not known up front.
+    pub(crate) fn large_motion(&self) -> Option<(AnimationValue, AnimationValue)> {
+        let limit = self.key_path.motion_limit()?;
+        let from = self.from_value.clone()?;
+        let to = match (&self.to_value, &self.by_value, &from) {
+            (Some(to), _, _) => to.clone(),
+            (None, Some(AnimationValue::Scalar(by)), AnimationValue::Scalar(from)) => {
+                AnimationValue::Scalar(from + by)
+            }
+            (None, Some(AnimationValue::Point(by)), AnimationValue::Point(from)) => {
+                AnimationValue::Point(CGPoint::new(from.x + by.x, from.y + by.y))
+            }
+            _ => return None,
+        };
+        let distance = match (&from, &to) {
+            (AnimationValue::Scalar(from), AnimationValue::Scalar(to)) => from.distance(*to),
+            (AnimationValue::Point(from), AnimationValue::Point(to)) => from.distance(*to),
+            _ => return None,
+        };
+        (distance > limit).then_some((from, to))
+    }
+
+    /// Under [`MotionPolicy::Reduced`], makes large motion jump straight to
+    /// its end value.
+    ///
+    /// A `CABasicAnimation` cannot hold the crossfade that
+    /// [`build_animation`](Self::build_animation) uses, so this is what
+    /// [`try_build`](Self::try_build) falls back to.
+    pub(crate) fn reduce_motion(&mut self) {
+        if AnimationSettings::motion_policy() != MotionPolicy::Reduced {
+            return;
+        }
+        if let Some((_, to)) = self.large_motion() {
+            self.from_value = Some(to.clone());
+            self.to_value = Some(to);
+            self.by_value = None;
+        }
+    }
+
+    /// A crossfade with this builder's timing: the layer fades out, jumps
+    /// from `from` to `to` while invisible, and fades back in.
+    pub(crate) fn crossfade(
+        self,
+        from: AnimationValue,
+        to: AnimationValue,
+    ) -> CAAnimationGroupBuilder {
+        let duration = self.timing.duration;
+        let mut jump = CAKeyframeAnimationBuilder::new(self.key_path)
+            .key_times([0.0, 0.5, 1.0])
+            .calculation_mode(CalculationMode::Discrete)
+            .duration(duration)
+            .easing(Easing::Linear);
+        jump.values = vec![from, to];
+        jump.additive = self.additive;
+
+        // Additive, so the layer's own opacity is kept outside the dip.
+        let fade = CAKeyframeAnimationBuilder::new(KeyPath::Opacity)
+            .values([0.0, -1.0, 0.0])
+            .key_times([0.0, 0.5, 1.0])
+            .easings([Easing::InOut, Easing::InOut])
+            .additive()
+            .duration(duration)
+            .easing(Easing::Linear);
+
+        let mut group = CAAnimationGroupBuilder::new()
+            .animation(Retained::into_super(Retained::into_super(jump.build())))
+            .animation(Retained::into_super(Retained::into_super(fade.build())));
+        group.timing = Timing {
+            easing: Easing::Linear,
+            ..self.timing
+        };
+        group.callbacks = self.callbacks;
+        group
+    }
+}
+
+impl CAKeyframeAnimationBuilder {
+    /// The first and last values, if the keyframes move, scale or rotate
+    /// the layer further than reduced motion allows anywhere along the way.
+    ///
+    /// Discrete keyframes jump rather than move, so they are left alone.
+    pub(crate) fn large_motion(&self) -> Option<(AnimationValue, AnimationValue)> {
+        if self.calculation_mode == CalculationMode::Discrete {
+            return None;
+        }
+        let limit = self.key_path.motion_limit()?;
+        let first = self.values.first()?.clone();
+        let last = self.values.last()?.clone();
+        (spread(&self.values)? > limit).then_some((first, last))
+    }
+
+    /// Under [`MotionPolicy::Reduced`], replaces large motion with a jump
+    /// from the first value to the last halfway through.
+    pub(crate) fn reduce_motion(&mut self) {
+        if AnimationSettings::motion_policy() != MotionPolicy::Reduced {
+            return;
+        }
+        if let Some((first, last)) = self.large_motion() {
+            self.values = vec![first, last];
+            self.key_times = vec![0.0, 0.5, 1.0];
+            self.calculation_mode = CalculationMode::Discrete;
+            self.easings.clear();
+            self.tensions.clear();
+            self.continuities.clear();
+            self.biases.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_motion() {
+        let slide = CABasicAnimationBuilder::new(KeyPath::PositionX).values(0.0, 300.0);
+        assert_eq!(
+            slide.large_motion(),
+            Some((AnimationValue::Scalar(0.0), AnimationValue::Scalar(300.0)))
+        );
+        let nudge = CABasicAnimationBuilder::new(KeyPath::PositionX).values(100.0, 110.0);
+        assert_eq!(nudge.large_motion(), None);
+
+        let pulse = CABasicAnimationBuilder::new(KeyPath::TransformScale).values(0.85, 1.15);
+        assert!(pulse.large_motion().is_some());
+        let breathe = CABasicAnimationBuilder::new(KeyPath::TransformScale).values(1.0, 1.05);
+        assert_eq!(breathe.large_motion(), None);
+
+        let spin = CABasicAnimationBuilder::new(KeyPath::TransformRotation).values(0.0, PI * 2.0);
+        assert!(spin.large_motion().is_some());
+
+        let mut fly = CABasicAnimationBuilder::new(KeyPath::Position);
+        fly.from_value = Some(CGPoint::new(0.0, 0.0).into());
+        fly.to_value = Some(CGPoint::new(30.0, 40.0).into());
+        assert!(fly.large_motion().is_some());
+        fly.to_value = Some(CGPoint::new(3.0, 4.0).into());
+        assert_eq!(fly.large_motion(), None);
+    }
+
+    #[test]
+    fn test_large_motion_by_and_unknown() {
+        let mut by = CABasicAnimationBuilder::new(KeyPath::PositionY).by(50.0);
+        by.from_value = Some(AnimationValue::Scalar(10.0));
+        assert_eq!(
+            by.large_motion(),
+            Some((AnimationValue::Scalar(10.0), AnimationValue::Scalar(60.0)))
+        );
+
+        // From the current value, or not motion at all
+        let current = CABasicAnimationBuilder::new(KeyPath::PositionY).from_current_to(500.0);
+        assert_eq!(current.large_motion(), None);
+        let fade = CABasicAnimationBuilder::new(KeyPath::Opacity).values(0.0, 1.0);
+        assert_eq!(fade.large_motion(), None);
+    }
+
+    #[test]
+    fn test_keyframe_large_motion() {
+        // Judged by the whole range, not just the ends
+        let bounce =
+            CAKeyframeAnimationBuilder::new(KeyPath::PositionY).values([100.0, 300.0, 100.0]);
+        assert_eq!(
+            bounce.large_motion(),
+            Some((AnimationValue::Scalar(100.0), AnimationValue::Scalar(100.0)))
+        );
+        let shake =
+            CAKeyframeAnimationBuilder::new(KeyPath::PositionX).values([0.0, -10.0, 10.0, 0.0]);
+        assert_eq!(shake.large_motion(), None);
+
+        let mut path = CAKeyframeAnimationBuilder::new(KeyPath::Position);
+        path.values = vec![
+            CGPoint::new(0.0, 0.0).into(),
+            CGPoint::new(30.0, 40.0).into(),
+            CGPoint::new(0.0, 5.0).into(),
+        ];
+        assert!(path.large_motion().is_some());
+
+        let jump = CAKeyframeAnimationBuilder::new(KeyPath::PositionX)
+            .values([0.0, 300.0])
+            .key_times([0.0, 0.5, 1.0])
+            .calculation_mode(CalculationMode::Discrete);
+        assert_eq!(jump.large_motion(), None);
+        let fade = CAKeyframeAnimationBuilder::new(KeyPath::Opacity).values([0.0, 1.0, 0.0]);
+        assert_eq!(fade.large_motion(), None);
+    }
+}
@@ -0,0 +1,328 @@
+//! Keyframes sampled from a Rust function of time.
+
+use std::time::Duration;
+
+use objc2_core_foundation::CGPoint;
+
+use super::{AnimationValue, CAKeyframeAnimationBuilder, CalculationMode, Easing};
+use crate::color::Color;
+
+/// Pieces the duration is split into before refining, so that periodic
+/// functions are not mistaken for straight lines.
+const INITIAL_SEGMENTS: u32 = 8;
+
+/// How often each initial piece may be halved.
+const MAX_DEPTH: u32 = 10;
+
+/// Points [`keyframes_fn`](CAKeyframeAnimationBuilder::keyframes_fn)
+/// probes to find how far apart the function's values get. A prime, so a
+/// function that repeats on the power-of-two grid the refining checks
+/// still shows its range.
+const PROBE_SEGMENTS: u32 = 251;
+
+/// Tolerance of [`keyframes_fn`](CAKeyframeAnimationBuilder::keyframes_fn),
+/// as a fraction of how far apart the function's values get.
+const RELATIVE_TOLERANCE: f64 = 0.001;
+
+/// A value that keyframes can be sampled into: numbers, points and colors.
+pub trait SampleValue: Copy + Into<AnimationValue> {
+    /// Blends linearly from `self` (0.0) to `other` (1.0).
+    fn lerp(self, other: Self, fraction: f64) -> Self;
+
+    /// How far apart two values are, in the units of the tolerance.
+    fn distance(self, other: Self) -> f64;
+}
+
+impl SampleValue for f64 {
+    fn lerp(self, other: Self, fraction: f64) -> Self {
+        self + (other - self) * fraction
+    }
+
+    fn distance(self, other: Self) -> f64 {
+        (other - self).abs()
+    }
+}
+
+impl SampleValue for CGPoint {
+    fn lerp(self, other: Self, fraction: f64) -> Self {
+        CGPoint::new(
+            SampleValue::lerp(self.x, other.x, fraction),
+            SampleValue::lerp(self.y, other.y, fraction),
+        )
+    }
+
+    /// The straight-line distance.
+    fn distance(self, other: Self) -> f64 {
+        (other.x - self.x).hypot(other.y - self.y)
+    }
+}
+
+impl SampleValue for Color {
+    fn lerp(self, other: Self, fraction: f64) -> Self {
+        Color::rgba(
+            SampleValue::lerp(self.r, other.r, fraction),
+            SampleValue::lerp(self.g, other.g, fraction),
+            SampleValue::lerp(self.b, other.b, fraction),
+            SampleValue::lerp(self.a, other.a, fraction),
+        )
+    }
+
+    /// The largest difference of any component, alpha included.
+    fn distance(self, other: Self) -> f64 {
+        [
+            self.r.distance(other.r),
+            self.g.distance(other.g),
+            self.b.distance(other.b),
+            self.a.distance(other.a),
+        ]
+        .into_iter()
+        .fold(0.0, f64::max)
+    }
+}
+
+/// Samples `f` from 0 to `duration` seconds so that straight lines between
+/// the samples stay within `tolerance` of it.
+///
+/// Each piece is checked at every eighth of its length and halved until it
+/// fits, up to [`MAX_DEPTH`] times. Returns the sample times and values,
+/// starting at 0 and ending at `duration`.
+pub(crate) fn sample<T: SampleValue>(
+    duration: f64,
+    tolerance: f64,
+    f: impl Fn(f64) -> T,
+) -> Vec<(f64, T)> {
+    let mut samples = vec![(0.0, f(0.0))];
+    if duration <= 0.0 {
+        return samples;
+    }
+    for index in 1..=INITIAL_SEGMENTS {
+        let start = samples[samples.len() - 1];
+        let time = duration * f64::from(index) / f64::from(INITIAL_SEGMENTS);
+        refine(
+            &f,
+            start,
+            (time, f(time)),
+            tolerance,
+            MAX_DEPTH,
+            &mut samples,
+        );
+    }
+    samples
+}
+
+/// Adds the samples after `start` up to and including `end`.
+fn refine<T: SampleValue>(
+    f: &impl Fn(f64) -> T,
+    start: (f64, T),
+    end: (f64, T),
+    tolerance: f64,
+    depth: u32,
+    samples: &mut Vec<(f64, T)>,
+) {
+    // A curve can cross the straight line anywhere, so it is checked at
+    // every eighth rather than only in the middle, and against a smaller
+    // tolerance to leave room for what lies between the checks.
+    let fits = (1..8)
+        .map(|eighth| f64::from(eighth) / 8.0)
+        .all(|fraction| {
+            let time = start.0 + (end.0 - start.0) * fraction;
+            f(time).distance(start.1.lerp(end.1, fraction)) <= tolerance * 0.75
+        });
+    if fits || depth == 0 {
+        samples.push(end);
+        return;
+    }
+    let time = (start.0 + end.0) / 2.0;
+    let middle = (time, f(time));
+    refine(f, start, middle, tolerance, depth - 1, samples);
+    refine(f, middle, end, tolerance, depth - 1, samples);
+}
+
+/// The default tolerance for `f`, relative to how far apart its values get.
+fn default_tolerance<T: SampleValue>(duration: f64, f: &impl Fn(f64) -> T) -> f64 {
+    let values: Vec<T> = (0..=PROBE_SEGMENTS)
+        .map(|index| f(duration * f64::from(index) / f64::from(PROBE_SEGMENTS)))
+        .collect();
+    let spread = values
+        .iter()
+        .flat_map(|a| values.iter().map(|b| a.distance(*b)))
+        .fold(0.0, f64::max);
+    spread * RELATIVE_TOLERANCE
+}
+
+impl CAKeyframeAnimationBuilder {
+    /// Sets the keyframes by sampling a function of time.
+    ///
+    /// `f` receives the time in seconds, from 0 to `duration`, and returns
+    /// a number, a `CGPoint` or a [`Color`]. Samples are added where the
+    /// function curves, until straight lines between them stay within 0.1%
+    /// of the range the function covers. Use
+    /// [`keyframes_fn_within`](Self::keyframes_fn_within) to give the
+    /// tolerance yourself.
+    ///
+    /// Also sets the duration, and the overall easing to `Linear` so each
+    /// value is reached at the time it was sampled at.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use std::f64::consts::TAU;
+    ///
+    /// // Wobble left and right twice a second
+    /// CAKeyframeAnimationBuilder::new(KeyPath::PositionX)
+    ///     .keyframes_fn(1.seconds(), |t| 100.0 + (t * 2.0 * TAU).sin() * 20.0)
+    ///     .repeat(Repeat::Forever)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn keyframes_fn<T: SampleValue>(self, duration: Duration, f: impl Fn(f64) -> T) -> Self {
+        let tolerance = default_tolerance(duration.as_secs_f64(), &f);
+        self.keyframes_fn_within(duration, tolerance, f)
+    }
+
+    /// Sets the keyframes by sampling a function of time, keeping straight
+    /// lines between the samples within `tolerance` of it.
+    ///
+    /// The tolerance is in the units of the values: points for positions
+    /// (measured as a distance), and the largest difference of any
+    /// component for colors.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Orbit a center, never more than half a point off the circle
+    /// CAKeyframeAnimationBuilder::new(KeyPath::Position)
+    ///     .keyframes_fn_within(2.seconds(), 0.5, |t| {
+    ///         let angle = t * PI;
+    ///         CGPoint::new(200.0 + 80.0 * angle.cos(), 200.0 + 80.0 * angle.sin())
+    ///     })
+    /// ```
+    #[must_use]
+    pub fn keyframes_fn_within<T: SampleValue>(
+        mut self,
+        duration: Duration,
+        tolerance: f64,
+        f: impl Fn(f64) -> T,
+    ) -> Self {
+        let secs = duration.as_secs_f64();
+        let samples = sample(secs, tolerance.max(0.0), f);
+        self.key_times = samples
+            .iter()
+            .map(|(time, _)| if secs > 0.0 { time / secs } else { 0.0 })
+            .collect();
+        self.values = samples.into_iter().map(|(_, value)| value.into()).collect();
+        self.easings.clear();
+        self.calculation_mode = CalculationMode::Linear;
+        self.timing.duration = duration;
+        self.timing.easing = Easing::Linear;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::*;
+    use crate::animation_builder::KeyPath;
+    use crate::random::Rng;
+
+    /// The largest distance between `f` and the straight lines through the
+    /// samples, checked at random times.
+    fn max_error<T: SampleValue>(samples: &[(f64, T)], f: impl Fn(f64) -> T, rng: &mut Rng) -> f64 {
+        let end = samples[samples.len() - 1].0;
+        (0..2000)
+            .map(|_| {
+                let time = rng.next_f64() * end;
+                let index = samples
+                    .windows(2)
+                    .position(|pair| time <= pair[1].0)
+                    .unwrap();
+                let (start, stop) = (samples[index], samples[index + 1]);
+                let fraction = (time - start.0) / (stop.0 - start.0);
+                f(time).distance(start.1.lerp(stop.1, fraction))
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_samples_stay_within_tolerance() {
+        let mut rng = Rng::new(46);
+        for _ in 0..50 {
+            let amplitude = 1.0 + rng.next_f64() * 100.0;
+            let frequency = 0.25 + rng.next_f64() * 4.0;
+            let duration = 0.5 + rng.next_f64() * 3.0;
+            let tolerance = 0.01 + rng.next_f64();
+            let f = |t: f64| (t * frequency * TAU).sin() * amplitude + t * t;
+
+            let samples = sample(duration, tolerance, f);
+            assert_eq!(samples[0].0, 0.0);
+            assert_eq!(samples[samples.len() - 1].0, duration);
+            assert!(samples.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            let error = max_error(&samples, f, &mut rng);
+            assert!(error <= tolerance, "{error} > {tolerance}");
+        }
+    }
+
+    #[test]
+    fn test_sampling_adapts_to_curvature() {
+        let line = sample(1.0, 0.001, |t| 3.0 * t - 1.0);
+        assert_eq!(line.len(), INITIAL_SEGMENTS as usize + 1);
+
+        let coarse = sample(1.0, 1.0, |t| (t * TAU).sin() * 50.0);
+        let fine = sample(1.0, 0.01, |t| (t * TAU).sin() * 50.0);
+        assert!(fine.len() > coarse.len() * 4);
+
+        // A jump cannot be approximated, so refining stops at the depth limit
+        let step = sample(1.0, 0.1, |t| if t < 0.3 { 0.0 } else { 1.0 });
+        assert!(step.len() <= (INITIAL_SEGMENTS as usize) * 2 + MAX_DEPTH as usize * 2);
+    }
+
+    #[test]
+    fn test_points_and_colors() {
+        let mut rng = Rng::new(7);
+        let circle = |t: f64| CGPoint::new(50.0 * (t * TAU).cos(), 50.0 * (t * TAU).sin());
+        let samples = sample(1.0, 0.5, circle);
+        assert!(max_error(&samples, circle, &mut rng) <= 0.5);
+
+        let fade = |t: f64| Color::RED.lerp(Color::BLUE.with_alpha(0.0), t * t / 4.0);
+        let samples = sample(2.0, 0.01, fade);
+        assert_eq!(samples[samples.len() - 1].1, Color::BLUE.with_alpha(0.0));
+        assert!(max_error(&samples, fade, &mut rng) <= 0.01);
+    }
+
+    #[test]
+    fn test_keyframes_fn() {
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+            .easing(Easing::InOut)
+            .keyframes_fn(Duration::from_secs(2), |t| (t * TAU).sin() * 20.0);
+        assert_eq!(builder.timing.duration, Duration::from_secs(2));
+        assert_eq!(builder.timing.easing, Easing::Linear);
+        assert_eq!(builder.values.len(), builder.key_times.len());
+        assert_eq!(builder.key_times[0], 0.0);
+        assert_eq!(builder.key_times[builder.key_times.len() - 1], 1.0);
+        assert_eq!(builder.values[0], AnimationValue::Scalar(0.0));
+
+        // The default tolerance is relative, so scaling the function up
+        // does not add keyframes.
+        let scaled = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+            .keyframes_fn(Duration::from_secs(2), |t| (t * TAU).sin() * 1280.0);
+        assert_eq!(scaled.values.len(), builder.values.len());
+
+        // Zero at every point of the initial grid, but not a flat line.
+        let aliased = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+            .keyframes_fn(Duration::from_secs(1), |t| (t * 8.0 * TAU).sin());
+        assert!(aliased.values.len() > 8 * INITIAL_SEGMENTS as usize);
+        assert!(aliased.values.len() < 1000);
+
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::Position).keyframes_fn_within(
+            Duration::from_secs(1),
+            1.0,
+            |t| CGPoint::new(t, 0.0),
+        );
+        assert_eq!(
+            builder.values[1],
+            AnimationValue::Point(CGPoint::new(0.125, 0.0))
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Animated content changes using `CATransition`.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_quartz_core::{
+    kCATransitionFade, kCATransitionFromBottom, kCATransitionFromLeft, kCATransitionFromRight,
+    kCATransitionFromTop, kCATransitionMoveIn, kCATransitionPush, kCATransitionReveal,
+    CAMediaTiming, CATransition, CATransitionSubtype, CATransitionType,
+};
+
+use super::{Easing, NO_MOTION_DURATION};
+use crate::settings::{slow_down, AnimationSettings, MotionPolicy};
+
+/// How the old content makes way for the new one.
+///
+/// # Examples
+///
+/// ```ignore
+/// // Crossfade a label's text
+/// label.transition(TransitionKind::Fade);
+/// label.setString(Some(&NSString::from_str("Done")));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TransitionKind {
+    /// The new content fades in over the old one (default).
+    #[default]
+    Fade,
+    /// The new content slides in and pushes the old content out.
+    Push,
+    /// The new content slides in over the old content.
+    MoveIn,
+    /// The old content slides out, uncovering the new content.
+    Reveal,
+}
+
+impl TransitionKind {
+    /// Returns the Core Animation transition type string.
+    fn to_ca_type(self) -> &'static CATransitionType {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                TransitionKind::Fade => kCATransitionFade,
+                TransitionKind::Push => kCATransitionPush,
+                TransitionKind::MoveIn => kCATransitionMoveIn,
+                TransitionKind::Reveal => kCATransitionReveal,
+            }
+        }
+    }
+}
+
+/// The side a [`TransitionKind::Push`], [`MoveIn`](TransitionKind::MoveIn)
+/// or [`Reveal`](TransitionKind::Reveal) moves from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionDirection {
+    /// Moves in from the left edge.
+    FromLeft,
+    /// Moves in from the right edge.
+    FromRight,
+    /// Moves in from the top edge.
+    FromTop,
+    /// Moves in from the bottom edge.
+    FromBottom,
+}
+
+impl TransitionDirection {
+    /// Returns the Core Animation transition subtype string.
+    fn to_ca_subtype(self) -> &'static CATransitionSubtype {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                TransitionDirection::FromLeft => kCATransitionFromLeft,
+                TransitionDirection::FromRight => kCATransitionFromRight,
+                TransitionDirection::FromTop => kCATransitionFromTop,
+                TransitionDirection::FromBottom => kCATransitionFromBottom,
+            }
+        }
+    }
+}
+
+/// Builder for configuring `CATransition` instances.
+///
+/// A transition animates the next change of a layer's contents, such as
+/// new text, a new background or added and removed sublayers, instead of
+/// swapping it abruptly. Add it to the layer, then make the change in the
+/// same run loop turn, or use
+/// [`CALayerExt::transition`](crate::CALayerExt::transition).
+///
+/// # Examples
+///
+/// ```ignore
+/// // Slide the next page in from the right
+/// let transition = TransitionBuilder::new(TransitionKind::Push)
+///     .direction(TransitionDirection::FromRight)
+///     .duration(350.millis())
+///     .easing(Easing::Out)
+///     .build();
+///
+/// page.addAnimation_forKey(&transition, Some(ns_string!("transition")));
+/// page.setContents(Some(&next_image));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionBuilder {
+    kind: TransitionKind,
+    direction: Option<TransitionDirection>,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl TransitionBuilder {
+    /// Creates a transition of the given kind, lasting 250 ms.
+    #[must_use]
+    pub fn new(kind: TransitionKind) -> Self {
+        Self {
+            kind,
+            direction: None,
+            duration: Duration::from_millis(250),
+            easing: Easing::default(),
+        }
+    }
+
+    /// Sets the side the new content comes from.
+    ///
+    /// Ignored by [`TransitionKind::Fade`]. Without a direction, Core
+    /// Animation moves from the left.
+    #[must_use]
+    pub fn direction(mut self, direction: TransitionDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Sets how long the transition takes.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the easing curve (default: `InOut`).
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Builds and returns the configured `CATransition`.
+    ///
+    /// Unlike the other builders, the transition is removed once it
+    /// finishes, since the layer already holds the new content.
+    ///
+    /// Under [`MotionPolicy::Reduced`], sliding transitions become fades;
+    /// under [`MotionPolicy::None`], the content changes right away.
+    #[must_use]
+    pub fn build(self) -> Retained<CATransition> {
+        let policy = AnimationSettings::motion_policy();
+        let (kind, duration) = match policy {
+            MotionPolicy::Full => (self.kind, self.duration),
+            MotionPolicy::Reduced => (TransitionKind::Fade, self.duration),
+            MotionPolicy::None => (TransitionKind::Fade, NO_MOTION_DURATION),
+        };
+        let transition = CATransition::animation();
+        transition.setType(kind.to_ca_type());
+        if let (Some(direction), MotionPolicy::Full) = (self.direction, policy) {
+            transition.setSubtype(Some(direction.to_ca_subtype()));
+        }
+        transition.setDuration(duration.as_secs_f64());
+        slow_down(&transition);
+        transition.setTimingFunction(Some(&self.easing.to_timing_function()));
+        transition
+    }
+}
+
+impl From<TransitionKind> for TransitionBuilder {
+    fn from(kind: TransitionKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let builder = TransitionBuilder::from(TransitionKind::Fade);
+        assert_eq!(builder.kind, TransitionKind::Fade);
+        assert_eq!(builder.direction, None);
+        assert_eq!(builder.duration, Duration::from_millis(250));
+        assert_eq!(builder.easing, Easing::InOut);
+
+        let builder = TransitionBuilder::new(TransitionKind::Push)
+            .direction(TransitionDirection::FromBottom)
+            .duration(Duration::from_millis(400))
+            .easing(Easing::Out);
+        assert_eq!(builder.kind, TransitionKind::Push);
+        assert_eq!(builder.direction, Some(TransitionDirection::FromBottom));
+        assert_eq!(builder.duration, Duration::from_millis(400));
+        assert_eq!(builder.easing, Easing::Out);
+    }
+}
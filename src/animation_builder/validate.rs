@@ -0,0 +1,400 @@
+//! Checks that catch configurations Core Animation silently accepts.
+
+use std::fmt;
+
+use super::{
+    AnimationValue, CABasicAnimationBuilder, CAKeyframeAnimationBuilder, KeyPath, Repeat, Timing,
+};
+
+/// Why an animation builder cannot build a sensible animation.
+///
+/// Returned by [`CABasicAnimationBuilder::try_build`],
+/// [`CABasicAnimationBuilder::validate`] and
+/// [`CAKeyframeAnimationBuilder::validate`]. Core Animation accepts all of
+/// these and then shows nothing, jumps, or plays with other values than
+/// asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationError {
+    /// The duration is zero, so the animation jumps straight to its end.
+    ZeroDuration,
+    /// The phase offset is not within 0.0–1.0.
+    PhaseOffsetOutOfRange(f64),
+    /// `Repeat::Times(0)`, which Core Animation plays once anyway.
+    ZeroRepeatCount,
+    /// A `Repeat::Count` that is not a positive, finite number.
+    InvalidRepeatCount(f32),
+    /// `Repeat::For(Duration::ZERO)`, which Core Animation plays once.
+    ZeroRepeatDuration,
+    /// The speed is zero, infinite or NaN. A speed of zero never plays.
+    InvalidSpeed(f32),
+    /// Neither a to value nor a relative value is set, or a keyframe
+    /// animation has no values.
+    MissingValues,
+    /// A value is infinite or NaN.
+    NonFiniteValue(f64),
+    /// A hold is set, but from and to are not both numbers.
+    HoldWithoutValues,
+    /// A hold is set, which a `CABasicAnimation` cannot play.
+    HoldNeedsKeyframes,
+    /// A value does not have the type the key path animates.
+    WrongValueType {
+        /// The animated property.
+        key_path: KeyPath,
+        /// The type the property has, e.g. `"a number"`.
+        expected: &'static str,
+        /// The type that was given.
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationError::ZeroDuration => f.write_str("duration must be greater than zero"),
+            AnimationError::PhaseOffsetOutOfRange(offset) => {
+                write!(f, "phase offset must be between 0.0 and 1.0, got {offset}")
+            }
+            AnimationError::ZeroRepeatCount => {
+                f.write_str("repeat count must be at least 1, got Repeat::Times(0)")
+            }
+            AnimationError::InvalidRepeatCount(count) => {
+                write!(f, "repeat count must be a positive number, got {count}")
+            }
+            AnimationError::ZeroRepeatDuration => {
+                f.write_str("repeat duration must be greater than zero")
+            }
+            AnimationError::InvalidSpeed(speed) => {
+                write!(f, "speed must be a finite, non-zero number, got {speed}")
+            }
+            AnimationError::MissingValues => {
+                f.write_str("no values to animate; set `values`, `by` or `from_current_to`")
+            }
+            AnimationError::NonFiniteValue(value) => {
+                write!(f, "values must be finite numbers, got {value}")
+            }
+            AnimationError::HoldWithoutValues => {
+                f.write_str("holds need numeric from and to values, set with `values`")
+            }
+            AnimationError::HoldNeedsKeyframes => {
+                f.write_str("holds need a keyframe animation, use `build_animation`")
+            }
+            AnimationError::WrongValueType {
+                key_path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` animates {expected}, got {found}",
+                key_path.as_str()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+impl KeyPath {
+    /// The type of value the property has, or `None` when it is not
+    /// checked.
+    ///
+    /// Custom key paths can have any type. Sizes and rectangles, as in
+    /// `shadowOffset` and `bounds`, have no [`AnimationValue`], so those
+    /// key paths are left unchecked too.
+    fn value_type(self) -> Option<&'static str> {
+        match self {
+            KeyPath::Position => Some("a point"),
+            KeyPath::BackgroundColor | KeyPath::BorderColor => Some("a color"),
+            KeyPath::Path => Some("a path"),
+            KeyPath::ShadowOffset | KeyPath::Bounds | KeyPath::Custom(_) => None,
+            _ => Some("a number"),
+        }
+    }
+}
+
+impl AnimationValue {
+    /// The type of this value, as named in [`AnimationError::WrongValueType`].
+    fn value_type(&self) -> &'static str {
+        match self {
+            AnimationValue::Scalar(_) => "a number",
+            AnimationValue::Path(_) => "a path",
+            AnimationValue::Point(_) => "a point",
+            AnimationValue::Color(_) => "a color",
+        }
+    }
+}
+
+impl Timing {
+    /// Checks the settings shared by all builders.
+    pub(crate) fn validate(&self) -> Result<(), AnimationError> {
+        if self.duration.is_zero() {
+            return Err(AnimationError::ZeroDuration);
+        }
+        if !(0.0..=1.0).contains(&self.phase_offset) {
+            return Err(AnimationError::PhaseOffsetOutOfRange(self.phase_offset));
+        }
+        match self.repeat {
+            Repeat::Times(0) => return Err(AnimationError::ZeroRepeatCount),
+            Repeat::Count(count) if !(count.is_finite() && count > 0.0) => {
+                return Err(AnimationError::InvalidRepeatCount(count));
+            }
+            Repeat::For(duration) if duration.is_zero() => {
+                return Err(AnimationError::ZeroRepeatDuration);
+            }
+            _ => {}
+        }
+        if !self.speed.is_finite() || self.speed == 0.0 {
+            return Err(AnimationError::InvalidSpeed(self.speed));
+        }
+        Ok(())
+    }
+}
+
+impl CABasicAnimationBuilder {
+    /// Checks the configuration without creating a Core Animation object.
+    ///
+    /// Runs the same checks as [`try_build`](Self::try_build), so it also
+    /// works in tests that never touch the main thread.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let result = CABasicAnimationBuilder::new(KeyPath::Opacity)
+    ///     .values(0.0, 1.0)
+    ///     .duration(Duration::ZERO)
+    ///     .validate();
+    ///
+    /// assert_eq!(result, Err(AnimationError::ZeroDuration));
+    /// ```
+    pub fn validate(&self) -> Result<(), AnimationError> {
+        self.timing.validate()?;
+        if self.to_value.is_none() && self.by_value.is_none() {
+            return Err(AnimationError::MissingValues);
+        }
+        if self.has_holds() && self.held_keyframes().is_none() {
+            return Err(AnimationError::HoldWithoutValues);
+        }
+
+        let values = [&self.from_value, &self.to_value, &self.by_value];
+        values
+            .into_iter()
+            .flatten()
+            .try_for_each(|value| check_value(self.key_path, value))
+    }
+}
+
+impl CAKeyframeAnimationBuilder {
+    /// Checks the configuration without creating a Core Animation object.
+    ///
+    /// Catches missing keyframes, infinite or NaN values and values that
+    /// do not fit the key path, such as numbers for `position`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let result = CAKeyframeAnimationBuilder::new(KeyPath::Position)
+    ///     .values([0.0, 1.0])
+    ///     .validate();
+    ///
+    /// assert!(matches!(result, Err(AnimationError::WrongValueType { .. })));
+    /// ```
+    pub fn validate(&self) -> Result<(), AnimationError> {
+        self.timing.validate()?;
+        if self.values.is_empty() {
+            return Err(AnimationError::MissingValues);
+        }
+        self.values
+            .iter()
+            .try_for_each(|value| check_value(self.key_path, value))
+    }
+}
+
+/// Checks that `value` is finite and has the type `key_path` animates.
+fn check_value(key_path: KeyPath, value: &AnimationValue) -> Result<(), AnimationError> {
+    let coordinates = match value {
+        AnimationValue::Scalar(number) => vec![*number],
+        AnimationValue::Point(point) => vec![point.x, point.y],
+        AnimationValue::Path(_) | AnimationValue::Color(_) => Vec::new(),
+    };
+    if let Some(number) = coordinates.into_iter().find(|number| !number.is_finite()) {
+        return Err(AnimationError::NonFiniteValue(number));
+    }
+    match key_path.value_type() {
+        Some(expected) if expected != value.value_type() => Err(AnimationError::WrongValueType {
+            key_path,
+            expected,
+            found: value.value_type(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::path::BezierPath;
+
+    fn fade() -> CABasicAnimationBuilder {
+        CABasicAnimationBuilder::new(KeyPath::Opacity).values(0.0, 1.0)
+    }
+
+    #[test]
+    fn test_valid_configurations() {
+        assert_eq!(fade().validate(), Ok(()));
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::PositionX)
+                .by(20.0)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::TransformScale)
+                .from_current_to(1.2)
+                .phase_offset(1.0)
+                .repeat(Repeat::Forever)
+                .speed(-1.0)
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(fade().repeat(Repeat::Count(0.5)).validate(), Ok(()));
+        assert_eq!(
+            fade()
+                .autoreverses()
+                .hold_at_end(Duration::from_millis(300))
+                .validate(),
+            Ok(())
+        );
+        // Custom key paths accept any value type
+        let mut custom = CABasicAnimationBuilder::new(KeyPath::Custom("sublayers.dot.path"));
+        custom.to_value = Some(AnimationValue::Path(BezierPath::new()));
+        assert_eq!(custom.validate(), Ok(()));
+        // Sizes and rectangles cannot be checked, so any value passes
+        for key_path in [KeyPath::ShadowOffset, KeyPath::Bounds] {
+            assert_eq!(
+                CABasicAnimationBuilder::new(key_path)
+                    .values(0.0, 1.0)
+                    .validate(),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_timing_errors() {
+        assert_eq!(
+            fade().duration(Duration::ZERO).validate(),
+            Err(AnimationError::ZeroDuration)
+        );
+        assert_eq!(
+            fade().phase_offset(1.5).validate(),
+            Err(AnimationError::PhaseOffsetOutOfRange(1.5))
+        );
+        assert_eq!(
+            fade().phase_offset(-0.25).validate(),
+            Err(AnimationError::PhaseOffsetOutOfRange(-0.25))
+        );
+        assert_eq!(
+            fade().repeat(Repeat::Times(0)).validate(),
+            Err(AnimationError::ZeroRepeatCount)
+        );
+        assert_eq!(
+            fade().repeat(Repeat::Count(-1.5)).validate(),
+            Err(AnimationError::InvalidRepeatCount(-1.5))
+        );
+        assert!(fade().repeat(Repeat::Count(f32::NAN)).validate().is_err());
+        assert_eq!(
+            fade().repeat(Repeat::For(Duration::ZERO)).validate(),
+            Err(AnimationError::ZeroRepeatDuration)
+        );
+        assert_eq!(
+            fade().speed(f32::INFINITY).validate(),
+            Err(AnimationError::InvalidSpeed(f32::INFINITY))
+        );
+        assert_eq!(
+            fade().speed(0.0).validate(),
+            Err(AnimationError::InvalidSpeed(0.0))
+        );
+        assert!(fade().phase_offset(f64::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn test_value_errors() {
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::Opacity).validate(),
+            Err(AnimationError::MissingValues)
+        );
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::Opacity)
+                .values(0.0, f64::INFINITY)
+                .validate(),
+            Err(AnimationError::NonFiniteValue(f64::INFINITY))
+        );
+
+        let error = CABasicAnimationBuilder::new(KeyPath::Path)
+            .values(0.0, 1.0)
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AnimationError::WrongValueType {
+                key_path: KeyPath::Path,
+                expected: "a path",
+                found: "a number",
+            }
+        );
+        assert_eq!(error.to_string(), "`path` animates a path, got a number");
+
+        assert_eq!(
+            CABasicAnimationBuilder::new(KeyPath::Opacity)
+                .from_current_to(1.0)
+                .hold_at_start(Duration::from_millis(100))
+                .validate(),
+            Err(AnimationError::HoldWithoutValues)
+        );
+
+        let mut morph = CABasicAnimationBuilder::new(KeyPath::TransformScale);
+        morph.to_value = Some(AnimationValue::Path(BezierPath::new()));
+        assert_eq!(
+            morph.validate().unwrap_err().to_string(),
+            "`transform.scale` animates a number, got a path"
+        );
+        assert!(matches!(
+            CABasicAnimationBuilder::new(KeyPath::Position)
+                .values(0.0, 1.0)
+                .validate(),
+            Err(AnimationError::WrongValueType {
+                expected: "a point",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_keyframe_errors() {
+        let wobble = || CAKeyframeAnimationBuilder::new(KeyPath::TransformRotation);
+        assert_eq!(wobble().values([0.0, 0.2, 0.0]).validate(), Ok(()));
+        assert_eq!(wobble().validate(), Err(AnimationError::MissingValues));
+        assert!(matches!(
+            wobble().values([0.0, f64::NAN, 0.0]).validate(),
+            Err(AnimationError::NonFiniteValue(value)) if value.is_nan()
+        ));
+        assert_eq!(
+            wobble()
+                .values([0.0, 1.0])
+                .duration(Duration::ZERO)
+                .validate(),
+            Err(AnimationError::ZeroDuration)
+        );
+        assert!(matches!(
+            CAKeyframeAnimationBuilder::new(KeyPath::Position)
+                .values([0.0, 1.0])
+                .validate(),
+            Err(AnimationError::WrongValueType {
+                expected: "a point",
+                found: "a number",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+//! Looping noise baked into keyframes, for idle motion that looks alive.
+
+use std::time::Duration;
+
+use objc2_core_foundation::CGPoint;
+
+use super::{CAKeyframeAnimationBuilder, Repeat};
+use crate::noise::Noise;
+
+/// Wiggles per loop; enough that the repetition is hard to spot.
+const LOOP_WIGGLES: u32 = 8;
+
+/// Octaves of noise, for a slow drift with some finer detail on top.
+const OCTAVES: u32 = 2;
+
+/// How closely the keyframes follow the noise, relative to the amplitude.
+const TOLERANCE: f64 = 0.01;
+
+/// Seed offset for the vertical channel of a point wiggle.
+const Y_CHANNEL: u64 = 0x5851_F42D_4C95_7F2D;
+
+impl CAKeyframeAnimationBuilder {
+    /// Wiggles the property around its own value with smooth noise.
+    ///
+    /// The value drifts up to `amplitude` away from the layer's value,
+    /// changing direction about `frequency` times a second. The same
+    /// `seed` always gives the same motion, so give each layer its own
+    /// seed to keep them from moving in step.
+    ///
+    /// The keyframes cover a seamless loop of 8 wiggles and repeat
+    /// forever. This sets the duration to that loop, the animation to be
+    /// [additive](Self::additive) and the repeat to `Forever`; a different
+    /// duration set afterwards stretches the motion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frequency` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // A hovering icon that bobs up and down
+    /// CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+    ///     .wiggle(0.8, 4.0, 1)
+    ///     .build();
+    ///
+    /// // A flickering neon sign
+    /// CAKeyframeAnimationBuilder::new(KeyPath::Opacity)
+    ///     .wiggle(6.0, 0.15, 2)
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn wiggle(self, frequency: f64, amplitude: f64, seed: u64) -> Self {
+        let noise = at_rest(Noise::new(seed));
+        self.bake(frequency, amplitude, move |x| noise(x) * amplitude)
+    }
+
+    /// Wiggles a point property, such as `position`, around its own value
+    /// with smooth noise.
+    ///
+    /// Moves up to `amplitude` away in x and in y, each driven by its own
+    /// noise, and otherwise works like [`wiggle`](Self::wiggle).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frequency` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Floating bubbles, each drifting its own way
+    /// for (index, bubble) in bubbles.iter().enumerate() {
+    ///     let float = CAKeyframeAnimationBuilder::new(KeyPath::Position)
+    ///         .wiggle_point(0.5, 6.0, index as u64)
+    ///         .build();
+    ///     bubble.addAnimation_forKey(&float, Some(ns_string!("float")));
+    /// }
+    /// ```
+    #[must_use]
+    pub fn wiggle_point(self, frequency: f64, amplitude: f64, seed: u64) -> Self {
+        let x_noise = at_rest(Noise::new(seed));
+        let y_noise = at_rest(Noise::new(seed ^ Y_CHANNEL));
+        self.bake(frequency, amplitude, move |x| {
+            CGPoint::new(x_noise(x) * amplitude, y_noise(x) * amplitude)
+        })
+    }
+
+    /// Samples `noise`, which takes the position in wiggles, into one loop.
+    fn bake<T: super::SampleValue>(
+        self,
+        frequency: f64,
+        amplitude: f64,
+        noise: impl Fn(f64) -> T,
+    ) -> Self {
+        assert!(
+            frequency.is_finite() && frequency > 0.0,
+            "wiggle frequency must be positive, got {frequency}"
+        );
+        let duration = Duration::from_secs_f64(f64::from(LOOP_WIGGLES) / frequency);
+        self.keyframes_fn_within(duration, amplitude.abs() * TOLERANCE, |t| {
+            noise(t * frequency)
+        })
+        .additive()
+        .repeat(Repeat::Forever)
+    }
+}
+
+/// Shifts looping `noise` to start at zero, so the wiggle starts on the
+/// layer's own value without a jump.
+///
+/// The shifted noise can reach further than 1.0 from zero; where it does,
+/// it is scaled back so the wiggle stays within its amplitude.
+fn at_rest(noise: Noise) -> impl Fn(f64) -> f64 {
+    const STEPS: u32 = 1024;
+    let noise = noise.looping(LOOP_WIGGLES);
+    let value = move |x: f64| noise.fractal(x, OCTAVES);
+    let rest = value(0.0);
+    let peak = (0..STEPS)
+        .map(|step| f64::from(LOOP_WIGGLES) * f64::from(step) / f64::from(STEPS))
+        .map(|x| (value(x) - rest).abs())
+        .fold(1.0, f64::max);
+    move |x| (value(x) - rest) / peak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation_builder::{AnimationValue, KeyPath};
+
+    fn scalars(builder: &CAKeyframeAnimationBuilder) -> Vec<f64> {
+        builder
+            .values
+            .iter()
+            .map(|value| match value {
+                AnimationValue::Scalar(value) => *value,
+                other => panic!("expected a number, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wiggle() {
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::PositionY).wiggle(2.0, 5.0, 42);
+        assert_eq!(builder.timing.duration, Duration::from_secs(4));
+        assert_eq!(builder.timing.repeat, Repeat::Forever);
+        assert!(builder.additive);
+
+        let values = scalars(&builder);
+        assert!(values.len() > 16);
+        assert!(values.iter().all(|value| value.abs() <= 5.0));
+        // Starts and ends on the layer's own value, so the loop is seamless
+        assert_eq!(values[0], 0.0);
+        assert!(values[values.len() - 1].abs() < 1e-9);
+        assert!(values.iter().any(|value| value.abs() > 1.0));
+
+        let same = CAKeyframeAnimationBuilder::new(KeyPath::PositionY).wiggle(2.0, 5.0, 42);
+        assert_eq!(scalars(&same), values);
+        let other = CAKeyframeAnimationBuilder::new(KeyPath::PositionY).wiggle(2.0, 5.0, 43);
+        assert_ne!(scalars(&other), values);
+    }
+
+    #[test]
+    fn test_wiggle_moves_at_whole_wiggles() {
+        for seed in 0..10 {
+            let eval = CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+                .wiggle(1.0, 5.0, seed)
+                .evaluator();
+            let at_whole_wiggles: Vec<f64> = (1..LOOP_WIGGLES)
+                .map(|wiggle| {
+                    let progress = f64::from(wiggle) / f64::from(LOOP_WIGGLES);
+                    match eval.value_at_progress(progress) {
+                        Some(AnimationValue::Scalar(value)) => value,
+                        other => panic!("expected a number, got {other:?}"),
+                    }
+                })
+                .collect();
+            assert!(
+                at_whole_wiggles.iter().any(|value| value.abs() > 0.5),
+                "{at_whole_wiggles:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wiggle_point() {
+        let builder = CAKeyframeAnimationBuilder::new(KeyPath::Position).wiggle_point(0.5, 6.0, 3);
+        assert_eq!(builder.timing.duration, Duration::from_secs(16));
+        let points: Vec<CGPoint> = builder
+            .values
+            .iter()
+            .map(|value| match value {
+                AnimationValue::Point(point) => *point,
+                other => panic!("expected a point, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(points[0], CGPoint::new(0.0, 0.0));
+        // The two axes move independently
+        assert!(points.iter().any(|point| (point.x - point.y).abs() > 1.0));
+        assert!(points
+            .iter()
+            .all(|point| point.x.abs() <= 6.0 && point.y.abs() <= 6.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "wiggle frequency must be positive")]
+    fn test_wiggle_rejects_zero_frequency() {
+        let _ = CAKeyframeAnimationBuilder::new(KeyPath::Opacity).wiggle(0.0, 1.0, 0);
+    }
+}
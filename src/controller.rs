@@ -0,0 +1,385 @@
+//! Playback control for running animations.
+//!
+//! An [`AnimationController`] drives the clock of a layer, and with it every
+//! animation on the layer and its sublayers: pause, resume, scrub and change
+//! speed after the animations have been added.
+//!
+//! ```ignore
+//! let mut controller = window.animation_controller();
+//!
+//! controller.pause();
+//! controller.seek_progress(0.5);  // Jump to the middle and stay there
+//! controller.set_rate(0.25);      // Quarter speed from here on
+//! controller.resume();
+//! ```
+//!
+//! The controller works through the layer's own `speed`, `timeOffset` and
+//! `beginTime`, so animations themselves are left untouched.
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2::Message;
+use objc2_quartz_core::{CACurrentMediaTime, CALayer, CAMediaTiming};
+
+use crate::settings::recorded_multiplier;
+use crate::timeline::active_duration;
+
+/// Handle for pausing, resuming, seeking and retiming a layer subtree.
+///
+/// Obtained from [`CALayerExt::animation_controller`](crate::CALayerExt::animation_controller)
+/// or [`Window::animation_controller`](crate::Window::animation_controller).
+/// See the [module docs](self) for an example.
+#[derive(Debug, Clone)]
+pub struct AnimationController {
+    layer: Retained<CALayer>,
+    rate: f32,
+}
+
+impl AnimationController {
+    /// Creates a controller for `layer` and its sublayers.
+    ///
+    /// The playback rate starts at the layer's current speed, or 1.0 if the
+    /// layer is paused. On a layer that runs at the global
+    /// [speed](crate::settings::AnimationSettings::apply_speed), such as a
+    /// window container, the rate is relative to that speed, so 1.0 keeps
+    /// slow motion.
+    pub fn new(layer: &CALayer) -> Self {
+        Self {
+            layer: layer.retain(),
+            rate: rate_of(layer.speed(), base_speed(layer)),
+        }
+    }
+
+    /// Returns the controlled layer.
+    pub fn layer(&self) -> &CALayer {
+        &self.layer
+    }
+
+    /// Freezes all animations at their current state.
+    pub fn pause(&self) {
+        set_clock_speed(&self.layer, 0.0);
+    }
+
+    /// Continues from where [`pause`](Self::pause) stopped, at the current
+    /// rate.
+    pub fn resume(&self) {
+        set_clock_speed(&self.layer, self.rate * base_speed(&self.layer));
+    }
+
+    /// Returns `true` while paused.
+    pub fn is_paused(&self) -> bool {
+        self.layer.speed() == 0.0
+    }
+
+    /// Returns the playback rate used while playing.
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Sets the playback rate, without a jump.
+    ///
+    /// * `rate` - 1.0 = normal, 2.0 = twice as fast, 0.5 = half speed
+    ///
+    /// While paused, the new rate takes effect on [`resume`](Self::resume).
+    /// On a layer that runs at the global speed, the rate multiplies it.
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+        if !self.is_paused() {
+            self.resume();
+        }
+    }
+
+    /// Returns how far the animations have played, from the start of the
+    /// earliest one.
+    pub fn current_time(&self) -> Duration {
+        let clock = LayerClock::of(&self.layer);
+        let elapsed = clock.local_time(parent_time(&self.layer)) - self.extent().origin();
+        Duration::from_secs_f64(elapsed.max(0.0))
+    }
+
+    /// Returns the time from the start of the earliest animation to the end
+    /// of the latest one.
+    ///
+    /// Animations that repeat forever count one cycle. Animations added in
+    /// the current run loop pass only count once Core Animation has started
+    /// them.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.extent().length())
+    }
+
+    /// Jumps to `time` after the start of the earliest animation.
+    ///
+    /// Keeps playing from there, or stays there while paused.
+    pub fn seek(&self, time: Duration) {
+        let target = self.extent().origin() + time.as_secs_f64();
+        retime(&self.layer, |clock, parent| {
+            clock.retimed(parent, target, clock.speed)
+        });
+    }
+
+    /// Jumps to a fraction of [`duration`](Self::duration).
+    ///
+    /// * `progress` - 0.0 = start, 1.0 = end
+    pub fn seek_progress(&self, progress: f64) {
+        let extent = self.extent();
+        let target = extent.origin() + extent.length() * progress.clamp(0.0, 1.0);
+        retime(&self.layer, |clock, parent| {
+            clock.retimed(parent, target, clock.speed)
+        });
+    }
+
+    /// Span of all animations in the subtree, in the layer's time space.
+    fn extent(&self) -> Extent {
+        let mut extent = Extent::default();
+        self.collect(&self.layer, &mut extent);
+        extent
+    }
+
+    fn collect(&self, layer: &CALayer, extent: &mut Extent) {
+        for key in layer.animationKeys().into_iter().flatten() {
+            // SAFETY: The key was just returned by the same layer.
+            let Some(anim) = (unsafe { layer.animationForKey(&key) }) else {
+                continue;
+            };
+            let begin = self
+                .layer
+                .convertTime_fromLayer(anim.beginTime(), Some(layer));
+            let length = active_duration(
+                anim.duration(),
+                anim.repeatCount(),
+                anim.repeatDuration(),
+                anim.autoreverses(),
+                anim.speed(),
+            );
+            extent.include(begin, length.as_secs_f64());
+        }
+        // SAFETY: The sublayers array is not mutated while iterating.
+        if let Some(sublayers) = unsafe { layer.sublayers() } {
+            for sublayer in sublayers.iter() {
+                self.collect(&sublayer, extent);
+            }
+        }
+    }
+}
+
+/// The global speed `layer` runs at on top of its playback rate, or 1.0.
+fn base_speed(layer: &CALayer) -> f32 {
+    recorded_multiplier(layer).unwrap_or(1.0)
+}
+
+/// The playback rate of a layer running at `speed`, relative to `base`.
+///
+/// A paused layer resumes at the base speed.
+fn rate_of(speed: f32, base: f32) -> f32 {
+    if speed == 0.0 {
+        1.0
+    } else {
+        speed / base
+    }
+}
+
+/// Runs `layer`'s clock at `speed` from now on, carrying on from its
+/// current local time.
+///
+/// Animations already on the layer, and delays already resolved against
+/// its clock, keep their place.
+pub(crate) fn set_clock_speed(layer: &CALayer, speed: f32) {
+    retime(layer, |clock, parent| {
+        clock.retimed(parent, clock.local_time(parent), speed)
+    });
+}
+
+/// Applies a new clock to `layer`, computed from its current one.
+fn retime(layer: &CALayer, f: impl FnOnce(LayerClock, f64) -> LayerClock) {
+    let clock = f(LayerClock::of(layer), parent_time(layer));
+    layer.setBeginTime(clock.begin_time);
+    layer.setSpeed(clock.speed);
+    layer.setTimeOffset(clock.time_offset);
+}
+
+/// Current time in the superlayer's time space, where the layer's
+/// `beginTime` lives.
+fn parent_time(layer: &CALayer) -> f64 {
+    let now = CACurrentMediaTime();
+    match layer.superlayer() {
+        Some(parent) => parent.convertTime_fromLayer(now, None),
+        None => now,
+    }
+}
+
+/// The timing properties that make up a layer's clock.
+///
+/// A layer's local time is `(parent - begin_time) * speed + time_offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LayerClock {
+    begin_time: f64,
+    speed: f32,
+    time_offset: f64,
+}
+
+impl LayerClock {
+    fn of(layer: &CALayer) -> Self {
+        Self {
+            begin_time: layer.beginTime(),
+            speed: layer.speed(),
+            time_offset: layer.timeOffset(),
+        }
+    }
+
+    /// Local time when the parent's time is `parent`.
+    fn local_time(&self, parent: f64) -> f64 {
+        (parent - self.begin_time) * f64::from(self.speed) + self.time_offset
+    }
+
+    /// A clock that shows `local` at parent time `parent` and runs at
+    /// `speed` from there on.
+    fn retimed(&self, parent: f64, local: f64, speed: f32) -> Self {
+        Self {
+            begin_time: parent,
+            speed,
+            time_offset: local,
+        }
+    }
+}
+
+/// Start and end of a set of animations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Extent {
+    start: f64,
+    end: f64,
+}
+
+impl Default for Extent {
+    fn default() -> Self {
+        Self {
+            start: f64::INFINITY,
+            end: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Extent {
+    fn include(&mut self, begin: f64, length: f64) {
+        self.start = self.start.min(begin);
+        self.end = self.end.max(begin + length);
+    }
+
+    /// Where time is measured from: the start, or zero without animations.
+    fn origin(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.start
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    fn length(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.end - self.start
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock() -> LayerClock {
+        LayerClock {
+            begin_time: 0.0,
+            speed: 1.0,
+            time_offset: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_local_time() {
+        let clock = LayerClock {
+            begin_time: 10.0,
+            speed: 2.0,
+            time_offset: 1.0,
+        };
+        assert_eq!(clock.local_time(10.0), 1.0);
+        assert_eq!(clock.local_time(12.0), 5.0);
+    }
+
+    #[test]
+    fn test_pause_and_resume_are_continuous() {
+        let playing = clock();
+        let paused = playing.retimed(5.0, playing.local_time(5.0), 0.0);
+        assert_eq!(paused.local_time(5.0), 5.0);
+        assert_eq!(paused.local_time(100.0), 5.0);
+
+        let resumed = paused.retimed(100.0, paused.local_time(100.0), 1.0);
+        assert_eq!(resumed.local_time(100.0), 5.0);
+        assert_eq!(resumed.local_time(101.0), 6.0);
+    }
+
+    #[test]
+    fn test_rate_change_is_continuous() {
+        let playing = clock();
+        let slow = playing.retimed(4.0, playing.local_time(4.0), 0.5);
+        assert_eq!(slow.local_time(4.0), 4.0);
+        assert_eq!(slow.local_time(6.0), 5.0);
+    }
+
+    #[test]
+    fn test_seek() {
+        let paused = clock().retimed(3.0, 3.0, 0.0);
+        let seeked = paused.retimed(8.0, 1.5, paused.speed);
+        assert_eq!(seeked.local_time(8.0), 1.5);
+        assert_eq!(seeked.local_time(20.0), 1.5);
+    }
+
+    #[test]
+    fn test_extent() {
+        let mut extent = Extent::default();
+        assert!(extent.is_empty());
+        assert_eq!(extent.length(), 0.0);
+        assert_eq!(extent.origin(), 0.0);
+
+        extent.include(2.0, 1.0);
+        extent.include(1.0, 0.5);
+        extent.include(2.5, 2.0);
+        assert_eq!(extent.start, 1.0);
+        assert_eq!(extent.end, 4.5);
+        assert_eq!(extent.length(), 3.5);
+        assert_eq!(extent.origin(), 1.0);
+    }
+
+    #[test]
+    fn test_delay_under_slowed_parent() {
+        // Resolved on a detached layer, a delay counts in media time
+        let now = 100.0;
+        let begin = now + 0.25;
+
+        // Slowing down from where the clock is starts it after the delay
+        let slowed = clock().retimed(now, clock().local_time(now), 0.25);
+        assert_eq!(slowed.local_time(now), now);
+        assert_eq!(slowed.local_time(now + 1.0), begin);
+
+        // Just setting the speed would push it far into the future
+        let unanchored = LayerClock {
+            speed: 0.25,
+            ..clock()
+        };
+        assert!(unanchored.local_time(now + 1.0) < now / 2.0);
+    }
+
+    #[test]
+    fn test_rate_is_relative_to_global_speed() {
+        assert_eq!(rate_of(1.0, 1.0), 1.0);
+        assert_eq!(rate_of(2.0, 1.0), 2.0);
+        // A window container in slow motion plays at rate 1.0
+        assert_eq!(rate_of(0.25, 0.25), 1.0);
+        assert_eq!(rate_of(0.5, 0.25), 2.0);
+        assert_eq!(rate_of(0.0, 0.25), 1.0);
+    }
+}
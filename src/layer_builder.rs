@@ -1,10 +1,19 @@
 //! Builder for `CALayer` (the basic compositing layer).
 
+use crate::animation_builder::presets::PresetBuilder;
+use crate::animation_builder::{CABasicAnimationBuilder, KeyPath, MotionPathBuilder};
 use crate::color::Color;
+use crate::layer_ext::CALayerExt;
 use objc2::rc::Retained;
 use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect};
 use objc2_core_graphics::CGColor;
-use objc2_quartz_core::{CALayer, CATransform3D};
+use objc2_quartz_core::{CAAnimation, CALayer, CATransform3D};
+
+/// A pending animation to be applied when the layer is built.
+struct PendingAnimation {
+    name: String,
+    animation: Retained<CAAnimation>,
+}
 
 /// Builder for `CALayer`.
 ///
@@ -25,6 +34,7 @@ pub struct CALayerBuilder {
     hidden: Option<bool>,
     transform: Option<CATransform3D>,
     opacity: Option<f32>,
+    animations: Vec<PendingAnimation>,
 }
 
 impl CALayerBuilder {
@@ -93,7 +103,127 @@ impl CALayerBuilder {
         self
     }
 
+    /// Adds an animation to be applied when the layer is built.
+    ///
+    /// The animation is configured using a closure that receives a
+    /// [`CABasicAnimationBuilder`] and returns the configured builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `key_path` - The property to animate (e.g., [`KeyPath::TransformScale`])
+    /// * `configure` - A closure that configures the animation builder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Simple pulse animation
+    /// CALayerBuilder::new()
+    ///     .bounds(CGRect::new(CGPoint::ZERO, CGSize::new(80.0, 80.0)))
+    ///     .background_color(Color::RED)
+    ///     .animate("pulse", KeyPath::TransformScale, |a| {
+    ///         a.values(0.85, 1.15)
+    ///             .duration(800.millis())
+    ///             .easing(Easing::InOut)
+    ///             .autoreverses()
+    ///             .repeat(Repeat::Forever)
+    ///     })
+    ///     .build();
+    ///
+    /// // Multiple animations on the same layer
+    /// CALayerBuilder::new()
+    ///     .background_color(Color::BLUE)
+    ///     .animate("scale", KeyPath::TransformScale, |a| {
+    ///         a.values(0.9, 1.1).duration(500.millis()).repeat(Repeat::Forever)
+    ///     })
+    ///     .animate("fade", KeyPath::Opacity, |a| {
+    ///         a.values(1.0, 0.5).duration(1.seconds()).repeat(Repeat::Forever)
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn animate<F>(mut self, name: impl Into<String>, key_path: KeyPath, configure: F) -> Self
+    where
+        F: FnOnce(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
+    {
+        let builder = CABasicAnimationBuilder::new(key_path);
+        let animation = configure(builder).build_animation();
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation,
+        });
+        self
+    }
+
+    /// Adds a motion path animation to be applied when the layer is built.
+    ///
+    /// The closure receives a [`MotionPathBuilder`] that moves the layer's
+    /// `position` along a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `configure` - A closure that configures the motion path builder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Orbit along a circular path, facing the direction of travel
+    /// CALayerBuilder::new()
+    ///     .background_color(Color::CYAN)
+    ///     .corner_radius(12.0)
+    ///     .animate_motion("orbit", |m| {
+    ///         m.path(&orbit_path)
+    ///             .rotation(RotationMode::Auto)
+    ///             .duration(2.seconds())
+    ///             .easing(Easing::Linear)
+    ///             .repeat(Repeat::Forever)
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn animate_motion<F>(mut self, name: impl Into<String>, configure: F) -> Self
+    where
+        F: FnOnce(MotionPathBuilder) -> MotionPathBuilder,
+    {
+        let animation = configure(MotionPathBuilder::new()).build();
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: Retained::into_super(Retained::into_super(animation)),
+        });
+        self
+    }
+
+    /// Adds a ready-made effect to be applied when the layer is built.
+    ///
+    /// Accepts a [`Preset`](crate::animation_builder::presets::Preset) directly, or a [`PresetBuilder`] for tuning.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `preset` - The effect to apply
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// CALayerBuilder::new()
+    ///     .animate_preset("intro", Preset::BounceIn)
+    ///     .animate_preset("idle", Preset::Heartbeat.repeat(Repeat::Forever).delay(1.seconds()))
+    ///     .build();
+    /// ```
+    pub fn animate_preset(
+        mut self,
+        name: impl Into<String>,
+        preset: impl Into<PresetBuilder>,
+    ) -> Self {
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: preset.into().build(),
+        });
+        self
+    }
+
     /// Builds and returns the configured `CALayer`.
+    ///
+    /// All pending animations added via `.animate()` are applied to the layer.
     pub fn build(self) -> Retained<CALayer> {
         let layer = CALayer::new();
 
@@ -119,6 +249,11 @@ impl CALayerBuilder {
             layer.setOpacity(opacity);
         }
 
+        // Apply all pending animations
+        for pending in self.animations {
+            layer.add_animation(&pending.animation, &pending.name);
+        }
+
         layer
     }
 }
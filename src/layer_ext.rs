@@ -1,15 +1,92 @@
 //! Snake_case method wrappers for `CALayer`.
 
-use objc2_quartz_core::CALayer;
+use objc2_foundation::NSString;
+use objc2_quartz_core::{kCATransition, CAAnimation, CACurrentMediaTime, CALayer, CAMediaTiming};
+
+use crate::animation_builder::TransitionBuilder;
+use crate::controller::AnimationController;
+use crate::settings::{in_slowed_tree, record_multiplier, recorded_multiplier, restore_speed};
 
 /// Extension trait providing snake_case methods for CALayer.
 pub trait CALayerExt {
     /// Add a sublayer to this layer.
     fn add_sublayer(&self, layer: &CALayer);
+
+    /// Get a controller for the animations of this layer and its sublayers.
+    fn animation_controller(&self) -> AnimationController;
+
+    /// Add an animation built by this crate under `key`.
+    ///
+    /// Builders store a delay as a relative `beginTime`. It starts counting
+    /// now, on the clock this layer has in its current tree, so it holds on
+    /// trees that are paused, seeked or play at another speed.
+    ///
+    /// Attach the layer to its final tree first. The delay is fixed when
+    /// the animation is added, so moving the layer under a superlayer with
+    /// another `speed`, `timeOffset` or `beginTime` afterwards shifts it.
+    /// In a tree that runs at the global
+    /// [speed](crate::settings::AnimationSettings::apply_speed), the
+    /// animation plays at its own speed, since the tree slows it down.
+    fn add_animation(&self, animation: &CAAnimation, key: &str);
+
+    /// Animate the next change of this layer's contents.
+    ///
+    /// Takes a [`TransitionKind`](crate::animation_builder::TransitionKind)
+    /// or a configured [`TransitionBuilder`]. Change the contents right
+    /// after, e.g. set new text; the change then fades or slides in
+    /// instead of swapping abruptly. Replaces a transition that has not
+    /// finished yet.
+    fn transition(&self, transition: impl Into<TransitionBuilder>);
 }
 
 impl CALayerExt for CALayer {
     fn add_sublayer(&self, layer: &CALayer) {
         self.addSublayer(layer);
     }
+
+    fn animation_controller(&self) -> AnimationController {
+        AnimationController::new(self)
+    }
+
+    fn add_animation(&self, animation: &CAAnimation, key: &str) {
+        add_animation_at(
+            self,
+            animation,
+            &NSString::from_str(key),
+            CACurrentMediaTime(),
+        );
+    }
+
+    fn transition(&self, transition: impl Into<TransitionBuilder>) {
+        let transition = transition.into().build();
+        // SAFETY: kCATransition is a valid extern static that is always
+        // available on macOS.
+        let key = unsafe { kCATransition };
+        add_animation_at(self, &transition, key, CACurrentMediaTime());
+    }
+}
+
+/// Adds `animation` to `layer` like [`CALayerExt::add_animation`], with its
+/// delay counting from the media time `now`.
+///
+/// Sharing `now` keeps animations added to several layers in step.
+pub(crate) fn add_animation_at(layer: &CALayer, animation: &CAAnimation, key: &NSString, now: f64) {
+    let speed = animation.speed();
+    let delay = animation.beginTime();
+    let multiplier = recorded_multiplier(animation);
+    if in_slowed_tree(layer) {
+        restore_speed(animation);
+    }
+    let relative = animation.beginTime();
+    if relative > 0.0 {
+        animation.setBeginTime(layer.convertTime_fromLayer(now, None) + relative);
+    }
+    // Core Animation copies the animation, so the original keeps its
+    // speed and relative delay for the next layer.
+    layer.addAnimation_forKey(animation, Some(key));
+    animation.setSpeed(speed);
+    animation.setBeginTime(delay);
+    if let Some(multiplier) = multiplier {
+        record_multiplier(animation, multiplier);
+    }
 }
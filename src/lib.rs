@@ -11,6 +11,10 @@
 //! | [`CAEmitterLayerBuilder`](particles::CAEmitterLayerBuilder) | Particle systems with closure-based cell configuration |
 //! | [`PointBurstBuilder`](particles::PointBurstBuilder) | Convenience API for radial particle bursts |
 //! | [`CABasicAnimationBuilder`](animation_builder::CABasicAnimationBuilder) | Standalone GPU-accelerated animations |
+//! | [`MotionPathBuilder`](animation_builder::MotionPathBuilder) | Motion along a path with auto-rotation |
+//! | [`CAKeyframeAnimationBuilder`](animation_builder::CAKeyframeAnimationBuilder) | Animations through a list of values |
+//! | [`CAAnimationGroupBuilder`](animation_builder::CAAnimationGroupBuilder) | Several animations played as one |
+//! | [`DecayAnimationBuilder`](animation_builder::DecayAnimationBuilder) | Fling motion that coasts to a stop |
 //!
 //! # Quick Start
 //!
@@ -61,12 +65,82 @@
 //! [`Opacity`](animation_builder::KeyPath::Opacity),
 //! [`ShadowRadius`](animation_builder::KeyPath::ShadowRadius),
 //! [`ShadowOpacity`](animation_builder::KeyPath::ShadowOpacity),
+//! [`Path`](animation_builder::KeyPath::Path),
 //! [`Custom`](animation_builder::KeyPath::Custom)
 //!
 //! **Easing curves:** [`Linear`](animation_builder::Easing::Linear),
 //! [`In`](animation_builder::Easing::In),
 //! [`Out`](animation_builder::Easing::Out),
-//! [`InOut`](animation_builder::Easing::InOut)
+//! [`InOut`](animation_builder::Easing::InOut),
+//! [`CubicBezier`](animation_builder::Easing::CubicBezier)
+//!
+//! # Timelines
+//!
+//! Lay out several animations on a shared clock with [`Timeline`](timeline::Timeline):
+//!
+//! ```ignore
+//! Timeline::new()
+//!     .animate(&title, "fade", KeyPath::Opacity, |a| a.values(0.0, 1.0))
+//!     .delay(100.millis())
+//!     .parallel(|p| {
+//!         p.animate(&left, "pop", KeyPath::TransformScale, |a| a.values(0.8, 1.0))
+//!             .animate(&right, "pop", KeyPath::TransformScale, |a| a.values(0.8, 1.0))
+//!     })
+//!     .play();
+//! ```
+//!
+//! Pause, scrub or slow down running animations with an
+//! [`AnimationController`](controller::AnimationController):
+//!
+//! ```ignore
+//! let mut controller = window.animation_controller();
+//! controller.pause();
+//! controller.seek_progress(0.5);
+//! controller.set_rate(0.5);
+//! controller.resume();
+//! ```
+//!
+//! Property changes on layers that are already shown animate implicitly.
+//! Control how with [`transaction`](transaction::transaction):
+//!
+//! ```ignore
+//! transaction(|t| t.duration(300.millis()).easing(Easing::Out), || {
+//!     layer.setPosition(CGPoint::new(100.0, 50.0));
+//! });
+//! ```
+//!
+//! Redirect a running animation to a new target without a jump using
+//! [`retarget`](retarget::retarget), which continues from the displayed value
+//! and velocity.
+//!
+//! To debug timing, slow every animation down at once with
+//! [`AnimationSettings`](settings::AnimationSettings), or by running with
+//! `CORE_ANIMATION_SPEED=0.25`. The builders also follow the system's
+//! "Reduce motion" setting through a [`MotionPolicy`](settings::MotionPolicy),
+//! which tests can force:
+//!
+//! ```ignore
+//! AnimationSettings::set_motion_policy(MotionPolicy::Reduced);
+//! ```
+//!
+//! For idle motion that never quite repeats, `wiggle` bakes seeded
+//! [`noise`] into a seamless keyframe loop:
+//!
+//! ```ignore
+//! CAKeyframeAnimationBuilder::new(KeyPath::PositionY)
+//!     .wiggle(0.8, 4.0, seed)
+//!     .build();
+//! ```
+//!
+//! A seeded [`Variation`](variation::Variation) jitters durations, delays and
+//! colors across many layers, the same way on every run.
+//!
+//! With the `spec` feature, animations can also be loaded from JSON or TOML
+//! files as an `AnimationSpec`, see the `spec` module.
+//!
+//! With the `lottie` feature, animations exported from After Effects as
+//! Lottie JSON can be played as a layer tree, and shape layer animations can
+//! be exported the other way, see the `lottie` module.
 //!
 //! # Particle Systems
 //!
@@ -117,12 +191,26 @@ compile_error!("`core-animation` only works on Apple platforms. Pass `--target a
 
 pub mod animation_builder;
 mod color;
+pub mod controller;
 mod duration_ext;
 mod layer_builder;
 mod layer_ext;
+#[cfg(feature = "lottie")]
+pub mod lottie;
+pub mod noise;
 pub mod particles;
+pub mod path;
+mod random;
+pub mod retarget;
+pub mod settings;
 mod shape_layer_builder;
+#[cfg(feature = "spec")]
+pub mod spec;
+pub mod stagger;
 mod text_layer_builder;
+pub mod timeline;
+pub mod transaction;
+pub mod variation;
 pub mod window;
 
 // Re-export Color type
@@ -133,7 +221,7 @@ pub use objc2_quartz_core::{CALayer, CAShapeLayer, CATextLayer, CATransform3D};
 
 // Re-export our builders
 pub use layer_builder::CALayerBuilder;
-pub use shape_layer_builder::CAShapeLayerBuilder;
+pub use shape_layer_builder::{CAShapeLayerBuilder, LineCap, LineJoin};
 pub use text_layer_builder::{CATextLayerBuilder, TextAlign, Truncation};
 
 // Re-export window types
@@ -157,7 +245,30 @@ pub mod prelude {
     pub use crate::color::Color;
 
     // Animation builder types
-    pub use crate::animation_builder::{CABasicAnimationBuilder, Easing, KeyPath, Repeat};
+    pub use crate::animation_builder::presets::{Preset, PresetBuilder};
+    pub use crate::animation_builder::{
+        AnimationError, CAAnimationGroupBuilder, CABasicAnimationBuilder,
+        CAKeyframeAnimationBuilder, CalculationMode, DecayAnimationBuilder, DecelerationRate,
+        Easing, FillMode, KeyPath, MotionPathBuilder, Repeat, RotationMode, SampleValue,
+        TransitionBuilder, TransitionDirection, TransitionKind,
+    };
+
+    // Path model for morphing and motion paths
+    pub use crate::path::{BezierPath, PathMeasure};
+
+    // Organic motion and variation
+    pub use crate::noise::Noise;
+    pub use crate::variation::Variation;
+
+    // Sequencing and playback control
+    pub use crate::controller::AnimationController;
+    pub use crate::retarget::{retarget, Retarget, Spring};
+    pub use crate::settings::{AnimationSettings, MotionPolicy};
+    #[cfg(feature = "spec")]
+    pub use crate::spec::AnimationSpec;
+    pub use crate::stagger::{Distribution, Stagger, StaggerTiming};
+    pub use crate::timeline::Timeline;
+    pub use crate::transaction::{transaction, Transaction};
 
     // Builders
     pub use crate::layer_builder::CALayerBuilder;
@@ -165,7 +276,7 @@ pub mod prelude {
         CAEmitterCellBuilder, CAEmitterLayerBuilder, EmitterMode, EmitterShape, ParticleImage,
         PointBurstBuilder, RenderMode,
     };
-    pub use crate::shape_layer_builder::CAShapeLayerBuilder;
+    pub use crate::shape_layer_builder::{CAShapeLayerBuilder, LineCap, LineJoin};
     pub use crate::text_layer_builder::{CATextLayerBuilder, TextAlign, Truncation};
     pub use crate::window::{Screen, Window, WindowBuilder, WindowLevel, WindowStyle};
 
@@ -0,0 +1,499 @@
+//! Import of Lottie (Bodymovin) JSON animations, as exported from After
+//! Effects.
+//!
+//! A file is parsed into a [`Composition`], a plain Rust model that can be
+//! inspected or adjusted, and then built into a layer tree with keyframe
+//! animations:
+//!
+//! ```ignore
+//! let composition = Composition::from_json(&std::fs::read_to_string("loader.json")?)?;
+//! for skipped in &composition.unsupported {
+//!     eprintln!("lottie: {skipped}");
+//! }
+//!
+//! let loader = composition.build(Repeat::Forever);
+//! loader.setPosition(CGPoint::new(200.0, 200.0));
+//! window.container().add_sublayer(&loader);
+//! ```
+//!
+//! # Supported subset
+//!
+//! | Lottie | Built as |
+//! |--------|----------|
+//! | Shape layers, groups | `CALayer` containers |
+//! | Rectangles, ellipses, paths | `CAShapeLayer` paths |
+//! | Fills, strokes | One shape layer per fill and stroke |
+//! | Trim paths | `strokeStart`/`strokeEnd` |
+//! | Solid layers | `CALayer` with a background color |
+//! | Null layers, parenting | Containers that only carry a transform |
+//! | Position, scale, rotation, opacity | Layer properties |
+//! | Keyframes with bezier easing or hold | [`CAKeyframeAnimationBuilder`] |
+//! | Layer in/out frames | Animated `hidden` |
+//!
+//! Everything else, such as masks, mattes, gradients, expressions or text
+//! layers, is left out or simplified and listed in
+//! [`Composition::unsupported`] with where it was found, e.g.
+//! `layers[2].ks.r: expressions`.
+//!
+//! # Export
+//!
+//! The other direction starts from a [`CompositionExport`]: add the same
+//! [`CAShapeLayerBuilder`](crate::CAShapeLayerBuilder)s, animations
+//! included, that would build the layers on screen. Timing such as delays,
+//! repeats and autoreverse is expanded into keyframes.
+//!
+//! ```ignore
+//! let spinner = CAShapeLayerBuilder::new()
+//!     .circle(80.0)
+//!     .position(CGPoint::new(50.0, 50.0))
+//!     .fill_color(Color::TRANSPARENT)
+//!     .stroke_color(Color::CYAN)
+//!     .line_width(6.0)
+//!     .animate("spin", KeyPath::TransformRotation, |a| {
+//!         a.values(0.0, std::f64::consts::TAU)
+//!             .duration(Duration::from_secs(1))
+//!             .repeat(Repeat::Forever)
+//!     });
+//! let composition = CompositionExport::new(100.0, 100.0).layer(spinner).build();
+//! std::fs::write("spinner.json", composition.to_json())?;
+//! ```
+//!
+//! Anything without a Lottie equivalent, such as callbacks, paced keyframes
+//! or shadows, ends up in [`Composition::unsupported`] of the result.
+//!
+//! Requires the `lottie` feature.
+
+mod export;
+mod model;
+
+pub use export::CompositionExport;
+pub(crate) use export::{ExportAnimation, ShapeLayerDescription};
+pub use model::{
+    Animated, Composition, Fill, Keyframe, Layer, LayerContent, LineCap, LineJoin, LottieError,
+    ShapeGroup, Stroke, Transform, Trim, Unsupported, Vec2,
+};
+
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_foundation::NSString;
+use objc2_quartz_core::{CALayer, CAShapeLayer, CATransform3D};
+
+use crate::animation_builder::{CAKeyframeAnimationBuilder, Easing, KeyPath, Repeat};
+use crate::color::Color;
+use crate::layer_builder::CALayerBuilder;
+use crate::path::BezierPath;
+use crate::shape_layer_builder::CAShapeLayerBuilder;
+
+impl Composition {
+    /// Builds the composition as a layer tree.
+    ///
+    /// The returned layer has the size of the composition and clips its
+    /// content to it. Its geometry is flipped, so coordinates inside match
+    /// the file, with y pointing down. Position it like any other layer.
+    ///
+    /// All animations share the length of the composition and play with
+    /// `repeat`, usually [`Repeat::Forever`] to loop like a Lottie player.
+    pub fn build(&self, repeat: Repeat) -> Retained<CALayer> {
+        let root = CALayerBuilder::new()
+            .bounds(CGRect::new(
+                CGPoint::ZERO,
+                CGSize::new(self.width, self.height),
+            ))
+            .build();
+        root.setGeometryFlipped(true);
+        root.setMasksToBounds(true);
+
+        let player = Player {
+            in_frame: self.in_frame,
+            out_frame: self.out_frame,
+            duration: Duration::from_secs_f64(self.duration_secs().max(0.0)),
+            repeat,
+        };
+        // Lottie lists the topmost layer first.
+        for layer in self.layers.iter().rev() {
+            let parent = self.parent_chain(layer, &root, &player);
+            parent.addSublayer(&player.layer(layer));
+        }
+        root
+    }
+
+    /// Builds containers carrying the transforms of the parents of `layer`,
+    /// outermost first, and returns the innermost one.
+    ///
+    /// Children follow the transform of their parent, but not its opacity
+    /// or visibility, so parents are repeated per child instead of nesting
+    /// the layers themselves.
+    fn parent_chain(
+        &self,
+        layer: &Layer,
+        root: &Retained<CALayer>,
+        player: &Player,
+    ) -> Retained<CALayer> {
+        let mut parents = Vec::new();
+        let mut next = layer.parent;
+        while let Some(index) = next {
+            let Some(parent) = self.layers.iter().find(|l| l.index == Some(index)) else {
+                break;
+            };
+            // Guard against cycles in malformed files.
+            if parents.len() > self.layers.len() {
+                break;
+            }
+            parents.push(parent);
+            next = parent.parent;
+        }
+
+        let mut container = root.clone();
+        for parent in parents.iter().rev() {
+            let child = player.transform(&parent.transform, false);
+            container.addSublayer(&child);
+            container = child;
+        }
+        container
+    }
+}
+
+/// Shared timing of the animations of a composition.
+struct Player {
+    in_frame: f64,
+    out_frame: f64,
+    duration: Duration,
+    repeat: Repeat,
+}
+
+impl Player {
+    /// Builds a Lottie layer with its content.
+    fn layer(&self, layer: &Layer) -> Retained<CALayer> {
+        let container = self.transform(&layer.transform, true);
+        if let Some(track) = Track::visibility(
+            layer.in_frame,
+            layer.out_frame,
+            self.in_frame,
+            self.out_frame,
+        ) {
+            container.setHidden(track.values[0] != 0.0);
+            self.add(&container, KeyPath::Custom("hidden"), track);
+        }
+
+        match &layer.content {
+            LayerContent::Null => {}
+            LayerContent::Solid {
+                color,
+                width,
+                height,
+            } => {
+                let solid = CALayerBuilder::new()
+                    .bounds(CGRect::new(CGPoint::ZERO, CGSize::new(*width, *height)))
+                    .position(CGPoint::new(width / 2.0, height / 2.0))
+                    .background_color(*color)
+                    .build();
+                container.addSublayer(&solid);
+            }
+            LayerContent::Shapes(group) => {
+                self.group_content(&container, group, None, None, None);
+            }
+        }
+        container
+    }
+
+    /// Builds a container whose origin is the anchor of `transform`.
+    ///
+    /// The container has no size, so its anchor point is the point `anchor`
+    /// of its content, and scale and rotation happen around it.
+    fn transform(&self, transform: &Transform, with_opacity: bool) -> Retained<CALayer> {
+        let [anchor_x, anchor_y] = transform.anchor;
+        let [x, y] = transform.position.initial();
+        let [scale_x, scale_y] = transform.scale.initial();
+        let static_transform = CATransform3D::new_scale(scale_x, scale_y, 1.0).concat(
+            CATransform3D::new_rotation(transform.rotation.initial(), 0.0, 0.0, 1.0),
+        );
+
+        let mut builder = CALayerBuilder::new()
+            .bounds(CGRect::new(CGPoint::new(anchor_x, anchor_y), CGSize::ZERO))
+            .position(CGPoint::new(x, y))
+            .transform(static_transform);
+        if with_opacity {
+            builder = builder.opacity(transform.opacity.initial() as f32);
+        }
+        let layer = builder.build();
+
+        let tracks = [
+            (KeyPath::PositionX, transform.position.map(|p| p[0])),
+            (KeyPath::PositionY, transform.position.map(|p| p[1])),
+            (KeyPath::TransformScaleX, transform.scale.map(|s| s[0])),
+            (KeyPath::TransformScaleY, transform.scale.map(|s| s[1])),
+        ];
+        for (key_path, value) in &tracks {
+            self.animate(&layer, *key_path, value);
+        }
+        self.animate(&layer, KeyPath::TransformRotation, &transform.rotation);
+        if with_opacity {
+            self.animate(&layer, KeyPath::Opacity, &transform.opacity);
+        }
+        layer
+    }
+
+    /// Adds the shapes of `group` to `container`.
+    ///
+    /// The group's own paths go below its subgroups, and the first subgroup
+    /// ends up on top, as in the file.
+    fn group_content(
+        &self,
+        container: &CALayer,
+        group: &ShapeGroup,
+        fill: Option<&Fill>,
+        stroke: Option<&Stroke>,
+        trim: Option<&Trim>,
+    ) {
+        let fill = group.fill.as_ref().or(fill);
+        let stroke = group.stroke.as_ref().or(stroke);
+        let trim = group.trim.as_ref().or(trim);
+
+        if !group.path.is_empty() {
+            if let Some(fill) = fill {
+                container.addSublayer(&self.fill(&group.path, fill));
+            }
+            if let Some(stroke) = stroke {
+                container.addSublayer(&self.stroke(&group.path, stroke, trim));
+            }
+        }
+        for child in group.groups.iter().rev() {
+            let layer = self.transform(&child.transform, true);
+            self.group_content(&layer, child, fill, stroke, trim);
+            container.addSublayer(&layer);
+        }
+    }
+
+    /// Builds a shape layer filling `path`.
+    fn fill(&self, path: &BezierPath, fill: &Fill) -> Retained<CAShapeLayer> {
+        let layer = CAShapeLayerBuilder::new()
+            .path(path.to_cgpath())
+            .fill_color(fill.color)
+            .opacity(fill.opacity.initial() as f32)
+            .build();
+        self.animate(&layer, KeyPath::Opacity, &fill.opacity);
+        layer
+    }
+
+    /// Builds a shape layer stroking `path`.
+    fn stroke(
+        &self,
+        path: &BezierPath,
+        stroke: &Stroke,
+        trim: Option<&Trim>,
+    ) -> Retained<CAShapeLayer> {
+        let layer = CAShapeLayerBuilder::new()
+            .path(path.to_cgpath())
+            .fill_color(Color::TRANSPARENT)
+            .stroke_color(stroke.color)
+            .line_width(stroke.width.initial())
+            .line_cap(stroke.cap)
+            .line_join(stroke.join)
+            .opacity(stroke.opacity.initial() as f32)
+            .build();
+        self.animate(&layer, KeyPath::Opacity, &stroke.opacity);
+        self.animate(&layer, KeyPath::Custom("lineWidth"), &stroke.width);
+
+        if let Some(trim) = trim {
+            layer.setStrokeStart(trim.start.initial());
+            layer.setStrokeEnd(trim.end.initial());
+            self.animate(&layer, KeyPath::Custom("strokeStart"), &trim.start);
+            self.animate(&layer, KeyPath::Custom("strokeEnd"), &trim.end);
+        }
+        layer
+    }
+
+    /// Animates `key_path` of `layer` if `value` has keyframes.
+    fn animate(&self, layer: &CALayer, key_path: KeyPath, value: &Animated<f64>) {
+        if let Animated::Keyframes(keyframes) = value {
+            let track = Track::new(keyframes, self.in_frame, self.out_frame);
+            self.add(layer, key_path, track);
+        }
+    }
+
+    /// Adds a keyframe animation through `track`, keyed by its key path.
+    fn add(&self, layer: &CALayer, key_path: KeyPath, track: Track) {
+        let animation = CAKeyframeAnimationBuilder::new(key_path)
+            .values(track.values)
+            .key_times(track.key_times)
+            .easings(track.easings)
+            .easing(Easing::Linear)
+            .duration(self.duration)
+            .repeat(self.repeat)
+            .build();
+        let key = NSString::from_str(key_path.as_str());
+        layer.addAnimation_forKey(&animation, Some(&key));
+    }
+}
+
+/// Values, key times and per-segment easings of a keyframe animation that
+/// spans the whole composition.
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    values: Vec<f64>,
+    key_times: Vec<f64>,
+    easings: Vec<Easing>,
+}
+
+impl Track {
+    /// Converts Lottie keyframes into a track.
+    ///
+    /// Frames become fractions of `in_frame..out_frame`, clamped to it. The
+    /// first and last values are held to the ends of the composition, and
+    /// hold keyframes jump to the next value at its frame.
+    fn new(keyframes: &[Keyframe<f64>], in_frame: f64, out_frame: f64) -> Self {
+        let mut track = Self {
+            values: Vec::with_capacity(keyframes.len() + 2),
+            key_times: Vec::with_capacity(keyframes.len() + 2),
+            easings: Vec::with_capacity(keyframes.len() + 1),
+        };
+        let time = |frame: f64| {
+            let length = out_frame - in_frame;
+            if length > 0.0 {
+                ((frame - in_frame) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+
+        let first = &keyframes[0];
+        if time(first.frame) > 0.0 {
+            track.push(0.0, first.value, Easing::Linear);
+        }
+        for (index, keyframe) in keyframes.iter().enumerate() {
+            let key_time = time(keyframe.frame);
+            match keyframes.get(index + 1) {
+                Some(next) if keyframe.hold => {
+                    track.push(key_time, keyframe.value, Easing::Linear);
+                    track.push(time(next.frame), keyframe.value, Easing::Linear);
+                }
+                Some(_) => track.push(key_time, keyframe.value, keyframe.easing),
+                None => track.push(key_time, keyframe.value, Easing::Linear),
+            }
+        }
+        if track.key_times.last().is_some_and(|&t| t < 1.0) {
+            track.push(1.0, keyframes[keyframes.len() - 1].value, Easing::Linear);
+        }
+        // The easing after the last value is never used.
+        track.easings.pop();
+        track
+    }
+
+    /// Builds a `hidden` track that shows a layer from `in_frame` to
+    /// `out_frame`, or `None` if it is visible throughout the composition.
+    fn visibility(
+        in_frame: f64,
+        out_frame: f64,
+        composition_in: f64,
+        composition_out: f64,
+    ) -> Option<Self> {
+        if in_frame <= composition_in && out_frame >= composition_out {
+            return None;
+        }
+        let keyframe = |frame: f64, value: f64| Keyframe {
+            frame,
+            value,
+            easing: Easing::Linear,
+            hold: true,
+        };
+        let mut keyframes = Vec::with_capacity(3);
+        if in_frame > composition_in {
+            keyframes.push(keyframe(composition_in, 1.0));
+        }
+        keyframes.push(keyframe(in_frame.max(composition_in), 0.0));
+        if out_frame < composition_out {
+            keyframes.push(keyframe(out_frame, 1.0));
+        }
+        Some(Self::new(&keyframes, composition_in, composition_out))
+    }
+
+    fn push(&mut self, key_time: f64, value: f64, easing: Easing) {
+        self.key_times.push(key_time);
+        self.values.push(value);
+        self.easings.push(easing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(frame: f64, value: f64) -> Keyframe<f64> {
+        Keyframe {
+            frame,
+            value,
+            easing: Easing::Out,
+            hold: false,
+        }
+    }
+
+    #[test]
+    fn test_track_spanning_composition() {
+        let track = Track::new(&[keyframe(0.0, 1.0), keyframe(30.0, 2.0)], 0.0, 30.0);
+        assert_eq!(track.values, vec![1.0, 2.0]);
+        assert_eq!(track.key_times, vec![0.0, 1.0]);
+        assert_eq!(track.easings, vec![Easing::Out]);
+    }
+
+    #[test]
+    fn test_track_holds_ends() {
+        let track = Track::new(&[keyframe(10.0, 1.0), keyframe(20.0, 2.0)], 0.0, 40.0);
+        assert_eq!(track.values, vec![1.0, 1.0, 2.0, 2.0]);
+        assert_eq!(track.key_times, vec![0.0, 0.25, 0.5, 1.0]);
+        assert_eq!(
+            track.easings,
+            vec![Easing::Linear, Easing::Out, Easing::Linear]
+        );
+    }
+
+    #[test]
+    fn test_track_hold_keyframe() {
+        let hold = Keyframe {
+            hold: true,
+            ..keyframe(0.0, 0.0)
+        };
+        let track = Track::new(&[hold, keyframe(10.0, 1.0)], 0.0, 10.0);
+        assert_eq!(track.values, vec![0.0, 0.0, 1.0]);
+        assert_eq!(track.key_times, vec![0.0, 1.0, 1.0]);
+        assert_eq!(track.easings, vec![Easing::Linear, Easing::Linear]);
+    }
+
+    #[test]
+    fn test_track_clamps_to_composition() {
+        let track = Track::new(&[keyframe(-10.0, 0.0), keyframe(50.0, 1.0)], 0.0, 20.0);
+        assert_eq!(track.key_times, vec![0.0, 1.0]);
+        assert_eq!(track.values.len(), track.easings.len() + 1);
+    }
+
+    #[test]
+    fn test_visibility() {
+        assert_eq!(Track::visibility(0.0, 60.0, 0.0, 60.0), None);
+
+        let track = Track::visibility(15.0, 45.0, 0.0, 60.0).unwrap();
+        assert_eq!(track.values, vec![1.0, 1.0, 0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(track.key_times, vec![0.0, 0.25, 0.25, 0.75, 0.75, 1.0]);
+        assert_eq!(track.easings.len(), 5);
+
+        let track = Track::visibility(0.0, 30.0, 0.0, 60.0).unwrap();
+        assert_eq!(track.values, vec![0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(track.key_times, vec![0.0, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_sample_tracks() {
+        let composition = Composition::from_json(include_str!("lottie/samples/pulse.json"))
+            .expect("sample parses");
+        let ring = &composition.layers[0];
+        let Animated::Keyframes(keyframes) = ring.transform.scale.map(|s| s[0]) else {
+            panic!("expected an animated scale");
+        };
+        let track = Track::new(&keyframes, composition.in_frame, composition.out_frame);
+        assert_eq!(track.values.len(), track.key_times.len());
+        assert_eq!(track.values.len(), track.easings.len() + 1);
+        assert_eq!(track.key_times.first(), Some(&0.0));
+        assert_eq!(track.key_times.last(), Some(&1.0));
+        assert!(track.key_times.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -0,0 +1,1575 @@
+//! Export of shape layers and their animations to Lottie JSON.
+//!
+//! Layers are set up with [`CAShapeLayerBuilder`], including the
+//! animations added with its `.animate()` and `.animate_keyframes()`, so a
+//! loader can be shown on screen and exported from the same code. They are
+//! converted into a [`Composition`], which [`Composition::to_json`] writes
+//! out.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use objc2_core_foundation::{CGPoint, CGRect};
+use serde_json::{json, Map, Value};
+
+use super::model::{
+    Animated, Composition, Fill, Keyframe, Layer, LayerContent, LineCap, LineJoin, ShapeGroup,
+    Stroke, Transform, Trim, Unsupported,
+};
+use crate::animation_builder::{
+    AnimationValue, CABasicAnimationBuilder, CAKeyframeAnimationBuilder, CalculationMode, Easing,
+    FillMode, KeyPath, Repeat, Timing,
+};
+use crate::color::Color;
+use crate::path::{BezierPath, PathElement};
+use crate::CAShapeLayerBuilder;
+
+/// Lottie format version written to exported files.
+const VERSION: &str = "5.7.4";
+
+/// A composition to export, holding shape layers and their animations.
+///
+/// # Examples
+///
+/// ```ignore
+/// let spinner = CAShapeLayerBuilder::new()
+///     .name("spinner")
+///     .circle(40.0)
+///     .position(CGPoint::new(50.0, 50.0))
+///     .fill_color(Color::TRANSPARENT)
+///     .stroke_color(Color::CYAN)
+///     .line_width(4.0)
+///     .animate("spin", KeyPath::TransformRotation, |a| {
+///         a.values(0.0, -std::f64::consts::TAU)
+///             .duration(1.seconds())
+///             .easing(Easing::Linear)
+///             .repeat(Repeat::Forever)
+///     });
+///
+/// let composition = CompositionExport::new(100.0, 100.0).layer(spinner).build();
+/// for skipped in &composition.unsupported {
+///     eprintln!("lottie: {skipped}");
+/// }
+/// std::fs::write("spinner.json", composition.to_json())?;
+/// ```
+pub struct CompositionExport {
+    name: String,
+    width: f64,
+    height: f64,
+    frame_rate: f64,
+    duration: Option<Duration>,
+    geometry_flipped: bool,
+    layers: Vec<ShapeLayerDescription>,
+}
+
+impl CompositionExport {
+    /// Creates a composition of the given size, at 60 frames per second.
+    #[must_use]
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            name: String::new(),
+            width,
+            height,
+            frame_rate: 60.0,
+            duration: None,
+            geometry_flipped: false,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Sets the name of the composition.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the frames per second keyframes are placed at.
+    #[must_use]
+    pub fn frame_rate(mut self, frame_rate: f64) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+
+    /// Sets the length of the composition.
+    ///
+    /// Defaults to the end of the longest animation, counting animations
+    /// that repeat forever once. Players loop the whole composition, so
+    /// animations that repeat forever are repeated until its end.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Takes coordinates as having y pointing down, as in a layer with
+    /// `geometryFlipped` or on iOS.
+    ///
+    /// By default coordinates are those of a macOS layer, with y pointing
+    /// up, and are flipped to Lottie's y pointing down.
+    #[must_use]
+    pub fn geometry_flipped(mut self) -> Self {
+        self.geometry_flipped = true;
+        self
+    }
+
+    /// Adds a layer on top of the ones added before, like `add_sublayer`.
+    ///
+    /// Takes the builder instead of a built layer, so its animations can
+    /// be read back.
+    #[must_use]
+    pub fn layer(mut self, layer: CAShapeLayerBuilder) -> Self {
+        self.layers.push(layer.describe());
+        self
+    }
+
+    /// Converts the layers and their animations into a [`Composition`].
+    ///
+    /// Whatever cannot be represented in Lottie is left out and listed in
+    /// [`Composition::unsupported`].
+    pub fn build(self) -> Composition {
+        let mut exporter = Exporter {
+            frame_rate: self.frame_rate,
+            end_secs: 0.0,
+            unsupported: Vec::new(),
+        };
+
+        let names: Vec<String> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                if layer.name.is_empty() {
+                    format!("Layer {}", index + 1)
+                } else {
+                    layer.name.clone()
+                }
+            })
+            .collect();
+        for (layer, name) in self.layers.iter().zip(&names) {
+            for &feature in &layer.unsupported {
+                exporter.skip(name, feature);
+            }
+        }
+        let motions: Vec<Vec<Motion>> = self
+            .layers
+            .iter()
+            .zip(&names)
+            .map(|(layer, name)| exporter.motions(layer, name))
+            .collect();
+
+        let natural = motions
+            .iter()
+            .flatten()
+            .map(Motion::end_secs)
+            .fold(0.0, f64::max);
+        exporter.end_secs = match self.duration {
+            Some(duration) => duration.as_secs_f64(),
+            None if natural > 0.0 => natural,
+            None => 1.0,
+        };
+        let out_frame = (exporter.end_secs * self.frame_rate).round().max(1.0);
+
+        let flip = (!self.geometry_flipped).then_some(self.height);
+        let mut layers: Vec<Layer> = self
+            .layers
+            .iter()
+            .zip(names)
+            .zip(&motions)
+            .enumerate()
+            .map(|(index, ((layer, name), motions))| {
+                exporter.layer(layer, name, motions, index, out_frame, flip)
+            })
+            .collect();
+        // Lottie lists the topmost layer first.
+        layers.reverse();
+
+        Composition {
+            name: self.name,
+            width: self.width,
+            height: self.height,
+            frame_rate: self.frame_rate,
+            in_frame: 0.0,
+            out_frame,
+            layers,
+            unsupported: exporter.unsupported,
+        }
+    }
+}
+
+/// The settings of a [`CAShapeLayerBuilder`], read back for export.
+///
+/// Unset values are `CAShapeLayer`'s defaults: a black fill, no stroke, a
+/// line width of 1 and the anchor point in the middle of the bounds.
+pub(crate) struct ShapeLayerDescription {
+    pub name: String,
+    pub bounds: CGRect,
+    pub position: CGPoint,
+    pub path: BezierPath,
+    pub fill_color: Color,
+    pub stroke_color: Option<Color>,
+    pub line_width: f64,
+    pub line_cap: LineCap,
+    pub line_join: LineJoin,
+    pub stroke_start: f64,
+    pub stroke_end: f64,
+    pub opacity: f32,
+    pub scale: f64,
+    pub rotation: f64,
+    pub animations: Vec<(String, ExportAnimation)>,
+    /// Layer settings Lottie cannot show, such as shadows.
+    pub unsupported: Vec<&'static str>,
+}
+
+/// An animation added to a [`CAShapeLayerBuilder`].
+pub(crate) enum ExportAnimation {
+    Basic(CABasicAnimationBuilder),
+    Keyframe(CAKeyframeAnimationBuilder),
+    /// An animation only available built, named by what it is.
+    Unsupported(&'static str),
+}
+
+impl ShapeLayerDescription {
+    /// Returns the value of `property` when it is not animated.
+    fn base_value(&self, property: Property) -> f64 {
+        match property {
+            Property::PositionX => self.position.x,
+            Property::PositionY => self.position.y,
+            Property::ScaleX | Property::ScaleY => self.scale,
+            Property::Rotation => self.rotation,
+            Property::Opacity => f64::from(self.opacity),
+            Property::LineWidth => self.line_width,
+            Property::StrokeStart => self.stroke_start,
+            Property::StrokeEnd => self.stroke_end,
+        }
+    }
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+/// A layer property Lottie can animate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Property {
+    PositionX,
+    PositionY,
+    ScaleX,
+    ScaleY,
+    Rotation,
+    Opacity,
+    LineWidth,
+    StrokeStart,
+    StrokeEnd,
+}
+
+impl Property {
+    /// Returns the properties animated through `key_path`.
+    fn for_key_path(key_path: KeyPath) -> Option<&'static [Property]> {
+        Some(match key_path.as_str() {
+            "position.x" => &[Property::PositionX],
+            "position.y" => &[Property::PositionY],
+            "transform.scale" => &[Property::ScaleX, Property::ScaleY],
+            "transform.scale.x" => &[Property::ScaleX],
+            "transform.scale.y" => &[Property::ScaleY],
+            "transform.rotation.z" | "transform.rotation" => &[Property::Rotation],
+            "opacity" => &[Property::Opacity],
+            "lineWidth" => &[Property::LineWidth],
+            "strokeStart" => &[Property::StrokeStart],
+            "strokeEnd" => &[Property::StrokeEnd],
+            _ => return None,
+        })
+    }
+}
+
+/// One animation reduced to absolute values over one pass.
+struct Motion {
+    location: String,
+    properties: &'static [Property],
+    values: Vec<f64>,
+    key_times: Vec<f64>,
+    /// Easing of each segment between two values.
+    easings: Vec<Easing>,
+    timing: Timing,
+    cumulative: bool,
+    /// The layer's own value, shown whenever the animation is not.
+    base: f64,
+}
+
+impl Motion {
+    /// Length of one pass in seconds, taking the speed into account.
+    fn pass_secs(&self) -> f64 {
+        self.timing.duration.as_secs_f64() / f64::from(self.timing.speed)
+    }
+
+    /// Length of a pass and its reverse, if it autoreverses.
+    fn period_secs(&self) -> f64 {
+        if self.timing.autoreverses {
+            2.0 * self.pass_secs()
+        } else {
+            self.pass_secs()
+        }
+    }
+
+    /// When the animation ends; animations that repeat forever count once.
+    fn end_secs(&self) -> f64 {
+        let delay = self.timing.delay.as_secs_f64();
+        match self.timing.active_seconds() {
+            Some(active) => delay + active / f64::from(self.timing.speed),
+            None => delay + self.period_secs(),
+        }
+    }
+
+    /// Lays the animation out as keyframes on the composition's timeline,
+    /// up to `end_secs`.
+    fn keyframes(&self, frame_rate: f64, end_secs: f64) -> Vec<Keyframe<f64>> {
+        let mut keyframes = Keyframes {
+            frame_rate,
+            keyframes: Vec::new(),
+        };
+        let start = self.timing.delay.as_secs_f64();
+        let fill_mode = self.timing.fill_mode();
+        if start > 0.0 && !matches!(fill_mode, FillMode::Backwards | FillMode::Both) {
+            keyframes.push(0.0, self.base, Easing::Linear, true);
+        }
+
+        let pass = self.pass_secs();
+        let count = self.values.len();
+        if pass <= 0.0 {
+            // Zero-length animations jump straight to their end state.
+            let index = if self.timing.autoreverses {
+                0
+            } else {
+                count - 1
+            };
+            keyframes.push(start, self.values[index], Easing::Linear, false);
+            return keyframes.keyframes;
+        }
+
+        let period = self.period_secs();
+        let offset = match self.timing.repeat {
+            Repeat::Forever => self.timing.phase_offset * period,
+            _ => 0.0,
+        };
+        let cycles = match self.timing.active_seconds() {
+            None => ((end_secs - start + offset) / period).ceil().max(1.0) as u32,
+            // A fractional last cycle is played in full.
+            Some(active) => (active / f64::from(self.timing.speed) / period - 1e-9)
+                .ceil()
+                .max(1.0) as u32,
+        };
+        let delta = if self.cumulative && !self.timing.autoreverses {
+            self.values[count - 1] - self.values[0]
+        } else {
+            0.0
+        };
+        let segment = |index: usize| self.easings.get(index).copied().unwrap_or(Easing::Linear);
+
+        for cycle in 0..cycles {
+            let cycle_start = start + f64::from(cycle) * period - offset;
+            let shift = delta * f64::from(cycle);
+            for index in 0..count {
+                let easing = match (index + 1 < count, self.timing.autoreverses) {
+                    (true, _) => segment(index),
+                    (false, true) => index
+                        .checked_sub(1)
+                        .map_or(Easing::Linear, |previous| segment(previous).reversed()),
+                    (false, false) => Easing::Linear,
+                };
+                let time = cycle_start + self.key_times[index] * pass;
+                keyframes.push(time, self.values[index] + shift, easing, false);
+            }
+            if self.timing.autoreverses {
+                for index in (0..count.saturating_sub(1)).rev() {
+                    let easing = match index {
+                        0 => Easing::Linear,
+                        _ => segment(index - 1).reversed(),
+                    };
+                    let time = cycle_start + pass + (1.0 - self.key_times[index]) * pass;
+                    keyframes.push(time, self.values[index] + shift, easing, false);
+                }
+            }
+        }
+
+        let finished = self.timing.remove_on_completion
+            || !matches!(fill_mode, FillMode::Forwards | FillMode::Both);
+        if self.timing.repeat != Repeat::Forever && finished {
+            let end = start + f64::from(cycles) * period;
+            keyframes.push(end, self.base, Easing::Linear, false);
+        }
+        keyframes.keyframes
+    }
+}
+
+/// Keyframes being laid out, in frames.
+struct Keyframes {
+    frame_rate: f64,
+    keyframes: Vec<Keyframe<f64>>,
+}
+
+impl Keyframes {
+    /// Adds a keyframe at `secs`; the segment after it uses `easing`.
+    ///
+    /// A keyframe at the same frame as the previous one turns that one
+    /// into a hold, so the value jumps.
+    fn push(&mut self, secs: f64, value: f64, easing: Easing, hold: bool) {
+        let frame = secs * self.frame_rate;
+        if let Some(last) = self.keyframes.last_mut() {
+            if (last.frame - frame).abs() < 1e-9 {
+                if last.value == value {
+                    last.easing = easing;
+                    last.hold = hold;
+                    return;
+                }
+                last.hold = true;
+            }
+        }
+        self.keyframes.push(Keyframe {
+            frame,
+            value,
+            easing,
+            hold,
+        });
+    }
+}
+
+/// Converts layers, collecting what cannot be represented on the way.
+struct Exporter {
+    frame_rate: f64,
+    end_secs: f64,
+    unsupported: Vec<Unsupported>,
+}
+
+impl Exporter {
+    fn skip(&mut self, location: &str, feature: impl Into<String>) {
+        self.unsupported.push(Unsupported {
+            location: location.to_string(),
+            feature: feature.into(),
+        });
+    }
+
+    /// Reduces the animations of `layer` to motions of known properties.
+    fn motions(&mut self, layer: &ShapeLayerDescription, name: &str) -> Vec<Motion> {
+        let mut motions = Vec::new();
+        for (animation_name, animation) in &layer.animations {
+            let location = format!("{name}.{animation_name}");
+            let key_path = match animation {
+                ExportAnimation::Basic(builder) => builder.key_path,
+                ExportAnimation::Keyframe(builder) => builder.key_path,
+                ExportAnimation::Unsupported(feature) => {
+                    self.skip(&location, *feature);
+                    continue;
+                }
+            };
+            let Some(properties) = Property::for_key_path(key_path) else {
+                self.skip(&location, format!("{} animations", key_path.as_str()));
+                continue;
+            };
+            let base = layer.base_value(properties[0]);
+            let motion = match animation {
+                ExportAnimation::Basic(builder) => self.basic(builder, location, base),
+                ExportAnimation::Keyframe(builder) => self.keyframe(builder, location, base),
+                ExportAnimation::Unsupported(_) => unreachable!(),
+            };
+            if let Some(motion) = motion {
+                motions.push(Motion {
+                    properties,
+                    ..motion
+                });
+            }
+        }
+        motions
+    }
+
+    fn basic(
+        &mut self,
+        builder: &CABasicAnimationBuilder,
+        location: String,
+        base: f64,
+    ) -> Option<Motion> {
+        let (Some(from), Some(to), Some(by)) = (
+            self.scalar(builder.from_value.as_ref(), &location),
+            self.scalar(builder.to_value.as_ref(), &location),
+            self.scalar(builder.by_value.as_ref(), &location),
+        ) else {
+            return None;
+        };
+
+        // Resolve from/to/by like `CAPropertyAnimation`; missing values are
+        // the layer's own, or zero offsets for additive animations.
+        let current = if builder.additive { 0.0 } else { base };
+        let (from, to) = match (from, to, by) {
+            (Some(from), Some(to), _) => (from, to),
+            (Some(from), None, Some(by)) => (from, from + by),
+            (None, Some(to), Some(by)) => (to - by, to),
+            (Some(from), None, None) => (from, current),
+            (None, Some(to), None) => (current, to),
+            (None, None, Some(by)) => (current, current + by),
+            (None, None, None) => {
+                self.skip(&location, "animations without values");
+                return None;
+            }
+        };
+        let shift = if builder.additive { base } else { 0.0 };
+
+        // Holds play as keyframes, like `build_animation` does.
+        let held = match builder.held_keyframes() {
+            Some(held) if builder.has_holds() => Some(held),
+            None if builder.has_holds() => {
+                self.skip(&location, "holds without from and to values");
+                None
+            }
+            _ => None,
+        };
+        let (values, key_times, easings, timing) = match held {
+            Some(held) => (held.values, held.key_times, held.easings, held.timing),
+            None => (
+                vec![from, to],
+                vec![0.0, 1.0],
+                vec![builder.timing.easing],
+                builder.timing.clone(),
+            ),
+        };
+
+        self.check_timing(&timing, &location);
+        if builder.callbacks.on_start.is_some() || builder.callbacks.on_complete.is_some() {
+            self.skip(&location, "start and completion callbacks");
+        }
+        self.valid_speed(&timing, &location)?;
+        Some(Motion {
+            location,
+            properties: &[],
+            values: values.into_iter().map(|value| value + shift).collect(),
+            key_times,
+            easings,
+            timing,
+            cumulative: builder.cumulative,
+            base,
+        })
+    }
+
+    fn keyframe(
+        &mut self,
+        builder: &CAKeyframeAnimationBuilder,
+        location: String,
+        base: f64,
+    ) -> Option<Motion> {
+        let mut values = Vec::with_capacity(builder.values.len());
+        for value in &builder.values {
+            values.push(self.scalar(Some(value), &location)??);
+        }
+        if values.is_empty() {
+            self.skip(&location, "animations without values");
+            return None;
+        }
+        if builder.additive {
+            values.iter_mut().for_each(|value| *value += base);
+        }
+
+        let count = values.len();
+        let key_times = if builder.key_times.len() == count {
+            builder.key_times.clone()
+        } else {
+            // Core Animation ignores key times that do not match the values.
+            (0..count)
+                .map(|index| index as f64 / (count - 1).max(1) as f64)
+                .collect()
+        };
+        match builder.calculation_mode {
+            CalculationMode::Linear => {}
+            CalculationMode::Discrete => self.skip(&location, "discrete keyframes"),
+            CalculationMode::Paced => self.skip(&location, "paced keyframe timing"),
+            CalculationMode::Cubic | CalculationMode::CubicPaced => {
+                self.skip(&location, "cubic keyframe curves");
+            }
+        }
+
+        let mut easings = if builder.easings.len() + 1 == count {
+            builder.easings.clone()
+        } else {
+            vec![Easing::Linear; count - 1]
+        };
+        if builder.timing.easing != Easing::Linear {
+            if count == 2 && builder.easings.is_empty() {
+                easings = vec![builder.timing.easing];
+            } else {
+                self.skip(&location, "easing across several keyframes");
+            }
+        }
+
+        self.check_timing(&builder.timing, &location);
+        if builder.callbacks.on_start.is_some() || builder.callbacks.on_complete.is_some() {
+            self.skip(&location, "start and completion callbacks");
+        }
+        self.valid_speed(&builder.timing, &location)?;
+        Some(Motion {
+            location,
+            properties: &[],
+            values,
+            key_times,
+            easings,
+            timing: builder.timing.clone(),
+            cumulative: builder.cumulative,
+            base,
+        })
+    }
+
+    /// Reads an optional number. Returns `None` (and reports) for paths,
+    /// points and colors.
+    fn scalar(&mut self, value: Option<&AnimationValue>, location: &str) -> Option<Option<f64>> {
+        let kind = match value {
+            None => return Some(None),
+            Some(AnimationValue::Scalar(value)) => return Some(Some(*value)),
+            Some(AnimationValue::Path(_)) => "path values",
+            Some(AnimationValue::Point(_)) => "point values",
+            Some(AnimationValue::Color(_)) => "color values",
+        };
+        self.skip(location, kind);
+        None
+    }
+
+    fn check_timing(&mut self, timing: &Timing, location: &str) {
+        let cycle = timing.duration.as_secs_f64() * if timing.autoreverses { 2.0 } else { 1.0 };
+        let cycles = match timing.repeat {
+            Repeat::Count(count) => f64::from(count),
+            Repeat::For(duration) if cycle > 0.0 => duration.as_secs_f64() / cycle,
+            _ => 1.0,
+        };
+        if (cycles - cycles.round()).abs() > 1e-9 {
+            self.skip(location, "fractional repeats");
+        }
+        if timing.phase_offset > 0.0 {
+            if timing.repeat != Repeat::Forever {
+                self.skip(location, "phase offsets of animations that end");
+            } else if !timing.delay.is_zero() {
+                self.skip(location, "phase offsets combined with delays");
+            }
+        }
+    }
+
+    fn valid_speed(&mut self, timing: &Timing, location: &str) -> Option<()> {
+        if timing.speed > 0.0 {
+            Some(())
+        } else {
+            self.skip(location, "speeds of zero or less");
+            None
+        }
+    }
+
+    /// Builds the Lottie layer for `layer`.
+    ///
+    /// `flip` is the composition height when y has to be flipped.
+    fn layer(
+        &mut self,
+        layer: &ShapeLayerDescription,
+        name: String,
+        motions: &[Motion],
+        index: usize,
+        out_frame: f64,
+        flip: Option<f64>,
+    ) -> Layer {
+        // Lottie has one track per property.
+        let mut tracks: BTreeMap<Property, Vec<Keyframe<f64>>> = BTreeMap::new();
+        for motion in motions {
+            if motion
+                .properties
+                .iter()
+                .any(|property| tracks.contains_key(property))
+            {
+                self.skip(&motion.location, "several animations of one property");
+                continue;
+            }
+            let keyframes = motion.keyframes(self.frame_rate, self.end_secs);
+            for &property in motion.properties {
+                tracks.insert(property, keyframes.clone());
+            }
+        }
+        let mut track = |property: Property| match tracks.remove(&property) {
+            Some(keyframes) => Animated::Keyframes(keyframes),
+            None => Animated::Static(layer.base_value(property)),
+        };
+
+        let position = track(Property::PositionX);
+        let position = self.pair(position, track(Property::PositionY), &name, "position");
+        let scale = track(Property::ScaleX);
+        let scale = self.pair(scale, track(Property::ScaleY), &name, "scale");
+        let mut rotation = track(Property::Rotation);
+        let opacity = track(Property::Opacity);
+        let width = track(Property::LineWidth);
+        let trim = Trim {
+            start: track(Property::StrokeStart),
+            end: track(Property::StrokeEnd),
+        };
+
+        let bounds = layer.bounds;
+        let mut anchor = [
+            bounds.origin.x + bounds.size.width / 2.0,
+            bounds.origin.y + bounds.size.height / 2.0,
+        ];
+        let mut position = position;
+        let mut path = layer.path.clone();
+        if let Some(height) = flip {
+            anchor[1] = -anchor[1];
+            position = position.map(|[x, y]| [x, height - y]);
+            rotation = rotation.map(|r| -r);
+            path = flipped(&path);
+        }
+
+        let stroke = layer.stroke_color.map(|color| Stroke {
+            color,
+            opacity: Animated::Static(1.0),
+            width,
+            cap: layer.line_cap,
+            join: layer.line_join,
+        });
+        let trimmed = trim.start.is_animated()
+            || trim.end.is_animated()
+            || trim.start.initial() != 0.0
+            || trim.end.initial() != 1.0;
+        let group = ShapeGroup {
+            name: String::new(),
+            transform: Transform::default(),
+            path,
+            fill: (layer.fill_color.a > 0.0).then_some(Fill {
+                color: layer.fill_color,
+                opacity: Animated::Static(1.0),
+            }),
+            trim: (trimmed && stroke.is_some()).then_some(trim),
+            stroke,
+            groups: Vec::new(),
+        };
+
+        Layer {
+            name,
+            index: Some(index as i64 + 1),
+            parent: None,
+            in_frame: 0.0,
+            out_frame,
+            transform: Transform {
+                anchor,
+                position,
+                scale,
+                rotation,
+                opacity,
+            },
+            content: LayerContent::Shapes(Box::new(group)),
+        }
+    }
+
+    /// Combines separate x and y tracks into one 2D track.
+    fn pair(
+        &mut self,
+        x: Animated<f64>,
+        y: Animated<f64>,
+        layer: &str,
+        property: &str,
+    ) -> Animated<[f64; 2]> {
+        match (&x, &y) {
+            (Animated::Static(x), Animated::Static(y)) => Animated::Static([*x, *y]),
+            (Animated::Keyframes(_), Animated::Static(y)) => x.map(|x| [x, *y]),
+            (Animated::Static(x), Animated::Keyframes(_)) => y.map(|y| [*x, y]),
+            (Animated::Keyframes(xs), Animated::Keyframes(ys)) => {
+                let aligned = xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| {
+                        x.frame == y.frame && x.easing == y.easing && x.hold == y.hold
+                    });
+                if !aligned {
+                    self.skip(
+                        &format!("{layer}.{property}"),
+                        "x and y animations with different timing",
+                    );
+                    let y = y.initial();
+                    return x.map(|x| [x, y]);
+                }
+                Animated::Keyframes(
+                    xs.iter()
+                        .zip(ys)
+                        .map(|(x, y)| Keyframe {
+                            frame: x.frame,
+                            value: [x.value, y.value],
+                            easing: x.easing,
+                            hold: x.hold,
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+/// Mirrors `path` vertically around y = 0.
+fn flipped(path: &BezierPath) -> BezierPath {
+    let flip = |p: CGPoint| CGPoint::new(p.x, -p.y);
+    let mut flipped = BezierPath::new();
+    for element in path.elements() {
+        flipped.push(match *element {
+            PathElement::MoveTo(p) => PathElement::MoveTo(flip(p)),
+            PathElement::LineTo(p) => PathElement::LineTo(flip(p)),
+            PathElement::QuadCurveTo(c, p) => PathElement::QuadCurveTo(flip(c), flip(p)),
+            PathElement::CurveTo(c1, c2, p) => PathElement::CurveTo(flip(c1), flip(c2), flip(p)),
+            PathElement::Close => PathElement::Close,
+        });
+    }
+    flipped
+}
+
+// ============================================================================
+// JSON writer
+// ============================================================================
+
+impl Composition {
+    /// Writes the composition as Lottie JSON.
+    ///
+    /// Color alpha is folded into the opacity of fills, strokes and solid
+    /// layers, since Lottie players ignore it.
+    pub fn to_json(&self) -> String {
+        let layers: Vec<Value> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| self.layer_json(layer, index))
+            .collect();
+        json!({
+            "v": VERSION,
+            "nm": self.name,
+            "fr": self.frame_rate,
+            "ip": self.in_frame,
+            "op": self.out_frame,
+            "w": self.width,
+            "h": self.height,
+            "ddd": 0,
+            "assets": [],
+            "layers": layers,
+        })
+        .to_string()
+    }
+
+    fn layer_json(&self, layer: &Layer, index: usize) -> Value {
+        let mut transform = layer.transform.clone();
+        let mut object = Map::new();
+        object.insert("ddd".into(), json!(0));
+        object.insert("ind".into(), json!(layer.index.unwrap_or(index as i64 + 1)));
+        object.insert("nm".into(), json!(layer.name));
+        match &layer.content {
+            LayerContent::Null => {
+                object.insert("ty".into(), json!(3));
+            }
+            LayerContent::Solid {
+                color,
+                width,
+                height,
+            } => {
+                object.insert("ty".into(), json!(1));
+                object.insert("sc".into(), json!(hex(*color)));
+                object.insert("sw".into(), json!(width));
+                object.insert("sh".into(), json!(height));
+                transform.opacity = transform.opacity.map(|o| o * color.a);
+            }
+            LayerContent::Shapes(group) => {
+                object.insert("ty".into(), json!(4));
+                object.insert("shapes".into(), Value::Array(group_json(group, false)));
+            }
+        }
+        if let Some(parent) = layer.parent {
+            object.insert("parent".into(), json!(parent));
+        }
+        object.insert("sr".into(), json!(1));
+        object.insert("ks".into(), Value::Object(transform_json(&transform)));
+        object.insert("ao".into(), json!(0));
+        object.insert(
+            "ip".into(),
+            json!(layer.in_frame.clamp(self.in_frame, self.out_frame)),
+        );
+        object.insert(
+            "op".into(),
+            json!(layer.out_frame.clamp(self.in_frame, self.out_frame)),
+        );
+        object.insert("st".into(), json!(0));
+        object.insert("bm".into(), json!(0));
+        Value::Object(object)
+    }
+}
+
+/// Writes the items of a group; `nested` groups end with their transform.
+fn group_json(group: &ShapeGroup, nested: bool) -> Vec<Value> {
+    let mut items: Vec<Value> = group
+        .groups
+        .iter()
+        .map(|child| {
+            json!({
+                "ty": "gr",
+                "nm": child.name,
+                "it": group_json(child, true),
+            })
+        })
+        .collect();
+
+    let paths: Vec<Value> = contours(&group.path).iter().map(Contour::to_json).collect();
+    let fill = group.fill.as_ref().map(|fill| {
+        json!({
+            "ty": "fl",
+            "nm": "Fill",
+            "c": color_json(fill.color),
+            "o": animated_json(&fill.opacity.map(|o| o * fill.color.a), |o| vec![o * 100.0]),
+            "r": 1,
+        })
+    });
+    items.extend(paths.iter().cloned());
+    if let Some(trim) = &group.trim {
+        items.push(json!({
+            "ty": "tm",
+            "nm": "Trim Paths",
+            "s": animated_json(&trim.start, |s| vec![s * 100.0]),
+            "e": animated_json(&trim.end, |e| vec![e * 100.0]),
+            "o": { "a": 0, "k": 0 },
+            "m": 1,
+        }));
+    }
+    if let Some(stroke) = &group.stroke {
+        items.push(json!({
+            "ty": "st",
+            "nm": "Stroke",
+            "c": color_json(stroke.color),
+            "o": animated_json(&stroke.opacity.map(|o| o * stroke.color.a), |o| vec![o * 100.0]),
+            "w": animated_json(&stroke.width, |w| vec![w]),
+            "lc": match stroke.cap {
+                LineCap::Butt => 1,
+                LineCap::Round => 2,
+                LineCap::Square => 3,
+            },
+            "lj": match stroke.join {
+                LineJoin::Miter => 1,
+                LineJoin::Round => 2,
+                LineJoin::Bevel => 3,
+            },
+            "ml": 4,
+        }));
+    }
+    if let Some(fill) = fill {
+        if group.trim.is_some() {
+            // Trim paths would cut the fill too, so it gets its own copy of
+            // the paths, below the trimmed stroke.
+            let mut it = paths;
+            it.push(fill);
+            it.push(transform_item(&Transform::default()));
+            items.push(json!({ "ty": "gr", "nm": "Fill", "it": it }));
+        } else {
+            items.push(fill);
+        }
+    }
+
+    if nested || group.transform != Transform::default() {
+        items.push(transform_item(&group.transform));
+    }
+    items
+}
+
+fn transform_item(transform: &Transform) -> Value {
+    let mut object = transform_json(transform);
+    object.insert("ty".into(), json!("tr"));
+    object.insert("nm".into(), json!("Transform"));
+    Value::Object(object)
+}
+
+fn transform_json(transform: &Transform) -> Map<String, Value> {
+    let [anchor_x, anchor_y] = transform.anchor;
+    let mut object = Map::new();
+    object.insert(
+        "a".into(),
+        json!({ "a": 0, "k": [anchor_x, anchor_y, 0.0] }),
+    );
+    object.insert(
+        "p".into(),
+        animated_json(&transform.position, |[x, y]| vec![x, y, 0.0]),
+    );
+    object.insert(
+        "s".into(),
+        animated_json(&transform.scale, |[x, y]| vec![x * 100.0, y * 100.0, 100.0]),
+    );
+    object.insert(
+        "r".into(),
+        animated_json(&transform.rotation, |r| vec![r.to_degrees()]),
+    );
+    object.insert(
+        "o".into(),
+        animated_json(&transform.opacity, |o| vec![o * 100.0]),
+    );
+    object
+}
+
+/// Writes an animatable property; single numbers are written bare when
+/// static, and as one-element arrays in keyframes.
+fn animated_json<T: Clone>(value: &Animated<T>, write: impl Fn(T) -> Vec<f64>) -> Value {
+    match value {
+        Animated::Static(value) => {
+            let components = write(value.clone());
+            match components.as_slice() {
+                [single] => json!({ "a": 0, "k": single }),
+                _ => json!({ "a": 0, "k": components }),
+            }
+        }
+        Animated::Keyframes(keyframes) => {
+            let last = keyframes.len() - 1;
+            let keyframes: Vec<Value> = keyframes
+                .iter()
+                .enumerate()
+                .map(|(index, keyframe)| {
+                    let mut object = Map::new();
+                    object.insert("t".into(), json!(keyframe.frame));
+                    object.insert("s".into(), json!(write(keyframe.value.clone())));
+                    if index < last {
+                        if keyframe.hold {
+                            object.insert("h".into(), json!(1));
+                        }
+                        let (x1, y1, x2, y2) = keyframe.easing.control_points();
+                        object.insert("o".into(), json!({ "x": [x1], "y": [y1] }));
+                        object.insert("i".into(), json!({ "x": [x2], "y": [y2] }));
+                    }
+                    Value::Object(object)
+                })
+                .collect();
+            json!({ "a": 1, "k": keyframes })
+        }
+    }
+}
+
+/// An opaque `[r, g, b, 1]` color; alpha goes into opacities instead.
+fn color_json(color: Color) -> Value {
+    json!({ "a": 0, "k": [color.r, color.g, color.b, 1.0] })
+}
+
+fn hex(color: Color) -> String {
+    let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color.r),
+        byte(color.g),
+        byte(color.b)
+    )
+}
+
+/// One subpath as vertices with tangents relative to them.
+#[derive(Debug)]
+struct Contour {
+    vertices: Vec<CGPoint>,
+    ins: Vec<CGPoint>,
+    outs: Vec<CGPoint>,
+    closed: bool,
+}
+
+impl Contour {
+    fn starting_at(point: CGPoint) -> Self {
+        Self {
+            vertices: vec![point],
+            ins: vec![CGPoint::ZERO],
+            outs: vec![CGPoint::ZERO],
+            closed: false,
+        }
+    }
+
+    fn last(&self) -> CGPoint {
+        self.vertices[self.vertices.len() - 1]
+    }
+
+    fn curve_to(&mut self, control1: CGPoint, control2: CGPoint, point: CGPoint) {
+        let last = self.last();
+        let index = self.outs.len() - 1;
+        self.outs[index] = CGPoint::new(control1.x - last.x, control1.y - last.y);
+        self.vertices.push(point);
+        self.ins
+            .push(CGPoint::new(control2.x - point.x, control2.y - point.y));
+        self.outs.push(CGPoint::ZERO);
+    }
+
+    /// Closes the contour, merging an end vertex that repeats the start.
+    fn close(&mut self) {
+        self.closed = true;
+        let (first, last) = (self.vertices[0], self.last());
+        if self.vertices.len() > 1
+            && (first.x - last.x).abs() < 1e-9
+            && (first.y - last.y).abs() < 1e-9
+        {
+            self.vertices.pop();
+            self.outs.pop();
+            if let Some(last_in) = self.ins.pop() {
+                self.ins[0] = last_in;
+            }
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let points =
+            |points: &[CGPoint]| -> Vec<[f64; 2]> { points.iter().map(|p| [p.x, p.y]).collect() };
+        json!({
+            "ty": "sh",
+            "nm": "Path",
+            "ks": {
+                "a": 0,
+                "k": {
+                    "c": self.closed,
+                    "v": points(&self.vertices),
+                    "i": points(&self.ins),
+                    "o": points(&self.outs),
+                },
+            },
+        })
+    }
+}
+
+/// Splits a path into Lottie's vertex-and-tangent contours.
+fn contours(path: &BezierPath) -> Vec<Contour> {
+    let mut contours: Vec<Contour> = Vec::new();
+    let mut current: Option<Contour> = None;
+    for element in path.elements() {
+        match *element {
+            PathElement::MoveTo(point) => {
+                contours.extend(current.take().filter(|contour| contour.vertices.len() > 1));
+                current = Some(Contour::starting_at(point));
+            }
+            PathElement::Close => {
+                if let Some(mut contour) = current.take() {
+                    let start = contour.vertices[0];
+                    contour.close();
+                    contours.push(contour);
+                    // Drawing continues from the start of the closed contour.
+                    current = Some(Contour::starting_at(start));
+                }
+            }
+            _ => {
+                let contour = current.get_or_insert_with(|| Contour::starting_at(CGPoint::ZERO));
+                let last = contour.last();
+                match *element {
+                    PathElement::LineTo(point) => contour.curve_to(last, point, point),
+                    PathElement::QuadCurveTo(control, point) => {
+                        // The cubic with the same shape as the quadratic.
+                        let toward = |from: CGPoint| {
+                            CGPoint::new(
+                                from.x + 2.0 / 3.0 * (control.x - from.x),
+                                from.y + 2.0 / 3.0 * (control.y - from.y),
+                            )
+                        };
+                        contour.curve_to(toward(last), toward(point), point);
+                    }
+                    PathElement::CurveTo(control1, control2, point) => {
+                        contour.curve_to(control1, control2, point);
+                    }
+                    PathElement::MoveTo(_) | PathElement::Close => unreachable!(),
+                }
+            }
+        }
+    }
+    // A trailing move without drawing adds nothing.
+    contours.extend(current.filter(|contour| contour.vertices.len() > 1));
+    contours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lottie::model::KAPPA;
+    use objc2_core_foundation::CGSize;
+
+    fn spinner() -> CAShapeLayerBuilder {
+        CAShapeLayerBuilder::new()
+            .name("spinner")
+            .circle(40.0)
+            .position(CGPoint::new(50.0, 30.0))
+            .fill_color(Color::TRANSPARENT)
+            .stroke_color(Color::CYAN)
+            .line_width(4.0)
+            .line_cap(LineCap::Round)
+            .animate("spin", KeyPath::TransformRotation, |a| {
+                a.values(0.0, -std::f64::consts::TAU)
+                    .duration(Duration::from_secs(1))
+                    .easing(Easing::Linear)
+                    .repeat(Repeat::Forever)
+            })
+    }
+
+    fn values(animated: &Animated<f64>) -> Vec<(f64, f64)> {
+        match animated {
+            Animated::Static(value) => vec![(0.0, *value)],
+            Animated::Keyframes(keyframes) => keyframes
+                .iter()
+                .map(|keyframe| (keyframe.frame, keyframe.value))
+                .collect(),
+        }
+    }
+
+    fn features(composition: &Composition) -> Vec<&str> {
+        composition
+            .unsupported
+            .iter()
+            .map(|unsupported| unsupported.feature.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_structure() {
+        let composition = CompositionExport::new(100.0, 80.0)
+            .name("loader")
+            .layer(spinner())
+            .build();
+        assert!(composition.unsupported.is_empty());
+
+        let json: Value = serde_json::from_str(&composition.to_json()).unwrap();
+        assert_eq!(json["v"], VERSION);
+        assert_eq!(json["nm"], "loader");
+        assert_eq!(json["fr"], 60.0);
+        assert_eq!(json["ip"], 0.0);
+        assert_eq!(json["op"], 60.0);
+        assert_eq!(
+            (json["w"].as_f64(), json["h"].as_f64()),
+            (Some(100.0), Some(80.0))
+        );
+
+        let layer = &json["layers"][0];
+        assert_eq!(layer["ty"], 4);
+        assert_eq!(layer["ind"], 1);
+        assert_eq!(layer["nm"], "spinner");
+        for key in ["a", "p", "s", "r", "o"] {
+            assert!(layer["ks"][key].is_object(), "ks.{key}");
+        }
+        // Flipped to y pointing down
+        assert_eq!(layer["ks"]["p"]["k"], json!([50.0, 50.0, 0.0]));
+        assert_eq!(layer["ks"]["a"]["k"], json!([20.0, -20.0, 0.0]));
+
+        let rotation = &layer["ks"]["r"];
+        assert_eq!(rotation["a"], 1);
+        assert_eq!(rotation["k"][0]["t"], 0.0);
+        assert_eq!(rotation["k"][0]["o"], json!({ "x": [0.0], "y": [0.0] }));
+        assert_eq!(rotation["k"][1]["t"], 60.0);
+        assert_eq!(rotation["k"][1]["s"], json!([360.0]));
+
+        let types: Vec<&str> = layer["shapes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["ty"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, ["sh", "st"]);
+        let stroke = &layer["shapes"][1];
+        assert_eq!(stroke["w"], json!({ "a": 0, "k": 4.0 }));
+        assert_eq!(stroke["lc"], 2);
+        assert_eq!(stroke["c"]["k"], json!([0.0, 1.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pulse = CAShapeLayerBuilder::new()
+            .name("pulse")
+            .bounds(CGRect::new(CGPoint::ZERO, CGSize::new(20.0, 10.0)))
+            .path(
+                BezierPath::new()
+                    .move_to(CGPoint::new(0.0, 0.0))
+                    .line_to(CGPoint::new(20.0, 0.0))
+                    .line_to(CGPoint::new(20.0, 10.0))
+                    .close()
+                    .to_cgpath(),
+            )
+            .fill_color(Color::RED.with_alpha(0.5))
+            .animate("pulse", KeyPath::TransformScale, |a| {
+                a.values(0.85, 1.15)
+                    .duration(Duration::from_millis(800))
+                    .easing(Easing::CubicBezier(0.25, 0.0, 0.75, 1.0))
+                    .autoreverses()
+                    .repeat(Repeat::Forever)
+            });
+        let exported = CompositionExport::new(100.0, 100.0)
+            .frame_rate(30.0)
+            .geometry_flipped()
+            .layer(pulse)
+            .build();
+        let imported = Composition::from_json(&exported.to_json()).unwrap();
+        assert!(
+            imported.unsupported.is_empty(),
+            "{:?}",
+            imported.unsupported
+        );
+        assert_eq!(imported.out_frame, 48.0);
+
+        let layer = &imported.layers[0];
+        assert_eq!(layer.transform.anchor, [10.0, 5.0]);
+        let Animated::Keyframes(scale) = &layer.transform.scale else {
+            panic!("expected an animated scale");
+        };
+        let frames: Vec<(f64, f64)> = scale.iter().map(|k| (k.frame, k.value[0])).collect();
+        assert_eq!(frames, [(0.0, 0.85), (24.0, 1.15), (48.0, 0.85)]);
+        // Reversing a symmetric curve gives the same curve
+        assert_eq!(scale[0].easing, Easing::CubicBezier(0.25, 0.0, 0.75, 1.0));
+        assert_eq!(scale[1].easing, Easing::CubicBezier(0.25, 0.0, 0.75, 1.0));
+
+        let LayerContent::Shapes(group) = &layer.content else {
+            panic!("expected a shape layer");
+        };
+        // Alpha moves into the fill opacity
+        let fill = group.fill.as_ref().unwrap();
+        assert_eq!(fill.color, Color::RED);
+        assert_eq!(fill.opacity, Animated::Static(0.5));
+        assert_eq!(group.stroke, None);
+        assert_eq!(
+            group.path.elements().last(),
+            Some(&PathElement::Close),
+            "closed path"
+        );
+        assert_eq!(group.path.elements().len(), 5);
+    }
+
+    #[test]
+    fn test_timing_expansion() {
+        let layer = CAShapeLayerBuilder::new()
+            .opacity(0.25)
+            .animate("fade", KeyPath::Opacity, |a| {
+                a.values(0.0, 1.0)
+                    .duration(Duration::from_millis(500))
+                    .easing(Easing::Out)
+                    .delay(Duration::from_millis(250))
+                    .repeat(Repeat::Times(2))
+                    .remove_on_completion()
+            })
+            .animate("grow", KeyPath::TransformScaleX, |a| {
+                a.by(0.5)
+                    .duration(Duration::from_secs(1))
+                    .speed(2.0)
+                    .cumulative()
+                    .repeat(Repeat::Times(2))
+            });
+        let composition = CompositionExport::new(10.0, 10.0)
+            .frame_rate(20.0)
+            .layer(layer)
+            .build();
+        assert_eq!(composition.out_frame, 25.0);
+        let transform = &composition.layers[0].transform;
+
+        // Layer value during the delay and after removal; jumps are holds.
+        assert_eq!(
+            values(&transform.opacity),
+            [
+                (0.0, 0.25),
+                (5.0, 0.0),
+                (15.0, 1.0),
+                (15.0, 0.0),
+                (25.0, 1.0),
+                (25.0, 0.25),
+            ]
+        );
+        let Animated::Keyframes(opacity) = &transform.opacity else {
+            unreachable!();
+        };
+        assert!(opacity[0].hold);
+        assert_eq!(opacity[1].easing, Easing::Out);
+        assert!(opacity[2].hold);
+
+        // `by` starts from the layer's scale, and each repeat continues.
+        let scale_x = transform.scale.map(|s| s[0]);
+        assert_eq!(values(&scale_x), [(0.0, 1.0), (10.0, 1.5), (20.0, 2.0)]);
+        assert_eq!(transform.scale.initial(), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_holds_and_fractional_repeats() {
+        let layer = CAShapeLayerBuilder::new().animate("blink", KeyPath::Opacity, |a| {
+            a.values(0.0, 1.0)
+                .duration(Duration::from_millis(500))
+                .easing(Easing::Linear)
+                .autoreverses()
+                .hold_at_end(Duration::from_millis(500))
+        });
+        let composition = CompositionExport::new(10.0, 10.0)
+            .frame_rate(10.0)
+            .layer(layer)
+            .build();
+        assert!(composition.unsupported.is_empty());
+        assert_eq!(composition.out_frame, 15.0);
+        assert_eq!(
+            values(&composition.layers[0].transform.opacity),
+            [(0.0, 0.0), (5.0, 1.0), (10.0, 1.0), (15.0, 0.0)]
+        );
+
+        let layer = CAShapeLayerBuilder::new().animate("fade", KeyPath::Opacity, |a| {
+            a.values(0.0, 1.0).repeat(Repeat::Count(1.5))
+        });
+        let composition = CompositionExport::new(10.0, 10.0).layer(layer).build();
+        assert_eq!(features(&composition), ["fractional repeats"]);
+    }
+
+    #[test]
+    fn test_keyframe_animation() {
+        let layer = CAShapeLayerBuilder::new()
+            .stroke_color(Color::WHITE)
+            .animate_keyframes("draw", KeyPath::Custom("strokeEnd"), |k| {
+                k.values([0.0, 0.8, 1.0])
+                    .key_times([0.0, 0.25, 1.0])
+                    .easings([Easing::In, Easing::Out])
+                    .easing(Easing::Linear)
+                    .duration(Duration::from_secs(2))
+            });
+        let composition = CompositionExport::new(10.0, 10.0)
+            .frame_rate(10.0)
+            .layer(layer)
+            .build();
+        assert!(composition.unsupported.is_empty());
+        let LayerContent::Shapes(group) = &composition.layers[0].content else {
+            panic!("expected a shape layer");
+        };
+        let trim = group.trim.as_ref().unwrap();
+        assert_eq!(trim.start, Animated::Static(0.0));
+        assert_eq!(values(&trim.end), [(0.0, 0.0), (5.0, 0.8), (20.0, 1.0)]);
+
+        // The trim must not cut the fill, so the fill gets its own group.
+        let json: Value = serde_json::from_str(&composition.to_json()).unwrap();
+        let types: Vec<&str> = json["layers"][0]["shapes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["ty"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, ["tm", "st", "gr"]);
+    }
+
+    #[test]
+    fn test_reports_unsupported() {
+        let layer = CAShapeLayerBuilder::new()
+            .name("dot")
+            .animate("tint", KeyPath::BackgroundColor, |a| a)
+            .animate("fade", KeyPath::Opacity, |a| a.values(0.0, 1.0))
+            .animate("blink", KeyPath::Opacity, |a| a.values(1.0, 0.0))
+            .animate("x", KeyPath::PositionX, |a| {
+                a.values(0.0, 10.0).on_complete(|_| {})
+            })
+            .animate("y", KeyPath::PositionY, |a| {
+                a.values(0.0, 10.0).duration(Duration::from_secs(1))
+            })
+            .animate("wave", KeyPath::TransformRotation, |a| {
+                a.values(0.0, 1.0).phase_offset(0.5)
+            })
+            .animate_keyframes("bounce", KeyPath::TransformScaleY, |k| {
+                k.values([1.0, 1.2, 1.0])
+                    .calculation_mode(CalculationMode::Paced)
+            });
+        let composition = CompositionExport::new(10.0, 10.0).layer(layer).build();
+        assert_eq!(
+            features(&composition),
+            [
+                "backgroundColor animations",
+                "start and completion callbacks",
+                "phase offsets of animations that end",
+                "paced keyframe timing",
+                "easing across several keyframes",
+                "several animations of one property",
+                "x and y animations with different timing",
+            ]
+        );
+        assert_eq!(
+            composition.unsupported[0].to_string(),
+            "dot.tint: backgroundColor animations"
+        );
+        assert_eq!(composition.unsupported[5].location, "dot.blink");
+        assert_eq!(composition.unsupported[6].location, "dot.position");
+    }
+
+    #[test]
+    fn test_reports_unsupported_layer_settings() {
+        let layer = CAShapeLayerBuilder::new()
+            .name("glow")
+            .shadow_opacity(0.5)
+            .translate(5.0, 0.0)
+            .animate_motion("orbit", |m| m);
+        let composition = CompositionExport::new(10.0, 10.0).layer(layer).build();
+        assert_eq!(
+            features(&composition),
+            ["translations", "shadows", "motion paths"]
+        );
+        assert_eq!(composition.unsupported[0].location, "glow");
+        assert_eq!(composition.unsupported[2].location, "glow.orbit");
+    }
+
+    #[test]
+    fn test_contours() {
+        let rect = BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(10.0, 0.0))
+            .line_to(CGPoint::new(10.0, 10.0))
+            .line_to(CGPoint::new(0.0, 10.0))
+            .close()
+            .move_to(CGPoint::new(20.0, 0.0))
+            .quad_curve_to(CGPoint::new(23.0, 3.0), CGPoint::new(26.0, 0.0));
+        let contours = contours(&rect);
+        assert_eq!(contours.len(), 2);
+        assert!(contours[0].closed);
+        assert_eq!(contours[0].vertices.len(), 4);
+        assert!(!contours[1].closed);
+        assert_eq!(contours[1].outs[0], CGPoint::new(2.0, 2.0));
+        assert_eq!(contours[1].ins[1], CGPoint::new(-2.0, 2.0));
+
+        // A curve back to the start merges with the first vertex.
+        let circle = contours_of_circle();
+        assert_eq!(circle.vertices.len(), 4);
+        assert!(circle.closed);
+        assert_ne!(circle.ins[0], CGPoint::ZERO);
+    }
+
+    /// An ellipse filling `rect`, with the same start point and direction as
+    /// `CGPath::with_ellipse_in_rect`, so trimmed strokes match.
+    fn ellipse_in_rect(rect: CGRect) -> BezierPath {
+        let (rx, ry) = (rect.size.width / 2.0, rect.size.height / 2.0);
+        let (cx, cy) = (rect.origin.x + rx, rect.origin.y + ry);
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+        BezierPath::new()
+            .move_to(CGPoint::new(cx + rx, cy))
+            .curve_to(
+                CGPoint::new(cx + rx, cy + ky),
+                CGPoint::new(cx + kx, cy + ry),
+                CGPoint::new(cx, cy + ry),
+            )
+            .curve_to(
+                CGPoint::new(cx - kx, cy + ry),
+                CGPoint::new(cx - rx, cy + ky),
+                CGPoint::new(cx - rx, cy),
+            )
+            .curve_to(
+                CGPoint::new(cx - rx, cy - ky),
+                CGPoint::new(cx - kx, cy - ry),
+                CGPoint::new(cx, cy - ry),
+            )
+            .curve_to(
+                CGPoint::new(cx + kx, cy - ry),
+                CGPoint::new(cx + rx, cy - ky),
+                CGPoint::new(cx + rx, cy),
+            )
+            .close()
+    }
+
+    fn contours_of_circle() -> Contour {
+        let mut contours = contours(&ellipse_in_rect(CGRect::new(
+            CGPoint::ZERO,
+            CGSize::new(10.0, 10.0),
+        )));
+        assert_eq!(contours.len(), 1);
+        contours.remove(0)
+    }
+
+    #[test]
+    fn test_imported_sample_survives_export() {
+        let original = Composition::from_json(include_str!("samples/pulse.json")).unwrap();
+        let copy = Composition::from_json(&original.to_json()).unwrap();
+        assert!(copy.unsupported.is_empty(), "{:?}", copy.unsupported);
+        assert_eq!(copy.layers.len(), original.layers.len());
+        for (copy, original) in copy.layers.iter().zip(&original.layers) {
+            assert_eq!(copy.name, original.name);
+            assert_eq!(copy.transform.anchor, original.transform.anchor);
+            assert_eq!(copy.transform.position, original.transform.position);
+        }
+        let (Animated::Keyframes(copy), Animated::Keyframes(original)) = (
+            &copy.layers[0].transform.scale,
+            &original.layers[0].transform.scale,
+        ) else {
+            panic!("expected an animated scale");
+        };
+        for (copy, original) in copy.iter().zip(original) {
+            assert_eq!(copy.frame, original.frame);
+            assert_eq!(copy.easing, original.easing);
+            assert!((copy.value[0] - original.value[0]).abs() < 1e-9);
+        }
+    }
+}
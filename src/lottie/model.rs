@@ -0,0 +1,1192 @@
+//! Intermediate model of a Lottie composition and its JSON parser.
+//!
+//! Values are converted to this crate's units while parsing: rotations in
+//! radians, scales as fractions (1.0 = 100%), opacities and trim ranges
+//! from 0.0 to 1.0. Times stay in frames.
+
+use std::fmt;
+
+use objc2_core_foundation::CGPoint;
+use serde_json::{Map, Value};
+
+use crate::animation_builder::Easing;
+use crate::color::Color;
+use crate::path::BezierPath;
+pub use crate::shape_layer_builder::{LineCap, LineJoin};
+
+/// A 2D value, such as a position or a scale, as `[x, y]`.
+pub type Vec2 = [f64; 2];
+
+/// Control point distance for approximating a quarter circle with a cubic.
+pub(crate) const KAPPA: f64 = 0.552_284_749_8;
+
+/// A parsed Lottie animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    /// Name of the composition (`nm`).
+    pub name: String,
+    /// Width in points (`w`).
+    pub width: f64,
+    /// Height in points (`h`).
+    pub height: f64,
+    /// Frames per second (`fr`).
+    pub frame_rate: f64,
+    /// First frame (`ip`).
+    pub in_frame: f64,
+    /// Frame at which the animation ends (`op`).
+    pub out_frame: f64,
+    /// Layers, topmost first.
+    pub layers: Vec<Layer>,
+    /// Features that were skipped or simplified while parsing.
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl Composition {
+    /// Parses a Lottie (Bodymovin) JSON document.
+    ///
+    /// Fails only if the document is not JSON or lacks the composition's
+    /// size, frame rate or frame range. Anything else that cannot be
+    /// represented is recorded in [`unsupported`](Self::unsupported).
+    pub fn from_json(json: &str) -> Result<Self, LottieError> {
+        let root: Value =
+            serde_json::from_str(json).map_err(|err| LottieError::Json(err.to_string()))?;
+        Parser::default().composition(&root)
+    }
+
+    /// Length of the animation in seconds.
+    pub fn duration_secs(&self) -> f64 {
+        (self.out_frame - self.in_frame) / self.frame_rate
+    }
+}
+
+/// A layer of a composition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    /// Name of the layer (`nm`).
+    pub name: String,
+    /// Index other layers refer to as their parent (`ind`).
+    pub index: Option<i64>,
+    /// Index of the layer whose transform this layer follows (`parent`).
+    pub parent: Option<i64>,
+    /// Frame at which the layer appears (`ip`).
+    pub in_frame: f64,
+    /// Frame at which the layer disappears (`op`).
+    pub out_frame: f64,
+    /// Placement of the layer (`ks`).
+    pub transform: Transform,
+    /// What the layer draws.
+    pub content: LayerContent,
+}
+
+/// What a [`Layer`] draws.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerContent {
+    /// Nothing; only used as a parent for other layers (type 3).
+    Null,
+    /// A rectangle of solid color, with its top-left corner at the layer's
+    /// origin (type 1).
+    Solid {
+        /// Fill color (`sc`).
+        color: Color,
+        /// Width (`sw`).
+        width: f64,
+        /// Height (`sh`).
+        height: f64,
+    },
+    /// Vector shapes (type 4).
+    Shapes(Box<ShapeGroup>),
+}
+
+/// Placement of a layer or shape group.
+///
+/// The point `anchor` of the content is placed at `position`, and scale and
+/// rotation happen around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    /// Point of the content that `position` refers to (`a`).
+    pub anchor: Vec2,
+    /// Position in the parent (`p`).
+    pub position: Animated<Vec2>,
+    /// Scale, 1.0 = 100% (`s`).
+    pub scale: Animated<Vec2>,
+    /// Clockwise rotation in radians (`r`).
+    pub rotation: Animated<f64>,
+    /// Opacity, from 0.0 to 1.0 (`o`).
+    pub opacity: Animated<f64>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            anchor: [0.0, 0.0],
+            position: Animated::Static([0.0, 0.0]),
+            scale: Animated::Static([1.0, 1.0]),
+            rotation: Animated::Static(0.0),
+            opacity: Animated::Static(1.0),
+        }
+    }
+}
+
+/// A group of shapes sharing a transform, fill and stroke (`gr`).
+///
+/// The paths of a group are drawn with its fill, then its stroke. Groups
+/// without a fill or stroke of their own use the closest one of an
+/// enclosing group.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShapeGroup {
+    /// Name of the group (`nm`).
+    pub name: String,
+    /// Placement of the group (`tr`).
+    pub transform: Transform,
+    /// All rectangles, ellipses and paths of the group, as one path.
+    pub path: BezierPath,
+    /// Fill of the paths (`fl`).
+    pub fill: Option<Fill>,
+    /// Outline of the paths (`st`).
+    pub stroke: Option<Stroke>,
+    /// Visible section of the strokes (`tm`).
+    pub trim: Option<Trim>,
+    /// Nested groups, topmost first.
+    pub groups: Vec<ShapeGroup>,
+}
+
+/// A solid fill (`fl`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    /// Fill color (`c`).
+    pub color: Color,
+    /// Opacity, from 0.0 to 1.0 (`o`).
+    pub opacity: Animated<f64>,
+}
+
+/// A solid outline (`st`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    /// Stroke color (`c`).
+    pub color: Color,
+    /// Opacity, from 0.0 to 1.0 (`o`).
+    pub opacity: Animated<f64>,
+    /// Line width (`w`).
+    pub width: Animated<f64>,
+    /// Shape of line ends (`lc`).
+    pub cap: LineCap,
+    /// Shape of corners (`lj`).
+    pub join: LineJoin,
+}
+
+/// The visible section of a stroke (`tm`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trim {
+    /// Start of the section, from 0.0 to 1.0 (`s`).
+    pub start: Animated<f64>,
+    /// End of the section, from 0.0 to 1.0 (`e`).
+    pub end: Animated<f64>,
+}
+
+/// A property that is either fixed or changes over time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Animated<T> {
+    /// The same value throughout.
+    Static(T),
+    /// Values at given frames, in order. Never empty.
+    Keyframes(Vec<Keyframe<T>>),
+}
+
+impl<T: Clone> Animated<T> {
+    /// Returns the value at the first frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes.
+    pub fn initial(&self) -> T {
+        match self {
+            Animated::Static(value) => value.clone(),
+            Animated::Keyframes(keyframes) => keyframes[0].value.clone(),
+        }
+    }
+
+    /// Applies `f` to every value.
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Animated<U> {
+        match self {
+            Animated::Static(value) => Animated::Static(f(value.clone())),
+            Animated::Keyframes(keyframes) => Animated::Keyframes(
+                keyframes
+                    .iter()
+                    .map(|keyframe| Keyframe {
+                        frame: keyframe.frame,
+                        value: f(keyframe.value.clone()),
+                        easing: keyframe.easing,
+                        hold: keyframe.hold,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns `true` if the value changes over time.
+    pub fn is_animated(&self) -> bool {
+        matches!(self, Animated::Keyframes(_))
+    }
+}
+
+/// A value at a given frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    /// Frame of the keyframe (`t`).
+    pub frame: f64,
+    /// Value at that frame (`s`).
+    pub value: T,
+    /// Curve toward the next keyframe (`o` and `i`).
+    pub easing: Easing,
+    /// Keep the value until the next keyframe instead of interpolating (`h`).
+    pub hold: bool,
+}
+
+/// A feature of the source that was skipped or simplified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// Where in the document, e.g. `layers[2].shapes[0]`.
+    pub location: String,
+    /// What was not supported, e.g. `masks`.
+    pub feature: String,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.feature)
+    }
+}
+
+/// Why a Lottie document could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LottieError {
+    /// The text is not valid JSON.
+    Json(String),
+    /// A required field is missing or has the wrong type.
+    Invalid {
+        /// Where in the document, e.g. `layers[0].ks`.
+        location: String,
+        /// What is wrong.
+        reason: String,
+    },
+}
+
+impl fmt::Display for LottieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LottieError::Json(message) => write!(f, "invalid Lottie JSON: {message}"),
+            LottieError::Invalid { location, reason } => write!(f, "{location}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for LottieError {}
+
+fn invalid(location: &str, reason: impl Into<String>) -> LottieError {
+    LottieError::Invalid {
+        location: location.to_string(),
+        reason: reason.into(),
+    }
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+/// Walks the JSON tree, collecting unsupported features on the way.
+#[derive(Default)]
+struct Parser {
+    unsupported: Vec<Unsupported>,
+}
+
+impl Parser {
+    fn skip(&mut self, location: &str, feature: impl Into<String>) {
+        self.unsupported.push(Unsupported {
+            location: location.to_string(),
+            feature: feature.into(),
+        });
+    }
+
+    fn composition(mut self, root: &Value) -> Result<Composition, LottieError> {
+        let root = root
+            .as_object()
+            .ok_or_else(|| invalid("", "expected a JSON object"))?;
+        let required = |key: &str| {
+            root.get(key)
+                .and_then(Value::as_f64)
+                .ok_or_else(|| invalid(key, "is required and must be a number"))
+        };
+        let width = required("w")?;
+        let height = required("h")?;
+        let frame_rate = required("fr")?;
+        let in_frame = required("ip")?;
+        let out_frame = required("op")?;
+        if frame_rate <= 0.0 {
+            return Err(invalid("fr", "must be positive"));
+        }
+        if out_frame <= in_frame {
+            return Err(invalid("op", "must be after `ip`"));
+        }
+
+        if root
+            .get("assets")
+            .and_then(Value::as_array)
+            .is_some_and(|assets| !assets.is_empty())
+        {
+            self.skip("assets", "precompositions and images");
+        }
+        if root.get("fonts").is_some() || root.get("chars").is_some() {
+            self.skip("fonts", "text");
+        }
+        if root
+            .get("markers")
+            .and_then(Value::as_array)
+            .is_some_and(|m| !m.is_empty())
+        {
+            self.skip("markers", "markers");
+        }
+
+        let mut layers = Vec::new();
+        for (index, layer) in array(root, "layers").iter().enumerate() {
+            if let Some(layer) = self.layer(layer, &format!("layers[{index}]"))? {
+                layers.push(layer);
+            }
+        }
+
+        Ok(Composition {
+            name: string(root, "nm"),
+            width,
+            height,
+            frame_rate,
+            in_frame,
+            out_frame,
+            layers,
+            unsupported: self.unsupported,
+        })
+    }
+
+    fn layer(&mut self, value: &Value, location: &str) -> Result<Option<Layer>, LottieError> {
+        let layer = value
+            .as_object()
+            .ok_or_else(|| invalid(location, "expected a layer object"))?;
+        if flag(layer, "hd") {
+            return Ok(None);
+        }
+
+        let kind = layer.get("ty").and_then(Value::as_i64);
+        let content = match kind {
+            Some(1) => LayerContent::Solid {
+                color: Color::from_hex(&string(layer, "sc")).unwrap_or(Color::BLACK),
+                width: layer.get("sw").and_then(Value::as_f64).unwrap_or(0.0),
+                height: layer.get("sh").and_then(Value::as_f64).unwrap_or(0.0),
+            },
+            Some(3) => LayerContent::Null,
+            Some(4) => {
+                let shapes =
+                    self.group_items(array(layer, "shapes"), &format!("{location}.shapes"))?;
+                LayerContent::Shapes(Box::new(shapes))
+            }
+            other => {
+                let feature = match other {
+                    Some(0) => "precomposition layers",
+                    Some(2) => "image layers",
+                    Some(5) => "text layers",
+                    Some(6) => "audio layers",
+                    Some(13) => "camera layers",
+                    _ => "unknown layer types",
+                };
+                self.skip(location, feature);
+                return Ok(None);
+            }
+        };
+
+        if flag(layer, "ddd") {
+            self.skip(location, "3D layers");
+        }
+        if flag(layer, "hasMask") || !array(layer, "masksProperties").is_empty() {
+            self.skip(location, "masks");
+        }
+        if layer.get("tt").is_some() || flag(layer, "td") {
+            self.skip(location, "track mattes");
+        }
+        if !array(layer, "ef").is_empty() {
+            self.skip(location, "effects");
+        }
+        if layer.get("tm").is_some() {
+            self.skip(location, "time remapping");
+        }
+        if layer.get("bm").and_then(Value::as_i64).unwrap_or(0) != 0 {
+            self.skip(location, "blend modes");
+        }
+        if flag(layer, "ao") {
+            self.skip(location, "auto-orient");
+        }
+        if layer.get("st").and_then(Value::as_f64).unwrap_or(0.0) != 0.0 {
+            self.skip(location, "start time offsets");
+        }
+        if layer.get("sr").and_then(Value::as_f64).unwrap_or(1.0) != 1.0 {
+            self.skip(location, "time stretching");
+        }
+
+        let transform = match layer.get("ks") {
+            Some(ks) => {
+                let location = format!("{location}.ks");
+                let ks = ks
+                    .as_object()
+                    .ok_or_else(|| invalid(&location, "expected a transform object"))?;
+                self.transform(ks, &location)?
+            }
+            None => Transform::default(),
+        };
+
+        Ok(Some(Layer {
+            name: string(layer, "nm"),
+            index: layer.get("ind").and_then(Value::as_i64),
+            parent: layer.get("parent").and_then(Value::as_i64),
+            in_frame: layer
+                .get("ip")
+                .and_then(Value::as_f64)
+                .unwrap_or(f64::NEG_INFINITY),
+            out_frame: layer
+                .get("op")
+                .and_then(Value::as_f64)
+                .unwrap_or(f64::INFINITY),
+            transform,
+            content,
+        }))
+    }
+
+    fn transform(
+        &mut self,
+        ks: &Map<String, Value>,
+        location: &str,
+    ) -> Result<Transform, LottieError> {
+        let mut transform = Transform::default();
+
+        if let Some(anchor) = ks.get("a") {
+            let anchor = self.animated(anchor, &format!("{location}.a"), vec2)?;
+            if anchor.is_animated() {
+                self.skip(&format!("{location}.a"), "animated anchor points");
+            }
+            transform.anchor = anchor.initial();
+        }
+        if let Some(position) = ks.get("p") {
+            transform.position = self.position(position, &format!("{location}.p"))?;
+        }
+        if let Some(scale) = ks.get("s") {
+            transform.scale = self
+                .animated(scale, &format!("{location}.s"), vec2)?
+                .map(|[x, y]| [x / 100.0, y / 100.0]);
+        }
+        if let Some(rotation) = ks.get("r").or_else(|| ks.get("rz")) {
+            transform.rotation = self
+                .animated(rotation, &format!("{location}.r"), scalar)?
+                .map(f64::to_radians);
+        }
+        if let Some(opacity) = ks.get("o") {
+            transform.opacity = self.percentage(opacity, &format!("{location}.o"))?;
+        }
+        if ks.get("rx").is_some() || ks.get("ry").is_some() || ks.get("or").is_some() {
+            self.skip(location, "3D rotation");
+        }
+        let skew = ks
+            .get("sk")
+            .map(|sk| self.animated(sk, &format!("{location}.sk"), scalar));
+        if let Some(skew) = skew.transpose()? {
+            if skew.is_animated() || skew.initial() != 0.0 {
+                self.skip(&format!("{location}.sk"), "skew");
+            }
+        }
+        Ok(transform)
+    }
+
+    /// Parses a position, which may be split into separate X and Y values.
+    fn position(&mut self, value: &Value, location: &str) -> Result<Animated<Vec2>, LottieError> {
+        if !value
+            .get("s")
+            .is_some_and(|s| s.as_bool() == Some(true) || s.as_i64() == Some(1))
+        {
+            return self.animated(value, location, vec2);
+        }
+
+        let x = value
+            .get("x")
+            .ok_or_else(|| invalid(location, "split position lacks `x`"))?;
+        let y = value
+            .get("y")
+            .ok_or_else(|| invalid(location, "split position lacks `y`"))?;
+        let x = self.animated(x, &format!("{location}.x"), scalar)?;
+        let y = self.animated(y, &format!("{location}.y"), scalar)?;
+        match (&x, &y) {
+            (Animated::Static(x), Animated::Static(y)) => Ok(Animated::Static([*x, *y])),
+            (Animated::Keyframes(_), Animated::Static(y)) => Ok(x.map(|x| [x, *y])),
+            (Animated::Static(x), Animated::Keyframes(_)) => Ok(y.map(|y| [*x, y])),
+            (Animated::Keyframes(xs), Animated::Keyframes(ys))
+                if xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| x.frame == y.frame) =>
+            {
+                Ok(Animated::Keyframes(
+                    xs.iter()
+                        .zip(ys)
+                        .map(|(x, y)| Keyframe {
+                            frame: x.frame,
+                            value: [x.value, y.value],
+                            easing: x.easing,
+                            hold: x.hold,
+                        })
+                        .collect(),
+                ))
+            }
+            _ => {
+                self.skip(location, "separate X and Y position keyframes");
+                Ok(Animated::Static([x.initial(), y.initial()]))
+            }
+        }
+    }
+
+    /// Parses a 0–100 value into a 0.0–1.0 one.
+    fn percentage(&mut self, value: &Value, location: &str) -> Result<Animated<f64>, LottieError> {
+        Ok(self
+            .animated(value, location, scalar)?
+            .map(|percent| percent / 100.0))
+    }
+
+    /// Parses an animatable property (`{ "a": 0|1, "k": ... }`).
+    fn animated<T: Clone>(
+        &mut self,
+        value: &Value,
+        location: &str,
+        parse: fn(&Value) -> Option<T>,
+    ) -> Result<Animated<T>, LottieError> {
+        let property = value
+            .as_object()
+            .ok_or_else(|| invalid(location, "expected a property object"))?;
+        if property.get("x").is_some_and(|x| !x.is_null()) {
+            self.skip(location, "expressions");
+        }
+        let k = property
+            .get("k")
+            .ok_or_else(|| invalid(location, "property lacks `k`"))?;
+
+        let keyframes = match k.as_array() {
+            Some(items) if items.first().is_some_and(|item| item.get("t").is_some()) => items,
+            _ => {
+                return parse(k)
+                    .map(Animated::Static)
+                    .ok_or_else(|| invalid(location, "property value has the wrong type"));
+            }
+        };
+
+        let mut spatial = false;
+        let mut parsed: Vec<Keyframe<T>> = Vec::with_capacity(keyframes.len());
+        let mut previous_end = None;
+        for keyframe in keyframes {
+            let frame = keyframe
+                .get("t")
+                .and_then(Value::as_f64)
+                .ok_or_else(|| invalid(location, "keyframe lacks a frame `t`"))?;
+            // Older files put the end value on the previous keyframe (`e`)
+            // and leave the last keyframe without a value.
+            let Some(value) = keyframe.get("s").and_then(parse).or(previous_end.take()) else {
+                continue;
+            };
+            previous_end = keyframe.get("e").and_then(parse);
+            spatial |= ["ti", "to"]
+                .iter()
+                .filter_map(|key| keyframe.get(*key).and_then(Value::as_array))
+                .flatten()
+                .any(|tangent| tangent.as_f64().is_some_and(|v| v != 0.0));
+
+            parsed.push(Keyframe {
+                frame,
+                value,
+                easing: easing(keyframe),
+                hold: keyframe.get("h").and_then(Value::as_i64) == Some(1),
+            });
+        }
+        if spatial {
+            self.skip(location, "curved motion paths");
+        }
+
+        match parsed.len() {
+            0 => Err(invalid(location, "keyframes have no values")),
+            1 => Ok(Animated::Static(parsed.remove(0).value)),
+            _ => Ok(Animated::Keyframes(parsed)),
+        }
+    }
+
+    /// Parses the items of a layer (`shapes`) or group (`it`) into a group.
+    fn group_items(&mut self, items: &[Value], location: &str) -> Result<ShapeGroup, LottieError> {
+        let mut group = ShapeGroup::default();
+        for (index, item) in items.iter().enumerate() {
+            let location = format!("{location}[{index}]");
+            let Some(item) = item.as_object() else {
+                return Err(invalid(&location, "expected a shape object"));
+            };
+            if flag(item, "hd") {
+                continue;
+            }
+
+            match item.get("ty").and_then(Value::as_str).unwrap_or_default() {
+                "gr" => {
+                    let mut nested =
+                        self.group_items(array(item, "it"), &format!("{location}.it"))?;
+                    nested.name = string(item, "nm");
+                    group.groups.push(nested);
+                }
+                "tr" => group.transform = self.transform(item, &location)?,
+                "rc" => {
+                    let center = self.static_value(item, "p", &location, vec2)?;
+                    let size = self.static_value(item, "s", &location, vec2)?;
+                    let radius = match item.get("r") {
+                        Some(_) => self.static_value(item, "r", &location, scalar)?,
+                        None => 0.0,
+                    };
+                    append(&mut group.path, &rectangle(center, size, radius));
+                }
+                "el" => {
+                    let center = self.static_value(item, "p", &location, vec2)?;
+                    let size = self.static_value(item, "s", &location, vec2)?;
+                    append(&mut group.path, &ellipse(center, size));
+                }
+                "sh" => {
+                    let path = self.static_value(item, "ks", &location, bezier)?;
+                    append(&mut group.path, &path);
+                }
+                "fl" => {
+                    let fill = Fill {
+                        color: self.static_value(item, "c", &location, color)?,
+                        opacity: self.percentage(
+                            property(item, "o", &location)?,
+                            &format!("{location}.o"),
+                        )?,
+                    };
+                    if item.get("r").and_then(Value::as_i64) == Some(2) {
+                        self.skip(&location, "even-odd fill rule");
+                    }
+                    if group.fill.is_some() {
+                        self.skip(&location, "more than one fill per group");
+                    } else {
+                        group.fill = Some(fill);
+                    }
+                }
+                "st" => {
+                    let stroke = Stroke {
+                        color: self.static_value(item, "c", &location, color)?,
+                        opacity: self.percentage(
+                            property(item, "o", &location)?,
+                            &format!("{location}.o"),
+                        )?,
+                        width: self.animated(
+                            property(item, "w", &location)?,
+                            &format!("{location}.w"),
+                            scalar,
+                        )?,
+                        cap: match item.get("lc").and_then(Value::as_i64) {
+                            Some(2) => LineCap::Round,
+                            Some(3) => LineCap::Square,
+                            _ => LineCap::Butt,
+                        },
+                        join: match item.get("lj").and_then(Value::as_i64) {
+                            Some(2) => LineJoin::Round,
+                            Some(3) => LineJoin::Bevel,
+                            _ => LineJoin::Miter,
+                        },
+                    };
+                    if !array(item, "d").is_empty() {
+                        self.skip(&location, "dashes");
+                    }
+                    if group.stroke.is_some() {
+                        self.skip(&location, "more than one stroke per group");
+                    } else {
+                        group.stroke = Some(stroke);
+                    }
+                }
+                "tm" => {
+                    let trim = Trim {
+                        start: self.percentage(
+                            property(item, "s", &location)?,
+                            &format!("{location}.s"),
+                        )?,
+                        end: self.percentage(
+                            property(item, "e", &location)?,
+                            &format!("{location}.e"),
+                        )?,
+                    };
+                    if let Some(offset) = item.get("o") {
+                        let offset = self.animated(offset, &format!("{location}.o"), scalar)?;
+                        if offset.is_animated() || offset.initial() != 0.0 {
+                            self.skip(&location, "trim path offsets");
+                        }
+                    }
+                    group.trim = Some(trim);
+                }
+                other => {
+                    let feature = match other {
+                        "gf" => "gradient fills".to_string(),
+                        "gs" => "gradient strokes".to_string(),
+                        "sr" => "stars and polygons".to_string(),
+                        "rp" => "repeaters".to_string(),
+                        "rd" => "rounded corners".to_string(),
+                        "mm" => "merge paths".to_string(),
+                        "tw" => "twist".to_string(),
+                        "op" => "offset paths".to_string(),
+                        "pb" => "pucker and bloat".to_string(),
+                        "zz" => "zig zag".to_string(),
+                        other => format!("shape type {other:?}"),
+                    };
+                    self.skip(&location, feature);
+                }
+            }
+        }
+
+        if group.trim.is_some() && group.fill.is_some() {
+            self.skip(location, "trim paths on fills");
+        }
+        Ok(group)
+    }
+
+    /// Parses a property that this crate draws without animating it.
+    ///
+    /// Animated values are reported and their first value is used.
+    fn static_value<T: Clone>(
+        &mut self,
+        item: &Map<String, Value>,
+        key: &str,
+        location: &str,
+        parse: fn(&Value) -> Option<T>,
+    ) -> Result<T, LottieError> {
+        let location = format!("{location}.{key}");
+        let value = item
+            .get(key)
+            .ok_or_else(|| invalid(&location, "is required"))?;
+        let value = self.animated(value, &location, parse)?;
+        if value.is_animated() {
+            let feature = match key {
+                "c" => "animated colors",
+                "ks" => "animated paths",
+                _ => "animated shape geometry",
+            };
+            self.skip(&location, feature);
+        }
+        Ok(value.initial())
+    }
+}
+
+// ============================================================================
+// Value helpers
+// ============================================================================
+
+fn array<'a>(object: &'a Map<String, Value>, key: &str) -> &'a [Value] {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn string(object: &Map<String, Value>, key: &str) -> String {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn flag(object: &Map<String, Value>, key: &str) -> bool {
+    object
+        .get(key)
+        .is_some_and(|value| value.as_bool() == Some(true) || value.as_i64() == Some(1))
+}
+
+fn property<'a>(
+    object: &'a Map<String, Value>,
+    key: &str,
+    location: &str,
+) -> Result<&'a Value, LottieError> {
+    object
+        .get(key)
+        .ok_or_else(|| invalid(&format!("{location}.{key}"), "is required"))
+}
+
+/// A number, or the first element of an array of numbers.
+fn scalar(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_array()?.first()?.as_f64())
+}
+
+fn vec2(value: &Value) -> Option<Vec2> {
+    let items = value.as_array()?;
+    Some([items.first()?.as_f64()?, items.get(1)?.as_f64()?])
+}
+
+/// An `[r, g, b, a]` color with components from 0 to 1.
+///
+/// Some exporters write 0–255 components; those are scaled down.
+fn color(value: &Value) -> Option<Color> {
+    let items: Vec<f64> = value.as_array()?.iter().filter_map(Value::as_f64).collect();
+    if items.len() < 3 {
+        return None;
+    }
+    let scale = if items[..3].iter().any(|&c| c > 1.0) {
+        255.0
+    } else {
+        1.0
+    };
+    Some(Color::rgba(
+        items[0] / scale,
+        items[1] / scale,
+        items[2] / scale,
+        items.get(3).map_or(1.0, |a| a / scale),
+    ))
+}
+
+/// A path given as vertices with in and out tangents (`{ i, o, v, c }`).
+fn bezier(value: &Value) -> Option<BezierPath> {
+    // Keyframed paths wrap the shape in a one-element array.
+    let value = value
+        .as_array()
+        .and_then(|items| items.first())
+        .unwrap_or(value);
+    let points = |key: &str| -> Option<Vec<CGPoint>> {
+        value
+            .get(key)?
+            .as_array()?
+            .iter()
+            .map(|point| vec2(point).map(|[x, y]| CGPoint::new(x, y)))
+            .collect()
+    };
+    let vertices = points("v")?;
+    let ins = points("i")?;
+    let outs = points("o")?;
+    if ins.len() != vertices.len() || outs.len() != vertices.len() {
+        return None;
+    }
+    let closed = value.get("c").and_then(Value::as_bool).unwrap_or(false);
+
+    let Some(&first) = vertices.first() else {
+        return Some(BezierPath::new());
+    };
+    let offset = |p: CGPoint, d: CGPoint| CGPoint::new(p.x + d.x, p.y + d.y);
+    let segment = |path: BezierPath, from: usize, to: usize| {
+        path.curve_to(
+            offset(vertices[from], outs[from]),
+            offset(vertices[to], ins[to]),
+            vertices[to],
+        )
+    };
+
+    let mut path = BezierPath::new().move_to(first);
+    for index in 1..vertices.len() {
+        path = segment(path, index - 1, index);
+    }
+    if closed {
+        path = segment(path, vertices.len() - 1, 0).close();
+    }
+    Some(path)
+}
+
+/// The curve from a keyframe to the next, from its `o` and `i` handles.
+fn easing(keyframe: &Value) -> Easing {
+    let handle = |key: &str, axis: &str| keyframe.get(key)?.get(axis).and_then(scalar);
+    match (
+        handle("o", "x"),
+        handle("o", "y"),
+        handle("i", "x"),
+        handle("i", "y"),
+    ) {
+        // Handles on the diagonal make a straight line.
+        (Some(x1), Some(y1), Some(x2), Some(y2)) if x1 == y1 && x2 == y2 => Easing::Linear,
+        (Some(x1), Some(y1), Some(x2), Some(y2)) => {
+            Easing::CubicBezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2)
+        }
+        _ => Easing::Linear,
+    }
+}
+
+fn append(path: &mut BezierPath, other: &BezierPath) {
+    for &element in other.elements() {
+        path.push(element);
+    }
+}
+
+/// A rectangle around `center`, with corners rounded by `radius`.
+pub(crate) fn rectangle(center: Vec2, size: Vec2, radius: f64) -> BezierPath {
+    let (left, right) = (center[0] - size[0] / 2.0, center[0] + size[0] / 2.0);
+    let (top, bottom) = (center[1] - size[1] / 2.0, center[1] + size[1] / 2.0);
+    let r = radius.clamp(0.0, size[0].min(size[1]) / 2.0);
+    if r == 0.0 {
+        return BezierPath::new()
+            .move_to(CGPoint::new(right, top))
+            .line_to(CGPoint::new(right, bottom))
+            .line_to(CGPoint::new(left, bottom))
+            .line_to(CGPoint::new(left, top))
+            .close();
+    }
+
+    let c = r * (1.0 - KAPPA);
+    BezierPath::new()
+        .move_to(CGPoint::new(right, top + r))
+        .line_to(CGPoint::new(right, bottom - r))
+        .curve_to(
+            CGPoint::new(right, bottom - c),
+            CGPoint::new(right - c, bottom),
+            CGPoint::new(right - r, bottom),
+        )
+        .line_to(CGPoint::new(left + r, bottom))
+        .curve_to(
+            CGPoint::new(left + c, bottom),
+            CGPoint::new(left, bottom - c),
+            CGPoint::new(left, bottom - r),
+        )
+        .line_to(CGPoint::new(left, top + r))
+        .curve_to(
+            CGPoint::new(left, top + c),
+            CGPoint::new(left + c, top),
+            CGPoint::new(left + r, top),
+        )
+        .line_to(CGPoint::new(right - r, top))
+        .curve_to(
+            CGPoint::new(right - c, top),
+            CGPoint::new(right, top + c),
+            CGPoint::new(right, top + r),
+        )
+        .close()
+}
+
+/// An ellipse around `center`, starting at the top like After Effects.
+pub(crate) fn ellipse(center: Vec2, size: Vec2) -> BezierPath {
+    let [cx, cy] = center;
+    let (rx, ry) = (size[0] / 2.0, size[1] / 2.0);
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    BezierPath::new()
+        .move_to(CGPoint::new(cx, cy - ry))
+        .curve_to(
+            CGPoint::new(cx + kx, cy - ry),
+            CGPoint::new(cx + rx, cy - ky),
+            CGPoint::new(cx + rx, cy),
+        )
+        .curve_to(
+            CGPoint::new(cx + rx, cy + ky),
+            CGPoint::new(cx + kx, cy + ry),
+            CGPoint::new(cx, cy + ry),
+        )
+        .curve_to(
+            CGPoint::new(cx - kx, cy + ry),
+            CGPoint::new(cx - rx, cy + ky),
+            CGPoint::new(cx - rx, cy),
+        )
+        .curve_to(
+            CGPoint::new(cx - rx, cy - ky),
+            CGPoint::new(cx - kx, cy - ry),
+            CGPoint::new(cx, cy - ry),
+        )
+        .close()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::PathElement;
+
+    const PULSE: &str = include_str!("samples/pulse.json");
+    const PARENTED: &str = include_str!("samples/parented.json");
+    const UNSUPPORTED: &str = include_str!("samples/unsupported.json");
+
+    fn features(composition: &Composition) -> Vec<&str> {
+        composition
+            .unsupported
+            .iter()
+            .map(|unsupported| unsupported.feature.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_composition_settings() {
+        let composition = Composition::from_json(PULSE).unwrap();
+        assert_eq!(composition.name, "Pulse");
+        assert_eq!((composition.width, composition.height), (200.0, 200.0));
+        assert_eq!(composition.frame_rate, 30.0);
+        assert_eq!(composition.duration_secs(), 2.0);
+        assert_eq!(composition.layers.len(), 2);
+        assert!(composition.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_keyframes_with_bezier_easing() {
+        let composition = Composition::from_json(PULSE).unwrap();
+        let Animated::Keyframes(scale) = &composition.layers[0].transform.scale else {
+            panic!("scale should be animated");
+        };
+        assert_eq!(scale.len(), 3);
+        assert_eq!(scale[0].frame, 0.0);
+        assert_eq!(scale[0].value, [0.8, 0.8]);
+        assert_eq!(scale[1].value, [1.2, 1.2]);
+        assert_eq!(scale[0].easing, Easing::CubicBezier(0.333, 0.0, 0.667, 1.0));
+        assert_eq!(scale[2].easing, Easing::Linear);
+    }
+
+    #[test]
+    fn test_diagonal_handles_are_linear() {
+        let keyframe = |o: f64, i: f64| serde_json::json!({ "o": { "x": [o], "y": [o] }, "i": { "x": [i], "y": [i] } });
+        assert_eq!(easing(&keyframe(0.0, 1.0)), Easing::Linear);
+        assert_eq!(easing(&keyframe(0.25, 0.75)), Easing::Linear);
+        assert_eq!(
+            easing(
+                &serde_json::json!({ "o": { "x": 0.4, "y": 0.0 }, "i": { "x": 0.6, "y": 1.0 } })
+            ),
+            Easing::CubicBezier(0.4, 0.0, 0.6, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_shapes_stroke_and_trim() {
+        let composition = Composition::from_json(PULSE).unwrap();
+        let LayerContent::Shapes(shapes) = &composition.layers[0].content else {
+            panic!("expected a shape layer");
+        };
+        let circle = &shapes.groups[0];
+        assert_eq!(circle.name, "Circle");
+        assert_eq!(
+            circle.path.elements()[0],
+            PathElement::MoveTo(CGPoint::new(0.0, -50.0))
+        );
+        let stroke = circle.stroke.as_ref().unwrap();
+        assert_eq!(stroke.color, Color::WHITE);
+        assert_eq!(stroke.width, Animated::Static(6.0));
+        assert_eq!(stroke.cap, LineCap::Round);
+        assert_eq!(stroke.join, LineJoin::Round);
+
+        let trim = circle.trim.as_ref().unwrap();
+        assert_eq!(trim.start, Animated::Static(0.0));
+        let Animated::Keyframes(end) = &trim.end else {
+            panic!("trim end should be animated");
+        };
+        assert_eq!((end[0].value, end[1].value), (0.0, 1.0));
+        assert_eq!(end[1].frame, 45.0);
+    }
+
+    #[test]
+    fn test_fill_rect_and_hold() {
+        let composition = Composition::from_json(PULSE).unwrap();
+        let dot = &composition.layers[1];
+        assert_eq!(dot.in_frame, 10.0);
+        assert!((dot.transform.rotation.initial() - std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+        let Animated::Keyframes(opacity) = &dot.transform.opacity else {
+            panic!("opacity should be animated");
+        };
+        assert!(opacity[0].hold);
+
+        let LayerContent::Shapes(shapes) = &dot.content else {
+            panic!("expected a shape layer");
+        };
+        let fill = shapes.fill.as_ref().unwrap();
+        assert_eq!(fill.color, Color::rgb(1.0, 128.0 / 255.0, 0.0));
+        assert_eq!(fill.opacity, Animated::Static(0.5));
+        // Rounded rectangle: four sides, four corners
+        assert_eq!(shapes.path.elements().len(), 10);
+    }
+
+    #[test]
+    fn test_solid_null_parent_and_split_position() {
+        let composition = Composition::from_json(PARENTED).unwrap();
+        let [background, rig, arrow] = &composition.layers[..] else {
+            panic!("expected three layers");
+        };
+        assert_eq!(background.parent, Some(2));
+        assert_eq!(
+            background.content,
+            LayerContent::Solid {
+                color: Color::from_hex("#1a1a26").unwrap(),
+                width: 320.0,
+                height: 240.0,
+            }
+        );
+        assert_eq!(background.transform.anchor, [160.0, 120.0]);
+
+        assert_eq!(rig.content, LayerContent::Null);
+        let Animated::Keyframes(position) = &rig.transform.position else {
+            panic!("position should be animated");
+        };
+        assert_eq!(position[0].value, [100.0, 120.0]);
+        // Legacy end value on the previous keyframe
+        assert_eq!(position[1].value, [220.0, 120.0]);
+        assert_eq!(
+            position[0].easing,
+            Easing::CubicBezier(0.42, 0.0, 0.58, 1.0)
+        );
+
+        let LayerContent::Shapes(shapes) = &arrow.content else {
+            panic!("expected a shape layer");
+        };
+        assert_eq!(shapes.path.elements().len(), 5);
+        assert_eq!(
+            shapes.fill.as_ref().unwrap().color,
+            Color::rgb(0.2, 0.6, 1.0)
+        );
+        assert_eq!(features(&composition), ["more than one fill per group"]);
+        assert_eq!(
+            composition.unsupported[0].to_string(),
+            "layers[2].shapes[2]: more than one fill per group"
+        );
+    }
+
+    #[test]
+    fn test_reports_unsupported_features() {
+        let composition = Composition::from_json(UNSUPPORTED).unwrap();
+        assert_eq!(composition.layers.len(), 1);
+        assert_eq!(
+            features(&composition),
+            [
+                "precompositions and images",
+                "precomposition layers",
+                "text layers",
+                "gradient fills",
+                "repeaters",
+                "animated colors",
+                "trim path offsets",
+                "trim paths on fills",
+                "masks",
+                "curved motion paths",
+                "expressions",
+            ]
+        );
+        assert_eq!(composition.unsupported[10].location, "layers[2].ks.r");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Composition::from_json("{"),
+            Err(LottieError::Json(_))
+        ));
+        let err = Composition::from_json(r#"{ "w": 10, "h": 10, "ip": 0, "op": 10 }"#).unwrap_err();
+        assert_eq!(err.to_string(), "fr: is required and must be a number");
+        let err = Composition::from_json(
+            r#"{ "w": 10, "h": 10, "fr": 30, "ip": 0, "op": 10,
+                 "layers": [{ "ty": 4, "ks": { "o": { "a": 0 } } }] }"#,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "layers[0].ks.o: property lacks `k`");
+    }
+
+    #[test]
+    fn test_ellipse_and_rectangle_paths() {
+        let circle = ellipse([10.0, 10.0], [20.0, 20.0]);
+        assert_eq!(circle.elements().len(), 6);
+        assert_eq!(
+            circle.elements()[1],
+            PathElement::CurveTo(
+                CGPoint::new(10.0 + 10.0 * KAPPA, 0.0),
+                CGPoint::new(20.0, 10.0 - 10.0 * KAPPA),
+                CGPoint::new(20.0, 10.0)
+            )
+        );
+
+        let square = rectangle([0.0, 0.0], [10.0, 10.0], 0.0);
+        assert_eq!(square.elements().len(), 5);
+        assert_eq!(
+            square.elements()[0],
+            PathElement::MoveTo(CGPoint::new(5.0, -5.0))
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Smooth, seeded noise for organic motion.
+//!
+//! Sine waves repeat too evenly to look alive. [`Noise`] is one-dimensional
+//! gradient (Perlin) noise: it drifts smoothly between -1 and 1 without an
+//! obvious rhythm, is the same for the same seed on every run, and can be
+//! made to loop seamlessly.
+//!
+//! ```ignore
+//! let noise = Noise::new(7).looping(8);
+//! let offset = noise.fractal(t * 2.0, 2) * 10.0;
+//! ```
+//!
+//! To animate a layer with it, use
+//! [`CAKeyframeAnimationBuilder::wiggle`](crate::animation_builder::CAKeyframeAnimationBuilder::wiggle)
+//! and
+//! [`wiggle_point`](crate::animation_builder::CAKeyframeAnimationBuilder::wiggle_point),
+//! which bake the noise into a looping keyframe animation:
+//!
+//! ```ignore
+//! // A bubble that drifts around its position, never the same way twice
+//! let float = CAKeyframeAnimationBuilder::new(KeyPath::Position)
+//!     .wiggle_point(0.5, 6.0, bubble_index)
+//!     .build();
+//! bubble.addAnimation_forKey(&float, Some(ns_string!("float")));
+//! ```
+
+use crate::random::{mix, Rng};
+
+/// Seeded one-dimensional gradient noise.
+///
+/// Values lie between -1 and 1. The noise passes through zero once per
+/// unit, at a seeded fraction past each whole number, and bends smoothly
+/// towards a random slope there. One unit of input is roughly one change
+/// of direction.
+///
+/// # Examples
+///
+/// ```ignore
+/// let noise = Noise::new(42);
+/// assert_eq!(noise.sample(0.3), Noise::new(42).sample(0.3));
+///
+/// // Repeats every 4 units, so 0.0 and 4.0 line up
+/// let looping = Noise::new(42).looping(4);
+/// assert_eq!(looping.sample(0.5), looping.sample(4.5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Noise {
+    seed: u64,
+    period: Option<u64>,
+}
+
+impl Noise {
+    /// Creates noise for the given seed.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { seed, period: None }
+    }
+
+    /// Makes the noise repeat every `period` units.
+    ///
+    /// The value and its slope both continue across the seam, so motion
+    /// baked from `0.0` to `period` loops without a visible jump. A period
+    /// of zero is treated as one.
+    #[must_use]
+    pub fn looping(mut self, period: u32) -> Self {
+        self.period = Some(u64::from(period.max(1)));
+        self
+    }
+
+    /// Returns the noise at `x`, between -1.0 and 1.0.
+    pub fn sample(&self, x: f64) -> f64 {
+        // Shifting the lattice per seed keeps the zeros of different seeds,
+        // such as the octaves of `fractal`, from lining up at whole numbers.
+        let x = x + self.shift();
+        let cell = x.floor();
+        let t = x - cell;
+        let left = self.gradient(cell as i64) * t;
+        let right = self.gradient(cell as i64 + 1) * (t - 1.0);
+        // Quintic fade, so the slope changes smoothly at whole numbers too.
+        let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        // Two unit slopes meet at most at 0.5, so this spans -1.0 to 1.0.
+        2.0 * (left + (right - left) * fade)
+    }
+
+    /// Returns several octaves of noise added up, between -1.0 and 1.0.
+    ///
+    /// Each octave is twice as fast and half as strong as the one before,
+    /// adding finer detail on top of the overall drift. Looping noise stays
+    /// looping, since every octave repeats within the period. At most 16
+    /// octaves are used.
+    pub fn fractal(&self, x: f64, octaves: u32) -> f64 {
+        let mut total = 0.0;
+        let mut weight = 1.0;
+        let mut weights = 0.0;
+        for octave in 0..octaves.clamp(1, 16) {
+            let noise = Noise {
+                seed: mix(self.seed, u64::from(octave)),
+                period: self.period.map(|period| period << octave),
+            };
+            total += noise.sample(x * f64::from(1u32 << octave)) * weight;
+            weights += weight;
+            weight /= 2.0;
+        }
+        total / weights
+    }
+
+    /// How far the lattice is shifted, between 0.0 and 1.0.
+    ///
+    /// A constant shift leaves the period, and so the loop, intact.
+    fn shift(&self) -> f64 {
+        Rng::new(self.seed).next_f64()
+    }
+
+    /// The slope at whole number `index`, between -1.0 and 1.0.
+    fn gradient(&self, index: i64) -> f64 {
+        let index = match self.period {
+            Some(period) => index.rem_euclid(period as i64),
+            None => index,
+        };
+        Rng::new(mix(self.seed, index as u64)).next_f64() * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let noise = Noise::new(7);
+        for i in 0..100 {
+            let x = f64::from(i) * 0.37;
+            assert_eq!(noise.sample(x), Noise::new(7).sample(x));
+        }
+        assert_ne!(Noise::new(7).sample(0.5), Noise::new(8).sample(0.5));
+    }
+
+    #[test]
+    fn test_range_and_zero_crossings() {
+        let noise = Noise::new(3);
+        for i in 0..2000 {
+            let x = f64::from(i) * 0.013 - 10.0;
+            let value = noise.sample(x);
+            assert!((-1.0..=1.0).contains(&value), "{value} at {x}");
+            assert!((-1.0..=1.0).contains(&noise.fractal(x, 3)));
+        }
+        // Zero once per unit, at the same seeded fraction past each whole
+        // number
+        let zero = 1.0 - noise.shift();
+        assert!(noise.sample(5.0 + zero).abs() < 1e-12);
+        assert!(noise.sample(-2.0 + zero).abs() < 1e-12);
+        assert_ne!(zero, 1.0 - Noise::new(4).shift());
+        // Octaves do not all cross zero at whole numbers
+        assert!((0..8).any(|i| noise.fractal(f64::from(i), 3).abs() > 0.01));
+    }
+
+    #[test]
+    fn test_looping_is_seamless() {
+        for seed in 0..20 {
+            let noise = Noise::new(seed).looping(5);
+            for i in 0..50 {
+                let x = f64::from(i) * 0.1;
+                assert!((noise.sample(x) - noise.sample(x + 5.0)).abs() < 1e-12);
+                assert!((noise.fractal(x, 3) - noise.fractal(x + 5.0, 3)).abs() < 1e-12);
+            }
+            // The slope matches across the seam too
+            let h = 1e-6;
+            let before = (noise.fractal(5.0, 2) - noise.fractal(5.0 - h, 2)) / h;
+            let after = (noise.fractal(h, 2) - noise.fractal(0.0, 2)) / h;
+            assert!((before - after).abs() < 1e-3, "{before} != {after}");
+        }
+    }
+
+    #[test]
+    fn test_smooth() {
+        // No jumps between nearby inputs, including across whole numbers
+        let noise = Noise::new(11);
+        for i in 0..1000 {
+            let x = f64::from(i) * 0.01;
+            assert!((noise.sample(x + 0.001) - noise.sample(x)).abs() < 0.01);
+        }
+    }
+}
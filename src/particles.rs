@@ -25,6 +25,7 @@
 //! For simple point bursts, use [`PointBurstBuilder`] instead.
 
 use crate::color::Color;
+use crate::settings::{AnimationSettings, MotionPolicy};
 use objc2::rc::Retained;
 use objc2::runtime::AnyObject;
 use objc2_core_foundation::{CFRetained, CGPoint, CGRect, CGSize};
@@ -192,6 +193,9 @@ impl CAEmitterCellBuilder {
     }
 
     /// Set the number of particles spawned per second.
+    ///
+    /// Capped when built, according to the
+    /// [`MotionPolicy`](crate::settings::MotionPolicy).
     pub fn birth_rate(mut self, rate: f32) -> Self {
         self.birth_rate = rate;
         self
@@ -326,7 +330,7 @@ impl CAEmitterCellBuilder {
     pub fn build(self) -> Retained<CAEmitterCell> {
         let cell = CAEmitterCell::new();
 
-        cell.setBirthRate(self.birth_rate);
+        cell.setBirthRate(AnimationSettings::motion_policy().birth_rate(self.birth_rate));
         cell.setLifetime(self.lifetime);
         cell.setLifetimeRange(self.lifetime_range);
         cell.setVelocity(self.velocity);
@@ -481,7 +485,12 @@ impl CAEmitterLayerBuilder {
 
         emitter.setEmitterPosition(CGPoint::new(self.position.0, self.position.1));
         emitter.setEmitterSize(CGSize::new(self.size.0, self.size.1));
-        emitter.setBirthRate(self.birth_rate);
+        // The cells cap their own rates; don't let the multiplier undo that.
+        let birth_rate = match AnimationSettings::motion_policy() {
+            MotionPolicy::Full => self.birth_rate,
+            MotionPolicy::Reduced | MotionPolicy::None => self.birth_rate.min(1.0),
+        };
+        emitter.setBirthRate(birth_rate);
 
         // Set shape
         unsafe {
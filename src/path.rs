@@ -0,0 +1,1039 @@
+//! Bézier path model with `CGPath` conversion.
+//!
+//! `CGPath` is opaque, so [`BezierPath`] mirrors its elements in plain Rust.
+//! This lets shapes be inspected, resampled and compared before they are
+//! handed back to Core Animation.
+//!
+//! ```ignore
+//! let play = BezierPath::new()
+//!     .move_to(CGPoint::new(0.0, 0.0))
+//!     .line_to(CGPoint::new(0.0, 40.0))
+//!     .line_to(CGPoint::new(35.0, 20.0))
+//!     .close();
+//!
+//! let cgpath = play.to_cgpath();
+//! ```
+//!
+//! # Morphing
+//!
+//! Core Animation only interpolates `path` smoothly when both paths have the
+//! same number and kind of elements. [`normalize_for_morph`] rewrites two
+//! arbitrary paths into matching cubic segments:
+//!
+//! 1. Lines, quadratic curves and implicit close segments become cubics.
+//! 2. Subpaths are paired up; a missing subpath becomes a collapsed point.
+//! 3. The longest segments are split until both subpaths have equal counts.
+//! 4. Closed subpaths are rotated (and reversed if needed) so their start
+//!    points line up, which avoids the "twisting" artifact.
+//!
+//! # Measuring
+//!
+//! [`PathMeasure`] maps distances along a path to points, tangents and
+//! sub-paths. Motion paths use it to start or end part of the way along a
+//! curve.
+
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+use objc2_core_foundation::{CFRetained, CGFloat, CGPoint};
+use objc2_core_graphics::{CGMutablePath, CGPath, CGPathElement, CGPathElementType};
+
+/// A single path element, mirroring `CGPathElement`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathElement {
+    /// Starts a new subpath at the point.
+    MoveTo(CGPoint),
+    /// Straight line to the point.
+    LineTo(CGPoint),
+    /// Quadratic curve with one control point, then the end point.
+    QuadCurveTo(CGPoint, CGPoint),
+    /// Cubic curve with two control points, then the end point.
+    CurveTo(CGPoint, CGPoint, CGPoint),
+    /// Closes the current subpath with a line back to its start.
+    Close,
+}
+
+/// A path made of [`PathElement`]s.
+///
+/// Build one with the chained methods or convert from an existing `CGPath`:
+///
+/// ```ignore
+/// let square = BezierPath::new()
+///     .move_to(CGPoint::new(0.0, 0.0))
+///     .line_to(CGPoint::new(40.0, 0.0))
+///     .line_to(CGPoint::new(40.0, 40.0))
+///     .line_to(CGPoint::new(0.0, 40.0))
+///     .close();
+///
+/// let circle = BezierPath::from_cgpath(&circle_cgpath);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BezierPath {
+    elements: Vec<PathElement>,
+}
+
+impl BezierPath {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new subpath at `point`.
+    pub fn move_to(mut self, point: CGPoint) -> Self {
+        self.elements.push(PathElement::MoveTo(point));
+        self
+    }
+
+    /// Adds a straight line to `point`.
+    pub fn line_to(mut self, point: CGPoint) -> Self {
+        self.elements.push(PathElement::LineTo(point));
+        self
+    }
+
+    /// Adds a quadratic curve to `point`.
+    pub fn quad_curve_to(mut self, control: CGPoint, point: CGPoint) -> Self {
+        self.elements.push(PathElement::QuadCurveTo(control, point));
+        self
+    }
+
+    /// Adds a cubic curve to `point`.
+    pub fn curve_to(mut self, control1: CGPoint, control2: CGPoint, point: CGPoint) -> Self {
+        self.elements
+            .push(PathElement::CurveTo(control1, control2, point));
+        self
+    }
+
+    /// Closes the current subpath.
+    pub fn close(mut self) -> Self {
+        self.elements.push(PathElement::Close);
+        self
+    }
+
+    /// Appends an element.
+    pub fn push(&mut self, element: PathElement) {
+        self.elements.push(element);
+    }
+
+    /// Returns the elements of this path.
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+
+    /// Returns `true` if the path has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the same shape drawn in the opposite direction.
+    ///
+    /// Subpaths are reversed in order as well, and all segments become
+    /// cubic curves.
+    pub fn reversed(&self) -> Self {
+        let contours: Vec<Contour> = self
+            .contours()
+            .into_iter()
+            .rev()
+            .map(|contour| Contour {
+                segments: reverse_segments(&contour.segments),
+                closed: contour.closed,
+            })
+            .collect();
+        Self::from_contours(&contours)
+    }
+
+    /// Blends this path towards `to`, point by point.
+    ///
+    /// * `t` - 0.0 = this path, 1.0 = `to`
+    ///
+    /// Returns `None` if the element lists differ in kind, which is when
+    /// Core Animation snaps instead of morphing. Paths returned by
+    /// [`normalize_for_morph`] always match.
+    pub fn interpolate(&self, to: &BezierPath, t: CGFloat) -> Option<BezierPath> {
+        if self.elements.len() != to.elements.len() {
+            return None;
+        }
+        self.elements
+            .iter()
+            .zip(&to.elements)
+            .map(|(a, b)| {
+                Some(match (*a, *b) {
+                    (PathElement::MoveTo(p0), PathElement::MoveTo(p1)) => {
+                        PathElement::MoveTo(lerp(p0, p1, t))
+                    }
+                    (PathElement::LineTo(p0), PathElement::LineTo(p1)) => {
+                        PathElement::LineTo(lerp(p0, p1, t))
+                    }
+                    (PathElement::QuadCurveTo(c0, p0), PathElement::QuadCurveTo(c1, p1)) => {
+                        PathElement::QuadCurveTo(lerp(c0, c1, t), lerp(p0, p1, t))
+                    }
+                    (PathElement::CurveTo(a0, b0, p0), PathElement::CurveTo(a1, b1, p1)) => {
+                        PathElement::CurveTo(lerp(a0, a1, t), lerp(b0, b1, t), lerp(p0, p1, t))
+                    }
+                    (PathElement::Close, PathElement::Close) => PathElement::Close,
+                    _ => return None,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|elements| BezierPath { elements })
+    }
+
+    /// Splits the path into subpaths made only of cubic segments.
+    fn contours(&self) -> Vec<Contour> {
+        let mut contours = Vec::new();
+        let mut current: Option<Contour> = None;
+        let mut start = CGPoint::ZERO;
+        let mut pen = CGPoint::ZERO;
+
+        for element in &self.elements {
+            match *element {
+                PathElement::MoveTo(p) => {
+                    contours.extend(current.take());
+                    start = p;
+                    pen = p;
+                }
+                PathElement::LineTo(p) => {
+                    current
+                        .get_or_insert_with(Contour::default)
+                        .segments
+                        .push(line_cubic(pen, p));
+                    pen = p;
+                }
+                PathElement::QuadCurveTo(c, p) => {
+                    current
+                        .get_or_insert_with(Contour::default)
+                        .segments
+                        .push(quad_cubic(pen, c, p));
+                    pen = p;
+                }
+                PathElement::CurveTo(c1, c2, p) => {
+                    current
+                        .get_or_insert_with(Contour::default)
+                        .segments
+                        .push([pen, c1, c2, p]);
+                    pen = p;
+                }
+                PathElement::Close => {
+                    if let Some(mut contour) = current.take() {
+                        if pen != start {
+                            contour.segments.push(line_cubic(pen, start));
+                        }
+                        contour.closed = true;
+                        contours.push(contour);
+                    }
+                    pen = start;
+                }
+            }
+        }
+        contours.extend(current);
+        contours
+    }
+
+    /// Rebuilds a path from cubic subpaths.
+    fn from_contours(contours: &[Contour]) -> Self {
+        let mut path = Self::new();
+        for contour in contours {
+            let Some(first) = contour.segments.first() else {
+                continue;
+            };
+            path.push(PathElement::MoveTo(first[0]));
+            for &[_, c1, c2, p] in &contour.segments {
+                path.push(PathElement::CurveTo(c1, c2, p));
+            }
+            if contour.closed {
+                path.push(PathElement::Close);
+            }
+        }
+        path
+    }
+}
+
+/// Rewrites two paths so Core Animation can interpolate between them.
+///
+/// Both returned paths consist of the same number of subpaths, each starting
+/// with `MoveTo`, followed by the same number of `CurveTo` elements and a
+/// `Close` on both sides or neither. The drawn shapes are unchanged, except
+/// that a closed subpath morphing to or from an open one ends with its
+/// closing line rather than a `Close`, so the corner at its start is not
+/// joined.
+///
+/// # Examples
+///
+/// ```ignore
+/// let (from, to) = normalize_for_morph(&triangle, &square);
+/// assert_eq!(from.elements().len(), to.elements().len());
+/// ```
+pub fn normalize_for_morph(from: &BezierPath, to: &BezierPath) -> (BezierPath, BezierPath) {
+    let mut from_contours = from.contours();
+    let mut to_contours = to.contours();
+
+    // Pair up subpaths; the missing side collapses into the other's center.
+    while from_contours.len() < to_contours.len() {
+        let counterpart = &to_contours[from_contours.len()];
+        from_contours.push(counterpart.collapsed());
+    }
+    while to_contours.len() < from_contours.len() {
+        let counterpart = &from_contours[to_contours.len()];
+        to_contours.push(counterpart.collapsed());
+    }
+
+    for (a, b) in from_contours.iter_mut().zip(to_contours.iter_mut()) {
+        let count = a.segments.len().max(b.segments.len());
+        a.subdivide_to(count);
+        b.subdivide_to(count);
+        b.align_to(a);
+        // `Close` on one side only would differ in kind and count. The
+        // closing line is already a segment, so dropping it keeps the shape.
+        let closed = a.closed && b.closed;
+        a.closed = closed;
+        b.closed = closed;
+    }
+
+    (
+        BezierPath::from_contours(&from_contours),
+        BezierPath::from_contours(&to_contours),
+    )
+}
+
+/// Samples per segment in the arc-length lookup table.
+const MEASURE_SAMPLES: usize = 32;
+
+/// Arc-length parametrization of a [`BezierPath`].
+///
+/// Each cubic segment is sampled into a table of cumulative lengths, which
+/// is inverted to find the curve parameter at a given distance. Distances
+/// are in points, measured from the start of the first subpath; jumps
+/// between subpaths do not count.
+///
+/// ```ignore
+/// let measure = PathMeasure::new(&circle);
+/// let quarter = measure.position_at(measure.length() * 0.25);
+/// let half_circle = measure.extract(0.0, measure.length() * 0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathMeasure {
+    segments: Vec<MeasuredSegment>,
+    length: CGFloat,
+}
+
+/// A cubic segment with its arc-length lookup table.
+#[derive(Debug, Clone)]
+struct MeasuredSegment {
+    cubic: Cubic,
+    /// Whether this segment begins a subpath.
+    starts_subpath: bool,
+    /// Distance from the start of the path to the start of this segment.
+    offset: CGFloat,
+    /// Cumulative length at `t = i / MEASURE_SAMPLES`.
+    lengths: [CGFloat; MEASURE_SAMPLES + 1],
+}
+
+impl MeasuredSegment {
+    fn length(&self) -> CGFloat {
+        self.lengths[MEASURE_SAMPLES]
+    }
+
+    /// Curve parameter at `distance` from the start of this segment.
+    fn t_at(&self, distance: CGFloat) -> CGFloat {
+        let i = self
+            .lengths
+            .partition_point(|&l| l <= distance)
+            .clamp(1, MEASURE_SAMPLES);
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        let frac = if l1 > l0 {
+            ((distance - l0) / (l1 - l0)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        ((i - 1) as CGFloat + frac) / MEASURE_SAMPLES as CGFloat
+    }
+}
+
+impl PathMeasure {
+    /// Measures `path`.
+    pub fn new(path: &BezierPath) -> Self {
+        let mut segments = Vec::new();
+        let mut offset = 0.0;
+        for contour in path.contours() {
+            for (i, cubic) in contour.segments.into_iter().enumerate() {
+                let mut lengths = [0.0; MEASURE_SAMPLES + 1];
+                let mut previous = cubic[0];
+                for (j, length) in lengths.iter_mut().enumerate().skip(1) {
+                    let point = cubic_point(&cubic, j as CGFloat / MEASURE_SAMPLES as CGFloat);
+                    *length = distance_squared(previous, point).sqrt();
+                    previous = point;
+                }
+                for j in 1..=MEASURE_SAMPLES {
+                    lengths[j] += lengths[j - 1];
+                }
+                let segment = MeasuredSegment {
+                    cubic,
+                    starts_subpath: i == 0,
+                    offset,
+                    lengths,
+                };
+                offset += segment.length();
+                segments.push(segment);
+            }
+        }
+        Self {
+            segments,
+            length: offset,
+        }
+    }
+
+    /// Total length of the path in points.
+    pub fn length(&self) -> CGFloat {
+        self.length
+    }
+
+    /// Point at `distance` along the path, clamped to the path.
+    ///
+    /// Returns `CGPoint::ZERO` for an empty path.
+    pub fn position_at(&self, distance: CGFloat) -> CGPoint {
+        self.locate(distance, false)
+            .map(|(i, t)| cubic_point(&self.segments[i].cubic, t))
+            .unwrap_or(CGPoint::ZERO)
+    }
+
+    /// Direction of travel at `distance` along the path, in radians.
+    ///
+    /// This is the angle Core Animation rotates a layer to when the
+    /// rotation mode is `auto`.
+    pub fn angle_at(&self, distance: CGFloat) -> CGFloat {
+        self.locate(distance, false)
+            .map(|(i, t)| {
+                let d = cubic_tangent(&self.segments[i].cubic, t);
+                d.y.atan2(d.x)
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the part of the path between two distances.
+    ///
+    /// Distances are clamped to the path and swapped if `start > end`. The
+    /// result is always open and made of cubic segments.
+    pub fn extract(&self, start: CGFloat, end: CGFloat) -> BezierPath {
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let mut path = BezierPath::new();
+        let (Some((i0, t0)), Some((i1, t1))) = (self.locate(start, true), self.locate(end, false))
+        else {
+            return path;
+        };
+
+        for i in i0..=i1.max(i0) {
+            let segment = &self.segments[i];
+            let lo = if i == i0 { t0 } else { 0.0 };
+            let hi = if i == i1 { t1.max(lo) } else { 1.0 };
+            let piece = sub_cubic(&segment.cubic, lo, hi);
+            if i == i0 || segment.starts_subpath {
+                path.push(PathElement::MoveTo(piece[0]));
+            }
+            path.push(PathElement::CurveTo(piece[1], piece[2], piece[3]));
+        }
+        path
+    }
+
+    /// Finds the segment index and curve parameter at `distance`.
+    ///
+    /// At a boundary between segments, `prefer_next` picks the segment that
+    /// starts there instead of the one that ends there.
+    fn locate(&self, distance: CGFloat, prefer_next: bool) -> Option<(usize, CGFloat)> {
+        if self.segments.is_empty() {
+            return None;
+        }
+        let distance = distance.clamp(0.0, self.length);
+        let i = if prefer_next {
+            self.segments
+                .partition_point(|s| s.offset + s.length() <= distance)
+        } else {
+            self.segments
+                .partition_point(|s| s.offset + s.length() < distance)
+        }
+        .min(self.segments.len() - 1);
+        let segment = &self.segments[i];
+        Some((i, segment.t_at(distance - segment.offset)))
+    }
+}
+
+// ============================================================================
+// Cubic segment helpers
+// ============================================================================
+
+/// A cubic segment: start, control 1, control 2, end.
+type Cubic = [CGPoint; 4];
+
+/// A subpath made only of cubic segments.
+#[derive(Debug, Clone, Default)]
+struct Contour {
+    segments: Vec<Cubic>,
+    closed: bool,
+}
+
+impl Contour {
+    /// A single zero-length segment at the center of this contour.
+    fn collapsed(&self) -> Self {
+        let center = if self.segments.is_empty() {
+            CGPoint::ZERO
+        } else {
+            let n = self.segments.len() as CGFloat;
+            let (sx, sy) = self
+                .segments
+                .iter()
+                .fold((0.0, 0.0), |(x, y), s| (x + s[0].x, y + s[0].y));
+            CGPoint::new(sx / n, sy / n)
+        };
+        Self {
+            segments: vec![[center; 4]],
+            closed: self.closed,
+        }
+    }
+
+    /// Splits the longest segments in half until there are `count` of them.
+    fn subdivide_to(&mut self, count: usize) {
+        while self.segments.len() < count {
+            let longest = self
+                .segments
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| polygon_length(a).total_cmp(&polygon_length(b)))
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            let (left, right) = split_cubic(&self.segments[longest], 0.5);
+            self.segments[longest] = left;
+            self.segments.insert(longest + 1, right);
+        }
+    }
+
+    /// Reorders this contour's segments so they line up with `target`.
+    ///
+    /// Closed contours may start at any segment and run in either direction;
+    /// open contours may only be reversed. The candidate with the smallest
+    /// summed squared distance between corresponding points wins.
+    fn align_to(&mut self, target: &Contour) {
+        let n = self.segments.len();
+        if n == 0 || n != target.segments.len() {
+            return;
+        }
+        let shifts = if self.closed && target.closed { n } else { 1 };
+
+        let mut best = (CGFloat::INFINITY, false, 0);
+        for reversed in [false, true] {
+            let candidate = if reversed {
+                reverse_segments(&self.segments)
+            } else {
+                self.segments.clone()
+            };
+            for shift in 0..shifts {
+                let cost: CGFloat = (0..n)
+                    .map(|i| distance_squared(target.segments[i][0], candidate[(i + shift) % n][0]))
+                    .sum();
+                if cost < best.0 {
+                    best = (cost, reversed, shift);
+                }
+            }
+        }
+
+        let (_, reversed, shift) = best;
+        if reversed {
+            self.segments = reverse_segments(&self.segments);
+        }
+        self.segments.rotate_left(shift);
+    }
+}
+
+/// A straight line expressed as a cubic segment.
+fn line_cubic(p0: CGPoint, p1: CGPoint) -> Cubic {
+    [p0, lerp(p0, p1, 1.0 / 3.0), lerp(p0, p1, 2.0 / 3.0), p1]
+}
+
+/// A quadratic curve elevated to a cubic segment.
+fn quad_cubic(p0: CGPoint, c: CGPoint, p1: CGPoint) -> Cubic {
+    [p0, lerp(p0, c, 2.0 / 3.0), lerp(p1, c, 2.0 / 3.0), p1]
+}
+
+/// Splits a cubic at `t` using de Casteljau's algorithm.
+fn split_cubic(c: &Cubic, t: CGFloat) -> (Cubic, Cubic) {
+    let p01 = lerp(c[0], c[1], t);
+    let p12 = lerp(c[1], c[2], t);
+    let p23 = lerp(c[2], c[3], t);
+    let p012 = lerp(p01, p12, t);
+    let p123 = lerp(p12, p23, t);
+    let mid = lerp(p012, p123, t);
+    ([c[0], p01, p012, mid], [mid, p123, p23, c[3]])
+}
+
+/// The part of a cubic between `lo` and `hi`.
+fn sub_cubic(c: &Cubic, lo: CGFloat, hi: CGFloat) -> Cubic {
+    if hi <= 0.0 {
+        return [c[0]; 4];
+    }
+    let (left, _) = split_cubic(c, hi);
+    split_cubic(&left, lo / hi).1
+}
+
+/// Point on a cubic at `t`.
+fn cubic_point(c: &Cubic, t: CGFloat) -> CGPoint {
+    let mt = 1.0 - t;
+    let (a, b, d, e) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+    CGPoint::new(
+        a * c[0].x + b * c[1].x + d * c[2].x + e * c[3].x,
+        a * c[0].y + b * c[1].y + d * c[2].y + e * c[3].y,
+    )
+}
+
+/// Derivative of a cubic at `t`.
+///
+/// Falls back to the chord when the derivative vanishes, which happens at
+/// the ends of segments whose control points coincide with their endpoints.
+fn cubic_tangent(c: &Cubic, t: CGFloat) -> CGPoint {
+    let mt = 1.0 - t;
+    let (a, b, d) = (3.0 * mt * mt, 6.0 * mt * t, 3.0 * t * t);
+    let tangent = CGPoint::new(
+        a * (c[1].x - c[0].x) + b * (c[2].x - c[1].x) + d * (c[3].x - c[2].x),
+        a * (c[1].y - c[0].y) + b * (c[2].y - c[1].y) + d * (c[3].y - c[2].y),
+    );
+    if distance_squared(CGPoint::ZERO, tangent) > 1e-18 {
+        tangent
+    } else {
+        CGPoint::new(c[3].x - c[0].x, c[3].y - c[0].y)
+    }
+}
+
+/// Reverses the drawing direction of a run of segments.
+fn reverse_segments(segments: &[Cubic]) -> Vec<Cubic> {
+    segments
+        .iter()
+        .rev()
+        .map(|&[p0, c1, c2, p1]| [p1, c2, c1, p0])
+        .collect()
+}
+
+/// Length of the control polygon, an upper bound of the arc length.
+fn polygon_length(c: &Cubic) -> CGFloat {
+    c.windows(2)
+        .map(|w| distance_squared(w[0], w[1]).sqrt())
+        .sum()
+}
+
+fn lerp(a: CGPoint, b: CGPoint, t: CGFloat) -> CGPoint {
+    CGPoint::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn distance_squared(a: CGPoint, b: CGPoint) -> CGFloat {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    dx * dx + dy * dy
+}
+
+// ============================================================================
+// CGPath conversions
+// ============================================================================
+
+impl BezierPath {
+    /// Reads the elements of a `CGPath`.
+    pub fn from_cgpath(path: &CGPath) -> Self {
+        let mut elements: Vec<PathElement> = Vec::new();
+        // SAFETY: `info` points to `elements`, which outlives the call, and
+        // `collect_element` only reads the points valid for each element type.
+        unsafe {
+            CGPath::apply(
+                Some(path),
+                (&mut elements as *mut Vec<PathElement>).cast(),
+                Some(collect_element),
+            );
+        }
+        Self { elements }
+    }
+
+    /// Creates a `CGPath` with the elements of this path.
+    pub fn to_cgpath(&self) -> CFRetained<CGPath> {
+        let path = CGMutablePath::new();
+        let p = Some(&*path);
+        let m = std::ptr::null();
+        for element in &self.elements {
+            // SAFETY: A null transform is allowed by all path functions.
+            unsafe {
+                match *element {
+                    PathElement::MoveTo(a) => CGMutablePath::move_to_point(p, m, a.x, a.y),
+                    PathElement::LineTo(a) => CGMutablePath::add_line_to_point(p, m, a.x, a.y),
+                    PathElement::QuadCurveTo(c, a) => {
+                        CGMutablePath::add_quad_curve_to_point(p, m, c.x, c.y, a.x, a.y)
+                    }
+                    PathElement::CurveTo(c1, c2, a) => {
+                        CGMutablePath::add_curve_to_point(p, m, c1.x, c1.y, c2.x, c2.y, a.x, a.y)
+                    }
+                    PathElement::Close => CGMutablePath::close_subpath(p),
+                }
+            }
+        }
+        CGPath::new_copy(Some(&**path)).expect("copying a valid CGPath never fails")
+    }
+}
+
+impl From<&CGPath> for BezierPath {
+    fn from(path: &CGPath) -> Self {
+        Self::from_cgpath(path)
+    }
+}
+
+/// `CGPathApplierFunction` collecting elements into a `Vec<PathElement>`.
+unsafe extern "C-unwind" fn collect_element(info: *mut c_void, element: NonNull<CGPathElement>) {
+    // SAFETY: `info` is the vector passed by `BezierPath::from_cgpath`.
+    let elements = unsafe { &mut *info.cast::<Vec<PathElement>>() };
+    // SAFETY: Core Graphics passes a valid element for the duration of the call.
+    let element = unsafe { element.as_ref() };
+    // SAFETY: The number of points read matches the element type.
+    let point = |i: usize| unsafe { *element.points.as_ptr().add(i) };
+
+    let converted = match element.r#type {
+        CGPathElementType::MoveToPoint => PathElement::MoveTo(point(0)),
+        CGPathElementType::AddLineToPoint => PathElement::LineTo(point(0)),
+        CGPathElementType::AddQuadCurveToPoint => PathElement::QuadCurveTo(point(0), point(1)),
+        CGPathElementType::AddCurveToPoint => PathElement::CurveTo(point(0), point(1), point(2)),
+        _ => PathElement::Close,
+    };
+    elements.push(converted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(origin: CGPoint, size: CGFloat) -> BezierPath {
+        BezierPath::new()
+            .move_to(origin)
+            .line_to(CGPoint::new(origin.x + size, origin.y))
+            .line_to(CGPoint::new(origin.x + size, origin.y + size))
+            .line_to(CGPoint::new(origin.x, origin.y + size))
+            .close()
+    }
+
+    fn triangle() -> BezierPath {
+        BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(0.0, 40.0))
+            .line_to(CGPoint::new(35.0, 20.0))
+            .close()
+    }
+
+    /// Element kinds, so structure can be compared independent of points.
+    fn shape(path: &BezierPath) -> Vec<u8> {
+        path.elements()
+            .iter()
+            .map(|e| match e {
+                PathElement::MoveTo(_) => 0,
+                PathElement::LineTo(_) => 1,
+                PathElement::QuadCurveTo(..) => 2,
+                PathElement::CurveTo(..) => 3,
+                PathElement::Close => 4,
+            })
+            .collect()
+    }
+
+    fn assert_close(a: &BezierPath, b: &BezierPath) {
+        assert_eq!(shape(a), shape(b));
+        let points = |path: &BezierPath| -> Vec<CGPoint> {
+            path.elements()
+                .iter()
+                .flat_map(|e| match *e {
+                    PathElement::MoveTo(p) | PathElement::LineTo(p) => vec![p],
+                    PathElement::QuadCurveTo(c, p) => vec![c, p],
+                    PathElement::CurveTo(c1, c2, p) => vec![c1, c2, p],
+                    PathElement::Close => vec![],
+                })
+                .collect()
+        };
+        for (p, q) in points(a).into_iter().zip(points(b)) {
+            assert!(distance_squared(p, q) < 1e-9, "{p:?} != {q:?}");
+        }
+    }
+
+    fn start_points(path: &BezierPath) -> Vec<CGPoint> {
+        path.elements()
+            .iter()
+            .filter_map(|e| match e {
+                PathElement::MoveTo(p) => Some(*p),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_builder_records_elements() {
+        let path = BezierPath::new()
+            .move_to(CGPoint::new(1.0, 2.0))
+            .quad_curve_to(CGPoint::new(3.0, 4.0), CGPoint::new(5.0, 6.0))
+            .close();
+        assert_eq!(
+            path.elements(),
+            &[
+                PathElement::MoveTo(CGPoint::new(1.0, 2.0)),
+                PathElement::QuadCurveTo(CGPoint::new(3.0, 4.0), CGPoint::new(5.0, 6.0)),
+                PathElement::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_closing_line_becomes_segment() {
+        let contours = triangle().contours();
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        assert_eq!(contours[0].segments.len(), 3);
+        assert_eq!(contours[0].segments[2][3], CGPoint::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_split_cubic_midpoint() {
+        let line = line_cubic(CGPoint::new(0.0, 0.0), CGPoint::new(30.0, 0.0));
+        let (left, right) = split_cubic(&line, 0.5);
+        assert_eq!(left[3], CGPoint::new(15.0, 0.0));
+        assert_eq!(right[0], CGPoint::new(15.0, 0.0));
+        assert_eq!(right[3], CGPoint::new(30.0, 0.0));
+    }
+
+    #[test]
+    fn test_normalize_matches_segment_counts() {
+        let (from, to) = normalize_for_morph(&triangle(), &square(CGPoint::ZERO, 40.0));
+        assert_eq!(shape(&from), shape(&to));
+        assert_eq!(shape(&from), vec![0, 3, 3, 3, 3, 4]);
+    }
+
+    #[test]
+    fn test_normalize_pads_missing_subpaths() {
+        let pause = BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(10.0, 0.0))
+            .line_to(CGPoint::new(10.0, 10.0))
+            .line_to(CGPoint::new(0.0, 10.0))
+            .close()
+            .move_to(CGPoint::new(20.0, 0.0))
+            .line_to(CGPoint::new(30.0, 0.0))
+            .line_to(CGPoint::new(30.0, 10.0))
+            .line_to(CGPoint::new(20.0, 10.0))
+            .close();
+        let (from, to) = normalize_for_morph(&triangle(), &pause);
+        assert_eq!(shape(&from), shape(&to));
+        assert_eq!(start_points(&from).len(), 2);
+        // The padded subpath collapses into the center of its counterpart.
+        assert_eq!(start_points(&from)[1], CGPoint::new(25.0, 5.0));
+    }
+
+    #[test]
+    fn test_normalize_aligns_start_points() {
+        let a = square(CGPoint::ZERO, 40.0);
+        // Same square, but drawn from the opposite corner.
+        let b = BezierPath::new()
+            .move_to(CGPoint::new(40.0, 40.0))
+            .line_to(CGPoint::new(0.0, 40.0))
+            .line_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(40.0, 0.0))
+            .close();
+        let (from, to) = normalize_for_morph(&a, &b);
+        assert_close(&from, &to);
+    }
+
+    #[test]
+    fn test_normalize_aligns_direction() {
+        let a = square(CGPoint::ZERO, 40.0);
+        // Same square, drawn counter-clockwise.
+        let b = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(0.0, 40.0))
+            .line_to(CGPoint::new(40.0, 40.0))
+            .line_to(CGPoint::new(40.0, 0.0))
+            .close();
+        let (from, to) = normalize_for_morph(&a, &b);
+        assert_close(&from, &to);
+    }
+
+    #[test]
+    fn test_normalize_keeps_open_paths_open() {
+        let a = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(10.0, 0.0));
+        let b = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(0.0, 10.0))
+            .line_to(CGPoint::new(10.0, 10.0));
+        let (from, to) = normalize_for_morph(&a, &b);
+        assert_eq!(shape(&from), vec![0, 3, 3]);
+        assert_eq!(shape(&to), vec![0, 3, 3]);
+    }
+
+    #[test]
+    fn test_normalize_open_to_closed() {
+        let (from, to) = normalize_for_morph(&l_shape(), &triangle());
+        assert_eq!(shape(&from), shape(&to));
+        assert_eq!(shape(&to), vec![0, 3, 3, 3]);
+        assert!(from.interpolate(&to, 0.5).is_some());
+
+        // The triangle still ends back at its start.
+        let start = start_points(&to)[0];
+        let Some(PathElement::CurveTo(_, _, end)) = to.elements().last() else {
+            panic!("expected a curve");
+        };
+        assert!(distance_squared(start, *end) < 1e-9);
+
+        let (from, to) = normalize_for_morph(&triangle(), &l_shape());
+        assert_eq!(shape(&from), shape(&to));
+    }
+
+    fn l_shape() -> BezierPath {
+        BezierPath::new()
+            .move_to(CGPoint::new(0.0, 0.0))
+            .line_to(CGPoint::new(10.0, 0.0))
+            .line_to(CGPoint::new(10.0, 10.0))
+    }
+
+    fn assert_point(p: CGPoint, x: CGFloat, y: CGFloat) {
+        assert!(
+            (p.x - x).abs() < 1e-6 && (p.y - y).abs() < 1e-6,
+            "{p:?} != ({x}, {y})"
+        );
+    }
+
+    #[test]
+    fn test_reversed_swaps_ends() {
+        let reversed = l_shape().reversed();
+        assert_eq!(
+            reversed.elements()[0],
+            PathElement::MoveTo(CGPoint::new(10.0, 10.0))
+        );
+        let measure = PathMeasure::new(&reversed);
+        assert_point(measure.position_at(measure.length()), 0.0, 0.0);
+    }
+
+    #[test]
+    fn test_measure_line_length() {
+        let line = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(30.0, 40.0));
+        assert!((PathMeasure::new(&line).length() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure_circle_length() {
+        // Four-segment cubic approximation of a circle with radius 10.
+        let r = 10.0;
+        let k = 0.552_284_749_8 * r;
+        let circle = BezierPath::new()
+            .move_to(CGPoint::new(r, 0.0))
+            .curve_to(CGPoint::new(r, k), CGPoint::new(k, r), CGPoint::new(0.0, r))
+            .curve_to(
+                CGPoint::new(-k, r),
+                CGPoint::new(-r, k),
+                CGPoint::new(-r, 0.0),
+            )
+            .curve_to(
+                CGPoint::new(-r, -k),
+                CGPoint::new(-k, -r),
+                CGPoint::new(0.0, -r),
+            )
+            .curve_to(
+                CGPoint::new(k, -r),
+                CGPoint::new(r, -k),
+                CGPoint::new(r, 0.0),
+            )
+            .close();
+        let measure = PathMeasure::new(&circle);
+        let circumference = 2.0 * std::f64::consts::PI * r;
+        assert!((measure.length() - circumference).abs() < 0.01);
+        assert_point(measure.position_at(measure.length() * 0.25), 0.0, r);
+    }
+
+    #[test]
+    fn test_measure_position_and_angle() {
+        let measure = PathMeasure::new(&l_shape());
+        assert!((measure.length() - 20.0).abs() < 1e-9);
+        assert_point(measure.position_at(5.0), 5.0, 0.0);
+        assert_point(measure.position_at(15.0), 10.0, 5.0);
+        assert!(measure.angle_at(5.0).abs() < 1e-9);
+        assert!((measure.angle_at(15.0) - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        // Distances are clamped to the path.
+        assert_point(measure.position_at(-3.0), 0.0, 0.0);
+        assert_point(measure.position_at(99.0), 10.0, 10.0);
+    }
+
+    #[test]
+    fn test_measure_extract() {
+        let measure = PathMeasure::new(&l_shape());
+        let part = measure.extract(5.0, 15.0);
+        assert_eq!(shape(&part), vec![0, 3, 3]);
+        assert_eq!(
+            part.elements()[0],
+            PathElement::MoveTo(CGPoint::new(5.0, 0.0))
+        );
+        let part_measure = PathMeasure::new(&part);
+        assert!((part_measure.length() - 10.0).abs() < 1e-6);
+        assert_point(part_measure.position_at(10.0), 10.0, 5.0);
+    }
+
+    #[test]
+    fn test_measure_extract_at_segment_boundary() {
+        let measure = PathMeasure::new(&l_shape());
+        let part = measure.extract(10.0, 20.0);
+        assert_eq!(shape(&part), vec![0, 3]);
+        assert_eq!(
+            part.elements()[0],
+            PathElement::MoveTo(CGPoint::new(10.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_measure_extract_keeps_subpath_jumps() {
+        let two_lines = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(10.0, 0.0))
+            .move_to(CGPoint::new(0.0, 20.0))
+            .line_to(CGPoint::new(10.0, 20.0));
+        let measure = PathMeasure::new(&two_lines);
+        assert!((measure.length() - 20.0).abs() < 1e-9);
+        let part = measure.extract(5.0, 15.0);
+        assert_eq!(shape(&part), vec![0, 3, 0, 3]);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let from = BezierPath::new()
+            .move_to(CGPoint::ZERO)
+            .line_to(CGPoint::new(10.0, 0.0));
+        let to = BezierPath::new()
+            .move_to(CGPoint::new(0.0, 10.0))
+            .line_to(CGPoint::new(20.0, 10.0));
+        let mid = from.interpolate(&to, 0.5).unwrap();
+        assert_eq!(
+            mid.elements(),
+            &[
+                PathElement::MoveTo(CGPoint::new(0.0, 5.0)),
+                PathElement::LineTo(CGPoint::new(15.0, 5.0)),
+            ]
+        );
+        assert_eq!(from.interpolate(&to, 0.0).unwrap(), from);
+
+        let curved = BezierPath::new().move_to(CGPoint::ZERO).curve_to(
+            CGPoint::ZERO,
+            CGPoint::ZERO,
+            CGPoint::new(1.0, 1.0),
+        );
+        assert!(from.interpolate(&curved, 0.5).is_none());
+        assert!(from.interpolate(&BezierPath::new(), 0.5).is_none());
+    }
+
+    #[test]
+    fn test_measure_empty_path() {
+        let measure = PathMeasure::new(&BezierPath::new());
+        assert_eq!(measure.length(), 0.0);
+        assert_eq!(measure.position_at(1.0), CGPoint::ZERO);
+        assert!(measure.extract(0.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_cgpath_round_trip() {
+        let path = triangle();
+        assert_eq!(BezierPath::from_cgpath(&path.to_cgpath()), path);
+    }
+}
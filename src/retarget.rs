@@ -0,0 +1,467 @@
+//! Smoothly redirecting a running animation to a new target.
+//!
+//! Starting a fresh `from → to` animation whenever a target changes makes
+//! the layer jump: the new animation starts from the model value, not from
+//! what is on screen, and from a standstill. [`retarget`] picks up the
+//! displayed value and its current velocity instead, and continues from
+//! there toward the new target:
+//!
+//! ```ignore
+//! // Called whenever new progress arrives
+//! retarget(&bar, KeyPath::Custom("bounds.size.width"), progress * 300.0);
+//!
+//! // With a spring
+//! Retarget::new(KeyPath::PositionX, x)
+//!     .spring(Spring::new(200.0, 20.0))
+//!     .apply(&ball);
+//! ```
+//!
+//! Retargeting works with scalar key paths. The layer's model value is set
+//! to the target, so it stays there once the animation is done.
+
+use std::time::Duration;
+
+use objc2::msg_send;
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2_foundation::{NSNumber, NSObjectNSKeyValueCoding, NSString};
+use objc2_quartz_core::{
+    CABasicAnimation, CACurrentMediaTime, CALayer, CAMediaTiming, CAMediaTimingFunction,
+    CASpringAnimation,
+};
+
+use crate::animation_builder::{bezier_slope, Easing, KeyPath};
+use crate::settings::{AnimationSettings, MotionPolicy};
+use crate::transaction::transaction;
+
+/// Redirects the animation of `key_path` on `layer` to `to`.
+///
+/// Uses the defaults of [`Retarget`]; see the [module docs](self).
+pub fn retarget(layer: &CALayer, key_path: KeyPath, to: f64) {
+    Retarget::new(key_path, to).apply(layer);
+}
+
+/// Builder for redirecting an animation to a new target.
+///
+/// # Examples
+///
+/// ```ignore
+/// Retarget::new(KeyPath::Opacity, 0.4)
+///     .duration(400.millis())
+///     .apply(&layer);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retarget {
+    key_path: KeyPath,
+    to: f64,
+    duration: Duration,
+    spring: Option<Spring>,
+}
+
+impl Retarget {
+    /// Creates a retarget of `key_path` toward `to`.
+    #[must_use]
+    pub fn new(key_path: KeyPath, to: f64) -> Self {
+        Self {
+            key_path,
+            to,
+            duration: Duration::from_millis(250),
+            spring: None,
+        }
+    }
+
+    /// Sets how long it takes to reach the target (default: 250ms).
+    ///
+    /// Ignored when a [`spring`](Self::spring) is set, which takes as long
+    /// as it needs to settle.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Moves to the target with a spring instead of an ease-out curve.
+    #[must_use]
+    pub fn spring(mut self, spring: Spring) -> Self {
+        self.spring = Some(spring);
+        self
+    }
+
+    /// Replaces the running animation of the key path on `layer`.
+    ///
+    /// Follows the [`MotionPolicy`]: under `None`, and for large moves,
+    /// scales and rotations under `Reduced`, the layer jumps straight to
+    /// the target.
+    pub fn apply(self, layer: &CALayer) {
+        let key = NSString::from_str(self.key_path.as_str());
+        let motion = Motion::of(layer, &self.key_path);
+
+        let to = NSNumber::new_f64(self.to);
+        transaction(
+            |t| t.disable_actions(true),
+            // SAFETY: An NSNumber is a valid value for scalar key paths.
+            || unsafe { layer.setValue_forKeyPath(Some(&to), &key) },
+        );
+        if !self.moves(AnimationSettings::motion_policy(), motion.value) {
+            layer.removeAnimationForKey(&key);
+            return;
+        }
+
+        let from = NSNumber::new_f64(motion.value);
+        let anim: Retained<CABasicAnimation> = match self.spring {
+            Some(spring) => {
+                let anim = CASpringAnimation::animationWithKeyPath(Some(&key));
+                anim.setMass(spring.mass);
+                anim.setStiffness(spring.stiffness);
+                anim.setDamping(spring.damping);
+                anim.setInitialVelocity(motion.spring_velocity_to(self.to));
+                anim.setDuration(anim.settlingDuration());
+                Retained::into_super(anim)
+            }
+            None => {
+                let duration = self.duration.as_secs_f64();
+                let (x1, y1, x2, y2) = motion.control_points_to(self.to, duration);
+                let anim = CABasicAnimation::animationWithKeyPath(Some(&key));
+                anim.setTimingFunction(Some(&CAMediaTimingFunction::functionWithControlPoints(
+                    x1 as f32, y1 as f32, x2 as f32, y2 as f32,
+                )));
+                anim.setDuration(duration);
+                anim
+            }
+        };
+        // SAFETY: NSNumber is a valid value for scalar key paths.
+        unsafe {
+            anim.setFromValue(Some(&from));
+            anim.setToValue(Some(&to));
+        }
+
+        layer.addAnimation_forKey(&anim, Some(&key));
+    }
+
+    /// Whether `policy` lets the layer move from `from` to the target
+    /// rather than jump there.
+    fn moves(&self, policy: MotionPolicy, from: f64) -> bool {
+        match policy {
+            MotionPolicy::Full => true,
+            MotionPolicy::Reduced => self
+                .key_path
+                .motion_limit()
+                .is_none_or(|limit| (self.to - from).abs() <= limit),
+            MotionPolicy::None => false,
+        }
+    }
+}
+
+// ============================================================================
+// Motion
+// ============================================================================
+
+/// The value of a property as displayed, and how fast it is changing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    /// Current value.
+    pub value: f64,
+    /// Change per second.
+    pub velocity: f64,
+}
+
+impl Motion {
+    /// A property that is not moving.
+    pub fn at_rest(value: f64) -> Self {
+        Self {
+            value,
+            velocity: 0.0,
+        }
+    }
+
+    /// Reads the displayed value of `key_path` on `layer`.
+    ///
+    /// The velocity comes from the animation running under the key path's
+    /// name, as added by [`Retarget::apply`]. It is zero if there is none,
+    /// or if it is not a plain from/to or spring animation.
+    pub fn of(layer: &CALayer, key_path: &KeyPath) -> Self {
+        let key = NSString::from_str(key_path.as_str());
+        // SAFETY: The presentation layer is only read.
+        let shown = unsafe { layer.presentationLayer() };
+        let value = shown
+            .as_deref()
+            .and_then(|shown| number(shown.valueForKeyPath(&key)))
+            .or_else(|| number(layer.valueForKeyPath(&key)))
+            .unwrap_or(0.0);
+
+        Self {
+            value,
+            velocity: velocity_of(layer, &key).unwrap_or(0.0),
+        }
+    }
+
+    /// Control points of an ease-out curve toward `to` that starts at this
+    /// velocity, for an animation lasting `duration` seconds.
+    ///
+    /// The initial slope of the curve is the velocity in units of the whole
+    /// distance per duration. Very steep starts are clamped to keep the
+    /// curve from overshooting wildly.
+    pub fn control_points_to(&self, to: f64, duration: f64) -> (f64, f64, f64, f64) {
+        let (_, _, x2, y2) = Easing::Out.control_points();
+        let distance = to - self.value;
+        if distance.abs() < f64::EPSILON || duration <= 0.0 {
+            return (1.0 / 3.0, 1.0 / 3.0, x2, y2);
+        }
+        let slope = self.velocity * duration / distance;
+        let x1 = if slope.abs() > 3.0 {
+            1.0 / slope.abs()
+        } else {
+            1.0 / 3.0
+        };
+        (x1, x1 * slope, x2, y2)
+    }
+
+    /// Initial velocity of a spring toward `to`, as a fraction of the
+    /// distance per second.
+    pub fn spring_velocity_to(&self, to: f64) -> f64 {
+        let distance = to - self.value;
+        if distance.abs() < f64::EPSILON {
+            0.0
+        } else {
+            self.velocity / distance
+        }
+    }
+}
+
+/// Velocity of the animation under `key` on `layer`, if it can be computed.
+fn velocity_of(layer: &CALayer, key: &NSString) -> Option<f64> {
+    // SAFETY: The animation is only read.
+    let anim = unsafe { layer.animationForKey(key) }?;
+    let anim = anim.downcast::<CABasicAnimation>().ok()?;
+    let from = number(anim.fromValue())?;
+    let to = number(anim.toValue())?;
+
+    let now = layer.convertTime_fromLayer(CACurrentMediaTime(), None);
+    let elapsed = (now - anim.beginTime()) * f64::from(anim.speed());
+
+    if let Some(spring) = anim.downcast_ref::<CASpringAnimation>() {
+        let params = Spring {
+            mass: spring.mass(),
+            stiffness: spring.stiffness(),
+            damping: spring.damping(),
+        };
+        return Some((to - from) * params.velocity(spring.initialVelocity(), elapsed));
+    }
+
+    let duration = anim.duration();
+    if duration <= 0.0 || !(0.0..duration).contains(&elapsed) {
+        return Some(0.0);
+    }
+    let points = anim
+        .timingFunction()
+        .map_or((0.0, 0.0, 1.0, 1.0), |function| control_points(&function));
+    Some((to - from) / duration * bezier_slope(points, elapsed / duration))
+}
+
+/// Reads the control points of a timing function.
+fn control_points(function: &CAMediaTimingFunction) -> (f64, f64, f64, f64) {
+    let mut first = [0.0_f32; 2];
+    let mut second = [0.0_f32; 2];
+    // SAFETY: Each index writes two floats into a buffer of two.
+    unsafe {
+        let _: () =
+            msg_send![function, getControlPointAtIndex: 1_usize, values: first.as_mut_ptr()];
+        let _: () =
+            msg_send![function, getControlPointAtIndex: 2_usize, values: second.as_mut_ptr()];
+    }
+    (
+        f64::from(first[0]),
+        f64::from(first[1]),
+        f64::from(second[0]),
+        f64::from(second[1]),
+    )
+}
+
+fn number(value: Option<Retained<AnyObject>>) -> Option<f64> {
+    value?
+        .downcast::<NSNumber>()
+        .ok()
+        .map(|number| number.as_f64())
+}
+
+// ============================================================================
+// Spring
+// ============================================================================
+
+/// Physical parameters of a spring, as used by `CASpringAnimation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    /// Mass of the object attached to the spring (default: 1.0).
+    pub mass: f64,
+    /// Stiffness of the spring (default: 100.0).
+    pub stiffness: f64,
+    /// Friction slowing the motion down (default: 10.0).
+    pub damping: f64,
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            stiffness: 100.0,
+            damping: 10.0,
+        }
+    }
+}
+
+impl Spring {
+    /// Creates a spring with unit mass.
+    pub fn new(stiffness: f64, damping: f64) -> Self {
+        Self {
+            stiffness,
+            damping,
+            ..Self::default()
+        }
+    }
+
+    /// Progress toward the target at `time` seconds, starting at 0.0 and
+    /// settling at 1.0.
+    ///
+    /// `initial_velocity` is in fractions of the distance per second.
+    pub fn progress(&self, initial_velocity: f64, time: f64) -> f64 {
+        1.0 + self.solve(initial_velocity, time).0
+    }
+
+    /// Rate of change of [`progress`](Self::progress) at `time` seconds.
+    pub fn velocity(&self, initial_velocity: f64, time: f64) -> f64 {
+        self.solve(initial_velocity, time).1
+    }
+
+    /// Displacement from the target and its derivative, for a motion that
+    /// starts one distance away.
+    fn solve(&self, v0: f64, t: f64) -> (f64, f64) {
+        let omega = (self.stiffness / self.mass).sqrt();
+        let zeta = self.damping / (2.0 * (self.stiffness * self.mass).sqrt());
+
+        if (zeta - 1.0).abs() < 1e-9 {
+            let b = v0 - omega;
+            let decay = (-omega * t).exp();
+            let x = decay * (-1.0 + b * t);
+            (x, decay * b - omega * x)
+        } else if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            let b = (v0 - zeta * omega) / omega_d;
+            let decay = (-zeta * omega * t).exp();
+            let (sin, cos) = (omega_d * t).sin_cos();
+            let x = decay * (-cos + b * sin);
+            (x, -zeta * omega * x + decay * omega_d * (sin + b * cos))
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            let c2 = (v0 + r1) / (r2 - r1);
+            let c1 = -1.0 - c2;
+            let (e1, e2) = ((r1 * t).exp(), (r2 * t).exp());
+            (c1 * e1 + c2 * e2, c1 * r1 * e1 + c2 * r2 * e2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_follows_motion_policy() {
+        let slide = Retarget::new(KeyPath::PositionX, 100.0);
+        assert!(slide.moves(MotionPolicy::Full, 0.0));
+        assert!(!slide.moves(MotionPolicy::Reduced, 0.0));
+        assert!(slide.moves(MotionPolicy::Reduced, 90.0));
+        assert!(!slide.moves(MotionPolicy::None, 90.0));
+
+        // Fading does not move the layer, so reduced motion keeps it.
+        let fade = Retarget::new(KeyPath::Opacity, 0.0);
+        assert!(fade.moves(MotionPolicy::Reduced, 1.0));
+        assert!(!fade.moves(MotionPolicy::None, 1.0));
+    }
+
+    #[test]
+    fn test_spring_endpoints() {
+        let springs = [
+            Spring::default(),
+            Spring::new(100.0, 20.0), // critically damped
+            Spring::new(100.0, 40.0), // overdamped
+        ];
+        for spring in springs {
+            for v0 in [0.0, 2.0, -1.5] {
+                assert_close(spring.progress(v0, 0.0), 0.0);
+                assert_close(spring.velocity(v0, 0.0), v0);
+                assert!((spring.progress(v0, 10.0) - 1.0).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spring_velocity_is_derivative() {
+        let h = 1e-6;
+        for spring in [
+            Spring::default(),
+            Spring::new(100.0, 20.0),
+            Spring::new(50.0, 30.0),
+        ] {
+            let t = 0.3;
+            let numeric = (spring.progress(1.0, t + h) - spring.progress(1.0, t - h)) / (2.0 * h);
+            assert!((spring.velocity(1.0, t) - numeric).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_handoff_preserves_velocity() {
+        // 100 units to go in 0.5s while moving at 300 units/s
+        let motion = Motion {
+            value: 0.0,
+            velocity: 300.0,
+        };
+        let points = motion.control_points_to(100.0, 0.5);
+        let velocity = 100.0 / 0.5 * bezier_slope(points, 0.0);
+        assert_close(velocity, 300.0);
+
+        // Moving away from the new target
+        let motion = Motion {
+            value: 50.0,
+            velocity: 100.0,
+        };
+        let points = motion.control_points_to(0.0, 0.5);
+        let velocity = -50.0 / 0.5 * bezier_slope(points, 0.0);
+        assert_close(velocity, 100.0);
+    }
+
+    #[test]
+    fn test_handoff_at_rest() {
+        let points = Motion::at_rest(10.0).control_points_to(20.0, 1.0);
+        assert_close(bezier_slope(points, 0.0), 0.0);
+        assert_eq!(points.2, 0.58);
+        assert_eq!(points.3, 1.0);
+
+        // Nowhere to go
+        let motion = Motion {
+            value: 5.0,
+            velocity: 10.0,
+        };
+        assert_eq!(motion.spring_velocity_to(5.0), 0.0);
+        assert_eq!(motion.spring_velocity_to(15.0), 1.0);
+    }
+
+    #[test]
+    fn test_handoff_clamps_steep_starts() {
+        let motion = Motion {
+            value: 0.0,
+            velocity: 10_000.0,
+        };
+        let (x1, y1, _, _) = motion.control_points_to(10.0, 1.0);
+        assert_close(y1, 1.0);
+        assert!(x1 > 0.0 && x1 < 1.0 / 3.0);
+    }
+}
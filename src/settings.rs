@@ -0,0 +1,330 @@
+//! Process-wide animation settings.
+//!
+//! Timing bugs are easier to see in slow motion. [`AnimationSettings`] holds
+//! a speed multiplier that every animation builder applies to the
+//! animations it builds, like the "slow animations" toggle of the iOS
+//! simulator:
+//!
+//! ```ignore
+//! // Everything built from here on plays at a quarter of its speed
+//! AnimationSettings::set_speed(0.25);
+//! ```
+//!
+//! Or without touching the code, from the environment:
+//!
+//! ```bash
+//! CORE_ANIMATION_SPEED=0.25 cargo run --example breathing_circle
+//! ```
+//!
+//! The multiplier stretches delays, stagger offsets and timeline layouts
+//! along with the animations, so choreography keeps its shape. It is read
+//! when an animation is built; animations that are already running keep
+//! their speed.
+//!
+//! Windows also run their container layer at the multiplier, so implicit
+//! animations, transitions and animations built by hand slow down too. Do
+//! the same for the root of other layer trees with
+//! [`AnimationSettings::apply_speed`]. Animations from the builders that
+//! are added inside such a tree, with `.animate()` on a layer builder or
+//! [`CALayerExt::add_animation`](crate::CALayerExt::add_animation), leave
+//! their own multiplier out, so nothing is slowed down twice. That is
+//! decided when the animation is added, so attach layers to the tree first.
+//!
+//! # Reduced Motion
+//!
+//! People with vestibular disorders turn on "Reduce motion" in the macOS
+//! accessibility settings. The builders follow a [`MotionPolicy`], which
+//! starts out matching that preference:
+//!
+//! | Policy | Effect |
+//! |--------|--------|
+//! | `Full` | Animations play as configured |
+//! | `Reduced` | Large moves, scales and rotations become crossfades or jumps, nothing repeats forever, particle birth rates are capped |
+//! | `None` | Animations jump to their end, and emitters stay empty |
+//!
+//! Tests and previews can force a policy:
+//!
+//! ```ignore
+//! AnimationSettings::set_motion_policy(MotionPolicy::Reduced);
+//! ```
+
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use objc2::Message;
+use objc2_app_kit::NSWorkspace;
+use objc2_foundation::{NSNumber, NSObject, NSObjectNSKeyValueCoding, NSString};
+use objc2_quartz_core::{CAAnimation, CALayer, CAMediaTiming};
+
+use crate::controller::set_clock_speed;
+
+/// Key under which slowed layers and animations record the multiplier.
+const MULTIPLIER_KEY: &str = "coreAnimationSpeedMultiplier";
+
+/// Particles per second a cell may emit under [`MotionPolicy::Reduced`].
+const REDUCED_BIRTH_RATE: f32 = 10.0;
+
+/// How much motion the builders may create.
+///
+/// Read with [`AnimationSettings::motion_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MotionPolicy {
+    /// Animations play as configured (default).
+    #[default]
+    Full,
+    /// Large motion is replaced with crossfades or jumps, animations that
+    /// repeat forever play once, and particle birth rates are capped.
+    Reduced,
+    /// Animations jump straight to their end and emitters stay empty.
+    None,
+}
+
+impl MotionPolicy {
+    /// Returns `Reduced` if "Reduce motion" is turned on in the system
+    /// accessibility settings, `Full` otherwise.
+    pub fn system() -> Self {
+        if NSWorkspace::sharedWorkspace().accessibilityDisplayShouldReduceMotion() {
+            MotionPolicy::Reduced
+        } else {
+            MotionPolicy::Full
+        }
+    }
+
+    /// Limits a particle cell's birth rate, in particles per second.
+    pub fn birth_rate(self, rate: f32) -> f32 {
+        match self {
+            MotionPolicy::Full => rate,
+            MotionPolicy::Reduced => rate.min(REDUCED_BIRTH_RATE),
+            MotionPolicy::None => 0.0,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            MotionPolicy::Full => 0,
+            MotionPolicy::Reduced => 1,
+            MotionPolicy::None => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => MotionPolicy::Reduced,
+            2 => MotionPolicy::None,
+            _ => MotionPolicy::Full,
+        }
+    }
+}
+
+/// Global settings for the animations built by this crate.
+///
+/// # Examples
+///
+/// ```ignore
+/// AnimationSettings::set_slow_motion(true);
+/// assert_eq!(AnimationSettings::speed(), AnimationSettings::SLOW_MOTION);
+///
+/// AnimationSettings::set_slow_motion(false);
+/// assert_eq!(AnimationSettings::speed(), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationSettings;
+
+impl AnimationSettings {
+    /// Environment variable with the initial speed, e.g. `0.25`.
+    pub const SPEED_VAR: &'static str = "CORE_ANIMATION_SPEED";
+
+    /// The speed of [`set_slow_motion`](Self::set_slow_motion).
+    pub const SLOW_MOTION: f32 = 0.1;
+
+    /// Returns the speed multiplier, 1.0 unless changed.
+    ///
+    /// Starts out as the value of [`SPEED_VAR`](Self::SPEED_VAR) if that is
+    /// set to a positive number.
+    pub fn speed() -> f32 {
+        f32::from_bits(speed_bits().load(Ordering::Relaxed))
+    }
+
+    /// Sets the speed multiplier for animations built, windows built and
+    /// trees applied from now on.
+    ///
+    /// * `speed` - 1.0 = normal, 0.25 = four times slower, 2.0 = twice as fast
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not a positive, finite number.
+    pub fn set_speed(speed: f32) {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "animation speed must be positive, got {speed}"
+        );
+        speed_bits().store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Turns slow motion at [`SLOW_MOTION`](Self::SLOW_MOTION) on, or back
+    /// to normal speed.
+    pub fn set_slow_motion(enabled: bool) {
+        Self::set_speed(if enabled { Self::SLOW_MOTION } else { 1.0 });
+    }
+
+    /// Runs `layer` and its sublayers at the speed multiplier.
+    ///
+    /// Windows do this for their container layer. Use it for the root of
+    /// other layer trees, such as the layer of your own view. The layer's
+    /// clock carries on from where it is, so delays that are already
+    /// counting keep their place. Sets the layer's speed, so call it again
+    /// after changing the multiplier.
+    ///
+    /// Animations from the builders that are added to the tree from now on
+    /// play at their own speed, since the tree already slows them down.
+    pub fn apply_speed(layer: &CALayer) {
+        let speed = Self::speed();
+        set_clock_speed(layer, speed);
+        record_multiplier(layer, speed);
+    }
+
+    /// Returns the motion policy the builders follow.
+    ///
+    /// Starts out as [`MotionPolicy::system`], read once on first use.
+    pub fn motion_policy() -> MotionPolicy {
+        MotionPolicy::from_u8(policy_bits().load(Ordering::Relaxed))
+    }
+
+    /// Sets the motion policy for animations and emitters built from now
+    /// on, regardless of the system setting.
+    pub fn set_motion_policy(policy: MotionPolicy) {
+        policy_bits().store(policy.to_u8(), Ordering::Relaxed);
+    }
+}
+
+/// Slows `anim` down by the multiplier, on top of its own speed, and
+/// stretches its relative delay along with it.
+///
+/// The multiplier is recorded on the animation, so that adding it to a
+/// slowed tree can take it back out.
+pub(crate) fn slow_down(anim: &CAAnimation) {
+    let multiplier = AnimationSettings::speed();
+    anim.setSpeed(anim.speed() * multiplier);
+    anim.setBeginTime(anim.beginTime() / f64::from(multiplier));
+    record_multiplier(anim, multiplier);
+}
+
+/// Takes the multiplier recorded by [`slow_down`] back out of `anim`.
+pub(crate) fn restore_speed(anim: &CAAnimation) {
+    let multiplier = recorded_multiplier(anim).unwrap_or(1.0);
+    anim.setSpeed(anim.speed() / multiplier);
+    anim.setBeginTime(anim.beginTime() * f64::from(multiplier));
+    record_multiplier(anim, 1.0);
+}
+
+/// Whether `layer` or one of its superlayers runs at the multiplier, see
+/// [`AnimationSettings::apply_speed`].
+pub(crate) fn in_slowed_tree(layer: &CALayer) -> bool {
+    let mut layer = Some(layer.retain());
+    while let Some(current) = layer {
+        if recorded_multiplier(&current).is_some() {
+            return true;
+        }
+        layer = current.superlayer();
+    }
+    false
+}
+
+/// The multiplier recorded on a layer or animation, if any.
+///
+/// Layers and animations keep values for arbitrary keys, and animations
+/// keep them when Core Animation copies them.
+pub(crate) fn recorded_multiplier(object: &NSObject) -> Option<f32> {
+    object
+        .valueForKey(&NSString::from_str(MULTIPLIER_KEY))?
+        .downcast::<NSNumber>()
+        .ok()
+        .map(|number| number.as_f32())
+}
+
+/// Records that `object` runs at, or was slowed down by, `multiplier`.
+pub(crate) fn record_multiplier(object: &NSObject, multiplier: f32) {
+    let value = NSNumber::new_f32(multiplier);
+    // SAFETY: Layers and animations accept values for arbitrary keys.
+    unsafe { object.setValue_forKey(Some(&value), &NSString::from_str(MULTIPLIER_KEY)) };
+}
+
+/// The multiplier's storage, initialized from the environment.
+fn speed_bits() -> &'static AtomicU32 {
+    static SPEED: OnceLock<AtomicU32> = OnceLock::new();
+    SPEED.get_or_init(|| {
+        let speed = std::env::var(AnimationSettings::SPEED_VAR)
+            .ok()
+            .and_then(|value| parse_speed(&value))
+            .unwrap_or(1.0);
+        AtomicU32::new(speed.to_bits())
+    })
+}
+
+/// The motion policy's storage, initialized from the system setting.
+fn policy_bits() -> &'static AtomicU8 {
+    static POLICY: OnceLock<AtomicU8> = OnceLock::new();
+    POLICY.get_or_init(|| AtomicU8::new(MotionPolicy::system().to_u8()))
+}
+
+/// Parses a speed, accepting only positive, finite numbers.
+fn parse_speed(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("0.25"), Some(0.25));
+        assert_eq!(parse_speed(" 2 "), Some(2.0));
+        assert_eq!(parse_speed("0"), None);
+        assert_eq!(parse_speed("-1"), None);
+        assert_eq!(parse_speed("inf"), None);
+        assert_eq!(parse_speed("slow"), None);
+    }
+
+    #[test]
+    fn test_set_speed() {
+        AnimationSettings::set_speed(0.5);
+        assert_eq!(AnimationSettings::speed(), 0.5);
+        AnimationSettings::set_slow_motion(true);
+        assert_eq!(AnimationSettings::speed(), AnimationSettings::SLOW_MOTION);
+        AnimationSettings::set_slow_motion(false);
+        assert_eq!(AnimationSettings::speed(), 1.0);
+    }
+
+    #[test]
+    fn test_birth_rate() {
+        assert_eq!(MotionPolicy::Full.birth_rate(200.0), 200.0);
+        assert_eq!(MotionPolicy::Reduced.birth_rate(200.0), REDUCED_BIRTH_RATE);
+        assert_eq!(MotionPolicy::Reduced.birth_rate(2.0), 2.0);
+        assert_eq!(MotionPolicy::None.birth_rate(200.0), 0.0);
+    }
+
+    #[test]
+    fn test_set_motion_policy() {
+        let system = AnimationSettings::motion_policy();
+        for policy in [
+            MotionPolicy::Full,
+            MotionPolicy::Reduced,
+            MotionPolicy::None,
+        ] {
+            AnimationSettings::set_motion_policy(policy);
+            assert_eq!(AnimationSettings::motion_policy(), policy);
+        }
+        AnimationSettings::set_motion_policy(system);
+    }
+
+    #[test]
+    #[should_panic(expected = "animation speed must be positive")]
+    fn test_rejects_zero_speed() {
+        AnimationSettings::set_speed(0.0);
+    }
+}
@@ -1,17 +1,104 @@
 //! Builder for `CAShapeLayer` (vector shape rendering).
 
-use crate::animation_builder::{CABasicAnimationBuilder, KeyPath};
+use crate::animation_builder::presets::PresetBuilder;
+use crate::animation_builder::{
+    CABasicAnimationBuilder, CAKeyframeAnimationBuilder, KeyPath, MotionPathBuilder,
+};
 use crate::color::Color;
+use crate::layer_ext::CALayerExt;
+#[cfg(feature = "lottie")]
+use crate::lottie::{ExportAnimation, ShapeLayerDescription};
+#[cfg(feature = "lottie")]
+use crate::path::BezierPath;
 use objc2::rc::Retained;
 use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::{CGColor, CGPath};
 use objc2_foundation::NSString;
-use objc2_quartz_core::{CABasicAnimation, CAShapeLayer, CATransform3D};
+use objc2_quartz_core::{
+    kCALineCapButt, kCALineCapRound, kCALineCapSquare, kCALineJoinBevel, kCALineJoinMiter,
+    kCALineJoinRound, CAAnimation, CALayer, CAShapeLayer, CATransform3D,
+};
+
+/// Shape of the ends of a stroke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineCap {
+    /// Ends exactly at the end point (default).
+    #[default]
+    Butt,
+    /// Rounded end.
+    Round,
+    /// Squared end, extending past the end point.
+    Square,
+}
+
+impl LineCap {
+    /// Returns the Core Animation line cap string for this cap.
+    fn to_ca_line_cap(self) -> &'static NSString {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                LineCap::Butt => kCALineCapButt,
+                LineCap::Round => kCALineCapRound,
+                LineCap::Square => kCALineCapSquare,
+            }
+        }
+    }
+}
+
+/// Shape of the corners of a stroke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineJoin {
+    /// Sharp corner (default).
+    #[default]
+    Miter,
+    /// Rounded corner.
+    Round,
+    /// Cut-off corner.
+    Bevel,
+}
+
+impl LineJoin {
+    /// Returns the Core Animation line join string for this join.
+    fn to_ca_line_join(self) -> &'static NSString {
+        // SAFETY: These extern statics are always valid on macOS.
+        unsafe {
+            match self {
+                LineJoin::Miter => kCALineJoinMiter,
+                LineJoin::Round => kCALineJoinRound,
+                LineJoin::Bevel => kCALineJoinBevel,
+            }
+        }
+    }
+}
 
 /// A pending animation to be applied when the layer is built.
 struct PendingAnimation {
     name: String,
-    animation: Retained<CABasicAnimation>,
+    animation: Pending,
+}
+
+/// An animation as it was added.
+///
+/// Kept as a builder until the layer is built, so the layer can also be
+/// exported to Lottie.
+enum Pending {
+    Basic(CABasicAnimationBuilder),
+    Keyframe(CAKeyframeAnimationBuilder),
+    Motion(MotionPathBuilder),
+    Preset(PresetBuilder),
+}
+
+impl Pending {
+    fn build(self) -> Retained<CAAnimation> {
+        match self {
+            Pending::Basic(builder) => builder.build_animation(),
+            Pending::Keyframe(builder) => {
+                Retained::into_super(Retained::into_super(builder.build()))
+            }
+            Pending::Motion(builder) => Retained::into_super(Retained::into_super(builder.build())),
+            Pending::Preset(builder) => builder.build(),
+        }
+    }
 }
 
 /// Builder for `CAShapeLayer`.
@@ -60,12 +147,17 @@ struct PendingAnimation {
 /// ```
 #[derive(Default)]
 pub struct CAShapeLayerBuilder {
+    name: Option<String>,
     bounds: Option<CGRect>,
     position: Option<CGPoint>,
     path: Option<CFRetained<CGPath>>,
     fill_color: Option<CFRetained<CGColor>>,
     stroke_color: Option<CFRetained<CGColor>>,
     line_width: Option<CGFloat>,
+    line_cap: Option<LineCap>,
+    line_join: Option<LineJoin>,
+    stroke_start: Option<CGFloat>,
+    stroke_end: Option<CGFloat>,
     transform: Option<CATransform3D>,
     hidden: Option<bool>,
     opacity: Option<f32>,
@@ -87,6 +179,13 @@ impl CAShapeLayerBuilder {
         Self::default()
     }
 
+    /// Sets the layer name, e.g. to find it in a layer tree or in a Lottie
+    /// export.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
     /// Sets the bounds rectangle.
     pub fn bounds(mut self, bounds: CGRect) -> Self {
         self.bounds = Some(bounds);
@@ -218,6 +317,30 @@ impl CAShapeLayerBuilder {
         self
     }
 
+    /// Sets the shape of the ends of the stroke.
+    pub fn line_cap(mut self, cap: LineCap) -> Self {
+        self.line_cap = Some(cap);
+        self
+    }
+
+    /// Sets the shape of the corners of the stroke.
+    pub fn line_join(mut self, join: LineJoin) -> Self {
+        self.line_join = Some(join);
+        self
+    }
+
+    /// Sets where the stroke starts, from 0.0 to 1.0 along the path.
+    pub fn stroke_start(mut self, start: CGFloat) -> Self {
+        self.stroke_start = Some(start);
+        self
+    }
+
+    /// Sets where the stroke ends, from 0.0 to 1.0 along the path.
+    pub fn stroke_end(mut self, end: CGFloat) -> Self {
+        self.stroke_end = Some(end);
+        self
+    }
+
     /// Sets the 3D transform.
     pub fn transform(mut self, transform: CATransform3D) -> Self {
         self.transform = Some(transform);
@@ -437,10 +560,112 @@ impl CAShapeLayerBuilder {
         F: FnOnce(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
     {
         let builder = CABasicAnimationBuilder::new(key_path);
-        let animation = configure(builder).build();
         self.animations.push(PendingAnimation {
             name: name.into(),
-            animation,
+            animation: Pending::Basic(configure(builder)),
+        });
+        self
+    }
+
+    /// Adds an animation through several values to be applied when the
+    /// layer is built.
+    ///
+    /// The closure receives a [`CAKeyframeAnimationBuilder`], as with
+    /// [`animate`](Self::animate).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Draw the stroke quickly, then slow down towards the end
+    /// CAShapeLayerBuilder::new()
+    ///     .path(check_path)
+    ///     .stroke_color(Color::GREEN)
+    ///     .animate_keyframes("draw", KeyPath::Custom("strokeEnd"), |k| {
+    ///         k.values([0.0, 0.8, 1.0])
+    ///             .key_times([0.0, 0.25, 1.0])
+    ///             .duration(1.seconds())
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn animate_keyframes<F>(
+        mut self,
+        name: impl Into<String>,
+        key_path: KeyPath,
+        configure: F,
+    ) -> Self
+    where
+        F: FnOnce(CAKeyframeAnimationBuilder) -> CAKeyframeAnimationBuilder,
+    {
+        let builder = CAKeyframeAnimationBuilder::new(key_path);
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: Pending::Keyframe(configure(builder)),
+        });
+        self
+    }
+
+    /// Adds a motion path animation to be applied when the layer is built.
+    ///
+    /// The closure receives a [`MotionPathBuilder`] that moves the layer's
+    /// `position` along a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `configure` - A closure that configures the motion path builder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Orbit along a circular path, facing the direction of travel
+    /// CAShapeLayerBuilder::new()
+    ///     .circle(12.0)
+    ///     .fill_color(Color::CYAN)
+    ///     .animate_motion("orbit", |m| {
+    ///         m.path(&orbit_path)
+    ///             .rotation(RotationMode::Auto)
+    ///             .duration(2.seconds())
+    ///             .easing(Easing::Linear)
+    ///             .repeat(Repeat::Forever)
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn animate_motion<F>(mut self, name: impl Into<String>, configure: F) -> Self
+    where
+        F: FnOnce(MotionPathBuilder) -> MotionPathBuilder,
+    {
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: Pending::Motion(configure(MotionPathBuilder::new())),
+        });
+        self
+    }
+
+    /// Adds a ready-made effect to be applied when the layer is built.
+    ///
+    /// Accepts a [`Preset`](crate::animation_builder::presets::Preset) directly, or a [`PresetBuilder`] for tuning.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `preset` - The effect to apply
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// CAShapeLayerBuilder::new()
+    ///     .animate_preset("intro", Preset::BounceIn)
+    ///     .animate_preset("idle", Preset::Heartbeat.repeat(Repeat::Forever).delay(1.seconds()))
+    ///     .build();
+    /// ```
+    pub fn animate_preset(
+        mut self,
+        name: impl Into<String>,
+        preset: impl Into<PresetBuilder>,
+    ) -> Self {
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: Pending::Preset(preset.into()),
         });
         self
     }
@@ -449,8 +674,17 @@ impl CAShapeLayerBuilder {
     ///
     /// All pending animations added via `.animate()` are applied to the layer.
     pub fn build(self) -> Retained<CAShapeLayer> {
+        self.build_in(None)
+    }
+
+    /// Builds the layer and adds it to `superlayer` before applying the
+    /// pending animations, so their delays count on the final tree's clock.
+    pub(crate) fn build_in(self, superlayer: Option<&CALayer>) -> Retained<CAShapeLayer> {
         let layer = CAShapeLayer::new();
 
+        if let Some(ref name) = self.name {
+            layer.setName(Some(&NSString::from_str(name)));
+        }
         if let Some(bounds) = self.bounds {
             layer.setBounds(bounds);
         }
@@ -469,6 +703,18 @@ impl CAShapeLayerBuilder {
         if let Some(width) = self.line_width {
             layer.setLineWidth(width);
         }
+        if let Some(cap) = self.line_cap {
+            layer.setLineCap(cap.to_ca_line_cap());
+        }
+        if let Some(join) = self.line_join {
+            layer.setLineJoin(join.to_ca_line_join());
+        }
+        if let Some(start) = self.stroke_start {
+            layer.setStrokeStart(start);
+        }
+        if let Some(end) = self.stroke_end {
+            layer.setStrokeEnd(end);
+        }
 
         // Transform handling: explicit transform takes precedence over shortcuts
         if let Some(transform) = self.transform {
@@ -515,12 +761,111 @@ impl CAShapeLayerBuilder {
             layer.setShadowOpacity(opacity);
         }
 
+        if let Some(superlayer) = superlayer {
+            superlayer.addSublayer(&layer);
+        }
+
         // Apply all pending animations
         for pending in self.animations {
-            let key = NSString::from_str(&pending.name);
-            layer.addAnimation_forKey(&pending.animation, Some(&key));
+            layer.add_animation(&pending.animation.build(), &pending.name);
         }
 
         layer
     }
 }
+
+#[cfg(feature = "lottie")]
+impl CAShapeLayerBuilder {
+    /// Reads the settings and pending animations back for
+    /// [`CompositionExport`](crate::lottie::CompositionExport).
+    pub(crate) fn describe(self) -> ShapeLayerDescription {
+        let mut unsupported = Vec::new();
+        let mut color = |color: &CGColor| {
+            let converted = color_components(color);
+            if converted.is_none() {
+                unsupported.push("colors outside RGB");
+            }
+            converted
+        };
+        let fill_color = self
+            .fill_color
+            .as_deref()
+            .map_or(Some(Color::BLACK), &mut color)
+            .unwrap_or(Color::TRANSPARENT);
+        let stroke_color = self.stroke_color.as_deref().and_then(&mut color);
+
+        if self.transform.is_some() {
+            unsupported.push("transforms");
+        }
+        if self.translation.is_some() {
+            unsupported.push("translations");
+        }
+        if self.hidden == Some(true) {
+            unsupported.push("hidden layers");
+        }
+        if self.shadow_opacity.is_some_and(|opacity| opacity > 0.0) {
+            unsupported.push("shadows");
+        }
+
+        let animations = self
+            .animations
+            .into_iter()
+            .map(|pending| {
+                let animation = match pending.animation {
+                    Pending::Basic(builder) => ExportAnimation::Basic(builder),
+                    Pending::Keyframe(builder) => ExportAnimation::Keyframe(builder),
+                    Pending::Motion(_) => ExportAnimation::Unsupported("motion paths"),
+                    Pending::Preset(_) => ExportAnimation::Unsupported("presets"),
+                };
+                (pending.name, animation)
+            })
+            .collect();
+
+        ShapeLayerDescription {
+            name: self.name.unwrap_or_default(),
+            bounds: self
+                .bounds
+                .unwrap_or(CGRect::new(CGPoint::ZERO, CGSize::ZERO)),
+            position: self.position.unwrap_or(CGPoint::ZERO),
+            path: self
+                .path
+                .as_deref()
+                .map_or_else(BezierPath::new, BezierPath::from_cgpath),
+            fill_color,
+            stroke_color,
+            line_width: self.line_width.unwrap_or(1.0),
+            line_cap: self.line_cap.unwrap_or_default(),
+            line_join: self.line_join.unwrap_or_default(),
+            stroke_start: self.stroke_start.unwrap_or(0.0),
+            stroke_end: self.stroke_end.unwrap_or(1.0),
+            opacity: self.opacity.unwrap_or(1.0),
+            scale: self.scale.unwrap_or(1.0),
+            rotation: self.rotation.unwrap_or(0.0),
+            animations,
+            unsupported,
+        }
+    }
+}
+
+/// Reads an RGB or grayscale `CGColor`, with its alpha.
+#[cfg(feature = "lottie")]
+fn color_components(color: &CGColor) -> Option<Color> {
+    let count = CGColor::number_of_components(Some(color));
+    // SAFETY: The components array holds `count` values, alpha last.
+    let component = |i: usize| unsafe { *CGColor::components(Some(color)).add(i) };
+    match count {
+        4 => Some(Color::rgba(
+            component(0),
+            component(1),
+            component(2),
+            component(3),
+        )),
+        2 => Some(Color::rgba(
+            component(0),
+            component(0),
+            component(0),
+            component(1),
+        )),
+        _ => None,
+    }
+}
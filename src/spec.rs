@@ -0,0 +1,886 @@
+//! Animations described as data, loaded from JSON or TOML.
+//!
+//! An [`AnimationSpec`] holds everything needed to build an animation, so
+//! timings can live in a file and be tweaked without recompiling:
+//!
+//! ```toml
+//! key_path = "transform.scale"
+//! from = 0.85
+//! to = 1.15
+//! duration = 0.8
+//! easing = "cubic-bezier(0.2, 0, 0, 1)"
+//! autoreverses = true
+//! repeat = "forever"
+//! ```
+//!
+//! ```ignore
+//! let spec = AnimationSpec::from_toml(&std::fs::read_to_string("pulse.toml")?)?;
+//! layer.add_animation(&spec.build()?, "pulse");
+//!
+//! // Or keep tuning in code
+//! let builder = CABasicAnimationBuilder::try_from(&spec)?.on_complete(|_| done());
+//! ```
+//!
+//! The kind of animation follows from the fields that are set:
+//!
+//! | Fields | Kind | Builder |
+//! |--------|------|---------|
+//! | `from`/`to` or `by` | Basic | [`CABasicAnimationBuilder`] |
+//! | `values` (and `key_times`) | Keyframe | [`CAKeyframeAnimationBuilder`] |
+//! | `animations` | Group | [`CAAnimationGroupBuilder`] |
+//!
+//! Durations and delays are in seconds. Specs are checked when loaded, and
+//! [`SpecError`] names the offending field, e.g.
+//! `animations[1].easing: unknown easing "bouncy"`.
+//!
+//! Requires the `spec` feature.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2_quartz_core::CAAnimation;
+use serde::{Deserialize, Serialize};
+
+use crate::animation_builder::{
+    AnimationError, CAAnimationGroupBuilder, CABasicAnimationBuilder, CAKeyframeAnimationBuilder,
+    Easing, KeyPath, Repeat,
+};
+
+/// Key paths with a dedicated [`KeyPath`] variant.
+const KNOWN_KEY_PATHS: [KeyPath; 17] = [
+    KeyPath::TransformScale,
+    KeyPath::TransformScaleX,
+    KeyPath::TransformScaleY,
+    KeyPath::TransformRotation,
+    KeyPath::Opacity,
+    KeyPath::Position,
+    KeyPath::PositionX,
+    KeyPath::PositionY,
+    KeyPath::BackgroundColor,
+    KeyPath::CornerRadius,
+    KeyPath::BorderWidth,
+    KeyPath::BorderColor,
+    KeyPath::ShadowOpacity,
+    KeyPath::ShadowRadius,
+    KeyPath::ShadowOffset,
+    KeyPath::Bounds,
+    KeyPath::Path,
+];
+
+/// A serializable description of an animation.
+///
+/// See the [module docs](self) for the format. All fields are optional;
+/// [`validate`](Self::validate) checks that they fit together.
+///
+/// Key paths without a [`KeyPath`] variant become [`KeyPath::Custom`]. Their
+/// names are interned to get the `'static` lifetime, so each distinct name
+/// is kept once, however often specs are reloaded.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationSpec {
+    /// Property to animate, e.g. `"opacity"` or `"position.x"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    /// Starting value of a basic animation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    /// Ending value of a basic animation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+    /// Relative change of a basic animation, instead of `from`/`to`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by: Option<f64>,
+    /// Keyframe values.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<f64>,
+    /// Time of each keyframe, rising from exactly 0.0 to exactly 1.0.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub key_times: Vec<f64>,
+    /// Animations played together as a group.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub animations: Vec<AnimationSpec>,
+    /// Duration in seconds.
+    ///
+    /// Defaults to 0.25 for basic and keyframe animations, and to the
+    /// longest child for groups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Timing curve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub easing: Option<EasingSpec>,
+    /// How often to play.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<RepeatSpec>,
+    /// Play forward, then back.
+    pub autoreverses: bool,
+    /// Add to the layer's value instead of replacing it.
+    pub additive: bool,
+    /// Continue each repeat from where the previous one ended.
+    pub cumulative: bool,
+    /// Fraction of the cycle to skip, from 0.0 to 1.0.
+    pub phase_offset: f64,
+    /// Delay in seconds before the animation starts.
+    pub delay: f64,
+}
+
+/// A timing curve in an [`AnimationSpec`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EasingSpec {
+    /// `"linear"`, `"in"`, `"out"`, `"in-out"`, or a CSS-style
+    /// `"cubic-bezier(x1, y1, x2, y2)"`.
+    Name(String),
+    /// Cubic Bézier control points `[x1, y1, x2, y2]`.
+    ControlPoints([f64; 4]),
+}
+
+/// Repeat behavior in an [`AnimationSpec`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RepeatSpec {
+    /// `"once"` or `"forever"`.
+    Name(String),
+    /// Number of times to play.
+    Times(u32),
+}
+
+/// What an [`AnimationSpec`] builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecKind {
+    /// A from→to animation.
+    Basic,
+    /// An animation through a list of values.
+    Keyframe,
+    /// Several animations played as one.
+    Group,
+}
+
+impl fmt::Display for SpecKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SpecKind::Basic => "basic",
+            SpecKind::Keyframe => "keyframe",
+            SpecKind::Group => "group",
+        })
+    }
+}
+
+/// Why an [`AnimationSpec`] could not be loaded or built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecError {
+    /// The text is not valid JSON or TOML, or has fields of the wrong type.
+    Parse(String),
+    /// A field has a value that cannot be animated.
+    Invalid {
+        /// Path to the field, e.g. `animations[0].duration`.
+        field: String,
+        /// What is wrong with it.
+        reason: String,
+    },
+    /// The spec describes a different kind of animation than requested.
+    WrongKind {
+        /// The kind that was requested.
+        expected: SpecKind,
+        /// The kind the spec describes.
+        found: SpecKind,
+    },
+}
+
+impl SpecError {
+    fn invalid(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        SpecError::Invalid {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::Parse(message) => write!(f, "invalid animation spec: {message}"),
+            SpecError::Invalid { field, reason } => write!(f, "{field}: {reason}"),
+            SpecError::WrongKind { expected, found } => {
+                write!(f, "expected a {expected} animation, found a {found} one")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+impl AnimationSpec {
+    /// Parses and validates a spec from JSON.
+    pub fn from_json(text: &str) -> Result<Self, SpecError> {
+        let spec: Self =
+            serde_json::from_str(text).map_err(|err| SpecError::Parse(err.to_string()))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Parses and validates a spec from TOML.
+    pub fn from_toml(text: &str) -> Result<Self, SpecError> {
+        let spec: Self = toml::from_str(text).map_err(|err| SpecError::Parse(err.to_string()))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Returns what this spec builds, based on the fields that are set.
+    pub fn kind(&self) -> SpecKind {
+        if !self.animations.is_empty() {
+            SpecKind::Group
+        } else if !self.values.is_empty() {
+            SpecKind::Keyframe
+        } else {
+            SpecKind::Basic
+        }
+    }
+
+    /// Checks that the fields describe a valid animation.
+    pub fn validate(&self) -> Result<(), SpecError> {
+        self.validate_at("", false)
+    }
+
+    /// Builds the animation this spec describes.
+    ///
+    /// Besides the checks of [`validate`](Self::validate), fails if the
+    /// values don't fit the key path, e.g. numbers for `position`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a callback was set and this is not called from the main
+    /// thread.
+    pub fn build(&self) -> Result<Retained<CAAnimation>, SpecError> {
+        let anim = match self.kind() {
+            SpecKind::Basic => Retained::into_super(Retained::into_super(
+                CABasicAnimationBuilder::try_from(self)?
+                    .try_build()
+                    .map_err(|err| SpecError::invalid(error_field(self, &err), err.to_string()))?,
+            )),
+            SpecKind::Keyframe => {
+                let builder = CAKeyframeAnimationBuilder::try_from(self)?;
+                builder
+                    .validate()
+                    .map_err(|err| SpecError::invalid(error_field(self, &err), err.to_string()))?;
+                Retained::into_super(Retained::into_super(builder.build()))
+            }
+            SpecKind::Group => {
+                Retained::into_super(CAAnimationGroupBuilder::try_from(self)?.build())
+            }
+        };
+        Ok(anim)
+    }
+
+    fn validate_at(&self, prefix: &str, in_group: bool) -> Result<(), SpecError> {
+        let field = |name: &str| format!("{prefix}{name}");
+        let kind = self.kind();
+
+        if let Some(duration) = self.duration {
+            if !(duration.is_finite() && duration > 0.0) {
+                return Err(SpecError::invalid(
+                    field("duration"),
+                    format!("must be a positive number of seconds, got {duration}"),
+                ));
+            }
+        }
+        if !(self.delay.is_finite() && self.delay >= 0.0) {
+            return Err(SpecError::invalid(
+                field("delay"),
+                format!("must be zero or more seconds, got {}", self.delay),
+            ));
+        }
+        if in_group && self.delay > 0.0 {
+            return Err(SpecError::invalid(
+                field("delay"),
+                "not supported on animations inside a group",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.phase_offset) {
+            return Err(SpecError::invalid(
+                field("phase_offset"),
+                format!("must be between 0.0 and 1.0, got {}", self.phase_offset),
+            ));
+        }
+        if let Some(easing) = &self.easing {
+            easing
+                .to_easing()
+                .map_err(|reason| SpecError::invalid(field("easing"), reason))?;
+        }
+        if let Some(repeat) = &self.repeat {
+            repeat
+                .to_repeat()
+                .map_err(|reason| SpecError::invalid(field("repeat"), reason))?;
+        }
+
+        if kind == SpecKind::Group {
+            let stray = [
+                ("key_path", self.key_path.is_some()),
+                ("from", self.from.is_some()),
+                ("to", self.to.is_some()),
+                ("by", self.by.is_some()),
+                ("values", !self.values.is_empty()),
+                ("key_times", !self.key_times.is_empty()),
+                ("additive", self.additive),
+                ("cumulative", self.cumulative),
+            ];
+            if let Some(&(name, _)) = stray.iter().find(|(_, set)| *set) {
+                return Err(SpecError::invalid(
+                    field(name),
+                    "belongs on the animations inside the group",
+                ));
+            }
+            for (index, child) in self.animations.iter().enumerate() {
+                child.validate_at(&format!("{prefix}animations[{index}]."), true)?;
+            }
+            return Ok(());
+        }
+
+        match self.key_path.as_deref() {
+            None => return Err(SpecError::invalid(field("key_path"), "is required")),
+            Some("") => return Err(SpecError::invalid(field("key_path"), "must not be empty")),
+            Some(_) => {}
+        }
+
+        for (name, value) in [("from", self.from), ("to", self.to), ("by", self.by)] {
+            if let Some(value) = value.filter(|value| !value.is_finite()) {
+                return Err(SpecError::invalid(
+                    field(name),
+                    format!("must be a finite number, got {value}"),
+                ));
+            }
+        }
+        if let Some(value) = self.values.iter().find(|value| !value.is_finite()) {
+            return Err(SpecError::invalid(
+                field("values"),
+                format!("must be finite numbers, got {value}"),
+            ));
+        }
+
+        if kind == SpecKind::Keyframe {
+            if let Some(name) = [("from", self.from), ("to", self.to), ("by", self.by)]
+                .iter()
+                .find_map(|&(name, value)| value.map(|_| name))
+            {
+                return Err(SpecError::invalid(
+                    field(name),
+                    "cannot be combined with `values`",
+                ));
+            }
+            if self.values.len() < 2 {
+                return Err(SpecError::invalid(
+                    field("values"),
+                    "needs at least two keyframes",
+                ));
+            }
+            if !self.key_times.is_empty() {
+                if self.key_times.len() != self.values.len() {
+                    return Err(SpecError::invalid(
+                        field("key_times"),
+                        format!(
+                            "has {} entries but `values` has {}",
+                            self.key_times.len(),
+                            self.values.len()
+                        ),
+                    ));
+                }
+                if self
+                    .key_times
+                    .iter()
+                    .any(|time| !(0.0..=1.0).contains(time))
+                    || self.key_times.windows(2).any(|pair| pair[1] < pair[0])
+                    || self.key_times.first() != Some(&0.0)
+                    || self.key_times.last() != Some(&1.0)
+                {
+                    return Err(SpecError::invalid(
+                        field("key_times"),
+                        "must rise from 0.0 to 1.0",
+                    ));
+                }
+            }
+            return Ok(());
+        }
+
+        if !self.key_times.is_empty() {
+            return Err(SpecError::invalid(field("key_times"), "requires `values`"));
+        }
+        match (self.from, self.to, self.by) {
+            (_, Some(_), None) | (None, None, Some(_)) => Ok(()),
+            (_, _, Some(_)) => Err(SpecError::invalid(
+                field("by"),
+                "cannot be combined with `from` or `to`",
+            )),
+            (Some(_), None, None) => {
+                Err(SpecError::invalid(field("to"), "is required with `from`"))
+            }
+            (None, None, None) => Err(SpecError::invalid(
+                field("to"),
+                "is required; set `from`/`to`, `by`, `values` or `animations`",
+            )),
+        }
+    }
+
+    /// Checks the spec and that it builds `expected`.
+    fn check_kind(&self, expected: SpecKind) -> Result<(), SpecError> {
+        self.validate()?;
+        let found = self.kind();
+        if found != expected {
+            return Err(SpecError::WrongKind { expected, found });
+        }
+        Ok(())
+    }
+
+    fn key_path(&self) -> KeyPath {
+        let name = self.key_path.as_deref().unwrap_or_default();
+        KNOWN_KEY_PATHS
+            .into_iter()
+            .find(|key_path| key_path.as_str() == name)
+            .unwrap_or_else(|| KeyPath::Custom(intern(name)))
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.duration.map(Duration::from_secs_f64)
+    }
+
+    /// Easing and repeat, already validated.
+    fn easing_and_repeat(&self) -> (Option<Easing>, Option<Repeat>) {
+        (
+            self.easing
+                .as_ref()
+                .and_then(|easing| easing.to_easing().ok()),
+            self.repeat
+                .as_ref()
+                .and_then(|repeat| repeat.to_repeat().ok()),
+        )
+    }
+}
+
+impl EasingSpec {
+    /// Converts to an [`Easing`], or explains why the curve is invalid.
+    pub fn to_easing(&self) -> Result<Easing, String> {
+        let [x1, y1, x2, y2] = match self {
+            EasingSpec::Name(name) => match name.as_str() {
+                "linear" => return Ok(Easing::Linear),
+                "in" | "ease-in" => return Ok(Easing::In),
+                "out" | "ease-out" => return Ok(Easing::Out),
+                "in-out" | "ease-in-out" => return Ok(Easing::InOut),
+                other => parse_cubic_bezier(other).ok_or_else(|| {
+                    format!(
+                        "unknown easing {other:?}, expected \"linear\", \"in\", \"out\", \
+                         \"in-out\" or \"cubic-bezier(x1, y1, x2, y2)\""
+                    )
+                })?,
+            },
+            EasingSpec::ControlPoints(points) => *points,
+        };
+        if ![x1, y1, x2, y2].iter().all(|point| point.is_finite()) {
+            return Err("control points must be finite".to_string());
+        }
+        if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+            return Err(format!(
+                "x coordinates must be between 0.0 and 1.0, got {x1} and {x2}"
+            ));
+        }
+        Ok(Easing::CubicBezier(x1, y1, x2, y2))
+    }
+}
+
+/// Returns a `'static` copy of `name`, allocated once per distinct name.
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(&interned) = names.get(name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.into());
+    names.insert(interned);
+    interned
+}
+
+/// The field of a basic or keyframe spec that an [`AnimationError`] is
+/// about.
+fn error_field(spec: &AnimationSpec, err: &AnimationError) -> &'static str {
+    let values = if spec.kind() == SpecKind::Keyframe {
+        "values"
+    } else {
+        "to"
+    };
+    match err {
+        AnimationError::ZeroDuration => "duration",
+        AnimationError::PhaseOffsetOutOfRange(_) => "phase_offset",
+        AnimationError::ZeroRepeatCount
+        | AnimationError::InvalidRepeatCount(_)
+        | AnimationError::ZeroRepeatDuration => "repeat",
+        AnimationError::WrongValueType { .. } => "key_path",
+        AnimationError::NonFiniteValue(_) => {
+            [("from", spec.from), ("to", spec.to), ("by", spec.by)]
+                .into_iter()
+                .find(|(_, value)| value.is_some_and(|value| !value.is_finite()))
+                .map_or(values, |(name, _)| name)
+        }
+        AnimationError::MissingValues => values,
+        AnimationError::InvalidSpeed(_)
+        | AnimationError::HoldWithoutValues
+        | AnimationError::HoldNeedsKeyframes => "to",
+    }
+}
+
+/// Parses `cubic-bezier(x1, y1, x2, y2)`.
+fn parse_cubic_bezier(text: &str) -> Option<[f64; 4]> {
+    let args = text
+        .trim()
+        .strip_prefix("cubic-bezier(")?
+        .strip_suffix(')')?;
+    let numbers: Vec<f64> = args
+        .split(',')
+        .map(|arg| arg.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    numbers.try_into().ok()
+}
+
+impl RepeatSpec {
+    /// Converts to a [`Repeat`], or explains why the value is invalid.
+    pub fn to_repeat(&self) -> Result<Repeat, String> {
+        match self {
+            RepeatSpec::Name(name) => match name.as_str() {
+                "once" => Ok(Repeat::Once),
+                "forever" => Ok(Repeat::Forever),
+                other => Err(format!(
+                    "unknown repeat {other:?}, expected \"once\", \"forever\" or a count"
+                )),
+            },
+            RepeatSpec::Times(0) => Err("count must be at least 1".to_string()),
+            RepeatSpec::Times(times) => Ok(Repeat::Times(*times)),
+        }
+    }
+}
+
+impl TryFrom<&AnimationSpec> for CABasicAnimationBuilder {
+    type Error = SpecError;
+
+    fn try_from(spec: &AnimationSpec) -> Result<Self, Self::Error> {
+        spec.check_kind(SpecKind::Basic)?;
+
+        let mut builder = CABasicAnimationBuilder::new(spec.key_path());
+        builder = match (spec.from, spec.to, spec.by) {
+            (Some(from), Some(to), _) => builder.values(from, to),
+            (None, Some(to), _) => builder.from_current_to(to),
+            (_, _, Some(by)) => builder.by(by),
+            _ => builder,
+        };
+        if spec.additive {
+            builder = builder.additive();
+        }
+        if spec.cumulative {
+            builder = builder.cumulative();
+        }
+
+        let (easing, repeat) = spec.easing_and_repeat();
+        if let Some(duration) = spec.duration() {
+            builder = builder.duration(duration);
+        }
+        if let Some(easing) = easing {
+            builder = builder.easing(easing);
+        }
+        if let Some(repeat) = repeat {
+            builder = builder.repeat(repeat);
+        }
+        if spec.autoreverses {
+            builder = builder.autoreverses();
+        }
+        Ok(builder
+            .phase_offset(spec.phase_offset)
+            .delay(Duration::from_secs_f64(spec.delay)))
+    }
+}
+
+impl TryFrom<&AnimationSpec> for CAKeyframeAnimationBuilder {
+    type Error = SpecError;
+
+    fn try_from(spec: &AnimationSpec) -> Result<Self, Self::Error> {
+        spec.check_kind(SpecKind::Keyframe)?;
+
+        let mut builder = CAKeyframeAnimationBuilder::new(spec.key_path())
+            .values(spec.values.iter().copied())
+            .key_times(spec.key_times.iter().copied());
+        if spec.additive {
+            builder = builder.additive();
+        }
+        if spec.cumulative {
+            builder = builder.cumulative();
+        }
+
+        let (easing, repeat) = spec.easing_and_repeat();
+        if let Some(duration) = spec.duration() {
+            builder = builder.duration(duration);
+        }
+        if let Some(easing) = easing {
+            builder = builder.easing(easing);
+        }
+        if let Some(repeat) = repeat {
+            builder = builder.repeat(repeat);
+        }
+        if spec.autoreverses {
+            builder = builder.autoreverses();
+        }
+        Ok(builder
+            .phase_offset(spec.phase_offset)
+            .delay(Duration::from_secs_f64(spec.delay)))
+    }
+}
+
+impl TryFrom<&AnimationSpec> for CAAnimationGroupBuilder {
+    type Error = SpecError;
+
+    /// Builds the animations inside the group right away, as
+    /// [`CAAnimationGroupBuilder`] holds finished animations.
+    fn try_from(spec: &AnimationSpec) -> Result<Self, Self::Error> {
+        spec.check_kind(SpecKind::Group)?;
+
+        let mut builder = CAAnimationGroupBuilder::new();
+        for child in &spec.animations {
+            builder = builder.animation(child.build()?);
+        }
+
+        let longest = spec
+            .animations
+            .iter()
+            .filter_map(AnimationSpec::duration)
+            .max()
+            .unwrap_or(Duration::from_millis(250));
+        let (easing, repeat) = spec.easing_and_repeat();
+        builder = builder.duration(spec.duration().unwrap_or(longest));
+        if let Some(easing) = easing {
+            builder = builder.easing(easing);
+        }
+        if let Some(repeat) = repeat {
+            builder = builder.repeat(repeat);
+        }
+        if spec.autoreverses {
+            builder = builder.autoreverses();
+        }
+        Ok(builder
+            .phase_offset(spec.phase_offset)
+            .delay(Duration::from_secs_f64(spec.delay)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation_builder::AnimationValue;
+
+    fn invalid_field(result: Result<AnimationSpec, SpecError>) -> String {
+        match result {
+            Err(SpecError::Invalid { field, .. }) => field,
+            other => panic!("expected an invalid field, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let spec = AnimationSpec::from_toml(
+            r#"
+            key_path = "transform.scale"
+            from = 0.85
+            to = 1.15
+            duration = 0.8
+            easing = "cubic-bezier(0.2, 0, 0, 1)"
+            autoreverses = true
+            repeat = "forever"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(spec.kind(), SpecKind::Basic);
+        assert_eq!(spec.key_path(), KeyPath::TransformScale);
+        assert_eq!(spec.duration(), Some(Duration::from_millis(800)));
+        assert_eq!(
+            spec.easing_and_repeat(),
+            (
+                Some(Easing::CubicBezier(0.2, 0.0, 0.0, 1.0)),
+                Some(Repeat::Forever)
+            )
+        );
+        assert!(spec.autoreverses);
+    }
+
+    #[test]
+    fn test_parse_json_group() {
+        let spec = AnimationSpec::from_json(
+            r#"{
+                "animations": [
+                    { "key_path": "opacity", "from": 0, "to": 1, "duration": 0.4 },
+                    { "key_path": "position.y", "values": [0, -20, 0], "duration": 0.6,
+                      "easing": [0.3, 0, 0.2, 1] }
+                ],
+                "repeat": 3
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(spec.kind(), SpecKind::Group);
+        assert_eq!(spec.animations[1].kind(), SpecKind::Keyframe);
+        assert_eq!(spec.repeat, Some(RepeatSpec::Times(3)));
+    }
+
+    #[test]
+    fn test_custom_key_path() {
+        let spec = AnimationSpec {
+            key_path: Some("bounds.size.width".into()),
+            to: Some(100.0),
+            ..Default::default()
+        };
+        assert_eq!(spec.key_path(), KeyPath::Custom("bounds.size.width"));
+
+        // Reloading the same spec reuses the interned name.
+        let (KeyPath::Custom(first), KeyPath::Custom(second)) =
+            (spec.key_path(), spec.clone().key_path())
+        else {
+            panic!("expected custom key paths");
+        };
+        assert!(std::ptr::eq(first, second));
+    }
+
+    #[test]
+    fn test_converts_to_builder() {
+        let spec = AnimationSpec::from_json(
+            r#"{ "key_path": "opacity", "from": 0, "to": 1, "duration": 2,
+                 "easing": "linear", "delay": 1 }"#,
+        )
+        .unwrap();
+        let eval = CABasicAnimationBuilder::try_from(&spec)
+            .unwrap()
+            .evaluator();
+        assert_eq!(
+            eval.value_at(Duration::from_millis(1500)),
+            Some(AnimationValue::Scalar(0.25))
+        );
+
+        let err = CAKeyframeAnimationBuilder::try_from(&spec).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "expected a keyframe animation, found a basic one"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            AnimationSpec::from_json(r#"{ "key_path": "opacity", "to": "one" }"#),
+            Err(SpecError::Parse(_))
+        ));
+        assert!(matches!(
+            AnimationSpec::from_toml("key_path = \"opacity\"\nto = 1.0\nspeed = 2.0"),
+            Err(SpecError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let parse = AnimationSpec::from_json;
+        assert_eq!(invalid_field(parse(r#"{ "to": 1 }"#)), "key_path");
+        assert_eq!(invalid_field(parse(r#"{ "key_path": "opacity" }"#)), "to");
+        assert_eq!(
+            invalid_field(parse(
+                r#"{ "key_path": "opacity", "to": 1, "duration": -1 }"#
+            )),
+            "duration"
+        );
+        assert_eq!(
+            invalid_field(parse(r#"{ "key_path": "opacity", "to": 1, "by": 1 }"#)),
+            "by"
+        );
+        assert_eq!(
+            invalid_field(parse(
+                r#"{ "key_path": "opacity", "values": [0, 1], "key_times": [0] }"#
+            )),
+            "key_times"
+        );
+        // Core Animation needs key times from exactly 0.0 to 1.0.
+        for key_times in ["[0.2, 1]", "[0, 0.8]"] {
+            let json = format!(
+                r#"{{ "key_path": "opacity", "values": [0, 1], "key_times": {key_times} }}"#
+            );
+            assert_eq!(invalid_field(parse(&json)), "key_times");
+        }
+        assert_eq!(
+            invalid_field(parse(
+                r#"{ "animations": [{ "key_path": "opacity", "to": 1, "repeat": 0 }] }"#
+            )),
+            "animations[0].repeat"
+        );
+
+        // TOML spells out infinities and NaN.
+        assert_eq!(
+            invalid_field(AnimationSpec::from_toml(
+                "key_path = \"opacity\"\nfrom = inf\nto = 1.0"
+            )),
+            "from"
+        );
+        assert_eq!(
+            invalid_field(AnimationSpec::from_toml(
+                "key_path = \"opacity\"\nvalues = [0.0, nan, 1.0]"
+            )),
+            "values"
+        );
+
+        let err = parse(r#"{ "key_path": "opacity", "to": 1, "easing": "bouncy" }"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("easing: unknown easing \"bouncy\""));
+    }
+
+    #[test]
+    fn test_builder_error_fields() {
+        let spec =
+            AnimationSpec::from_json(r#"{ "key_path": "position", "values": [0, 1] }"#).unwrap();
+        let err = CAKeyframeAnimationBuilder::try_from(&spec)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(error_field(&spec, &err), "key_path");
+
+        let spec = AnimationSpec {
+            key_path: Some("opacity".into()),
+            by: Some(f64::INFINITY),
+            ..Default::default()
+        };
+        let err = AnimationError::NonFiniteValue(f64::INFINITY);
+        assert_eq!(error_field(&spec, &err), "by");
+        assert_eq!(error_field(&AnimationSpec::default(), &err), "to");
+    }
+
+    #[test]
+    fn test_easing_spec() {
+        let named = |name: &str| EasingSpec::Name(name.into()).to_easing();
+        assert_eq!(named("in-out"), Ok(Easing::InOut));
+        assert_eq!(named("ease-in"), Ok(Easing::In));
+        assert_eq!(
+            named("cubic-bezier(0.4,0,0.2,1)"),
+            Ok(Easing::CubicBezier(0.4, 0.0, 0.2, 1.0))
+        );
+        assert!(named("cubic-bezier(0.4, 0, 0.2)").is_err());
+        assert!(EasingSpec::ControlPoints([1.5, 0.0, 0.5, 1.0])
+            .to_easing()
+            .is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let spec = AnimationSpec {
+            key_path: Some("opacity".into()),
+            values: vec![0.0, 1.0, 0.5],
+            easing: Some(EasingSpec::ControlPoints([0.2, 0.0, 0.0, 1.0])),
+            repeat: Some(RepeatSpec::Name("forever".into())),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(AnimationSpec::from_json(&json).unwrap(), spec);
+        let text = toml::to_string(&spec).unwrap();
+        assert_eq!(AnimationSpec::from_toml(&text).unwrap(), spec);
+    }
+}
@@ -0,0 +1,349 @@
+//! Staggered timing across groups of layers.
+//!
+//! A [`Stagger`] spreads delays or phase offsets over a row or grid of
+//! items, so loaders and ripples no longer compute `phase_offset` by hand.
+//!
+//! ```ignore
+//! // Wave through a row of dots, starting from the middle
+//! Stagger::new()
+//!     .from(Distribution::FromCenter)
+//!     .animate_staggered(&dots, "pulse", KeyPath::TransformScale, StaggerTiming::Phase, |a| {
+//!         a.values(0.5, 1.2)
+//!             .duration(600.millis())
+//!             .autoreverses()
+//!             .repeat(Repeat::Forever)
+//!     });
+//!
+//! // Reveal a 6-column grid, 40 ms apart, easing the spread
+//! let delays = Stagger::new()
+//!     .grid(6)
+//!     .easing(Easing::Out)
+//!     .delays(tiles.len(), 40.millis());
+//! ```
+//!
+//! # Distributions
+//!
+//! | Variant | Starts at |
+//! |---------|-----------|
+//! | `Linear` | First item, then in order (default) |
+//! | `Reverse` | Last item, then backwards |
+//! | `FromCenter` | Middle, spreading outwards |
+//! | `FromEdges` | Outermost items, closing in on the middle |
+//! | `FromIndex(i)` | Item `i`, spreading outwards |
+//! | `Random { seed }` | Shuffled, reproducible for a given seed |
+//!
+//! On a [`grid`](Stagger::grid), distances are measured in cells, so
+//! `FromCenter` spreads out in rings.
+
+use std::time::Duration;
+
+use objc2_foundation::NSString;
+use objc2_quartz_core::{CACurrentMediaTime, CALayer};
+
+use crate::animation_builder::{CABasicAnimationBuilder, Easing, KeyPath};
+use crate::layer_ext::{add_animation_at, CALayerExt};
+use crate::random::Rng;
+use crate::timeline::cue_fill_mode;
+
+/// Where a [`Stagger`] starts and how it spreads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Distribution {
+    /// From the first item to the last (default).
+    #[default]
+    Linear,
+    /// From the last item to the first.
+    Reverse,
+    /// From the middle outwards.
+    FromCenter,
+    /// From the outermost items inwards.
+    FromEdges,
+    /// Outwards from the item at this index.
+    FromIndex(usize),
+    /// Random order, reproducible for the same seed.
+    Random {
+        /// Seed for the random order.
+        seed: u64,
+    },
+}
+
+/// How [`Stagger::animate_staggered`] offsets each layer's animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StaggerTiming {
+    /// Delay each start by this much per step, on top of the builder's own
+    /// delay.
+    ///
+    /// Layers hold the animation's start value while they wait.
+    Delay(Duration),
+    /// Start every layer now, at a different point in its cycle.
+    ///
+    /// Best for looping animations.
+    Phase,
+}
+
+/// Computes staggered offsets for a group of items.
+///
+/// Items are laid out in a single row unless [`grid`](Self::grid) is set.
+/// See the [module docs](self) for examples.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Stagger {
+    distribution: Distribution,
+    columns: Option<usize>,
+    easing: Option<Easing>,
+}
+
+impl Stagger {
+    /// Creates a linear stagger over a single row.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the distribution.
+    #[must_use]
+    pub fn from(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Lays items out in a grid with this many columns, row by row.
+    #[must_use]
+    pub fn grid(mut self, columns: usize) -> Self {
+        self.columns = Some(columns.max(1));
+        self
+    }
+
+    /// Eases the spread, e.g. `Easing::In` bunches early items together
+    /// and spreads later ones apart.
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = Some(easing);
+        self
+    }
+
+    /// Returns each item's position in the stagger, from 0.0 (first to
+    /// start) to 1.0 (last to start).
+    pub fn fractions(&self, count: usize) -> Vec<f64> {
+        let distances = self.distances(count);
+        let span = max_distance(&distances);
+        distances
+            .iter()
+            .map(|&distance| self.shape(distance, span))
+            .collect()
+    }
+
+    /// Returns a start delay per item, with `each` between neighbors.
+    ///
+    /// With the default linear distribution, item `i` waits `i * each`.
+    pub fn delays(&self, count: usize, each: Duration) -> Vec<Duration> {
+        let distances = self.distances(count);
+        let span = max_distance(&distances);
+        distances
+            .iter()
+            .map(|&distance| each.mul_f64(self.shape(distance, span) * span))
+            .collect()
+    }
+
+    /// Returns a phase offset per item, for use with
+    /// [`phase_offset`](CABasicAnimationBuilder::phase_offset).
+    ///
+    /// Offsets spread over the cycle without wrapping around, so the last
+    /// item does not line up with the first: five linear items get 0.0,
+    /// 0.2, 0.4, 0.6 and 0.8.
+    pub fn phase_offsets(&self, count: usize) -> Vec<f64> {
+        let distances = self.distances(count);
+        let span = max_distance(&distances);
+        distances
+            .iter()
+            .map(|&distance| self.shape(distance, span) * span / (span + 1.0))
+            .collect()
+    }
+
+    /// Adds the same animation to every layer, staggered.
+    ///
+    /// `configure` is called once per layer with a fresh builder, as with
+    /// `.animate()` on the layer builders. The stagger then adds to the
+    /// builder's delay or sets its phase offset according to `timing`.
+    pub fn animate_staggered<L, F>(
+        &self,
+        layers: &[L],
+        name: &str,
+        key_path: KeyPath,
+        timing: StaggerTiming,
+        configure: F,
+    ) where
+        L: AsRef<CALayer>,
+        F: Fn(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
+    {
+        match timing {
+            StaggerTiming::Phase => {
+                for (layer, phase) in layers.iter().zip(self.phase_offsets(layers.len())) {
+                    let anim = configure(CABasicAnimationBuilder::new(key_path))
+                        .phase_offset(phase)
+                        .build_animation();
+                    layer.as_ref().add_animation(&anim, name);
+                }
+            }
+            StaggerTiming::Delay(each) => {
+                let key = NSString::from_str(name);
+                let now = CACurrentMediaTime();
+                for (layer, delay) in layers.iter().zip(self.delays(layers.len(), each)) {
+                    let builder = configure(CABasicAnimationBuilder::new(key_path));
+                    let delay = builder.timing.delay + delay;
+                    // Hold the start value during the delay.
+                    let fill_mode = cue_fill_mode(delay, builder.timing.fill_mode());
+                    let anim = builder.delay(delay).fill_mode(fill_mode).build_animation();
+                    add_animation_at(layer.as_ref(), &anim, &key, now);
+                }
+            }
+        }
+    }
+
+    /// Raw distance of each item from where the stagger starts, in steps.
+    fn distances(&self, count: usize) -> Vec<f64> {
+        if count == 0 {
+            return Vec::new();
+        }
+        let columns = self.columns.unwrap_or(count).min(count);
+        let rows = count.div_ceil(columns);
+        let cell = |index: usize| ((index % columns) as f64, (index / columns) as f64);
+        let from = |origin: (f64, f64)| -> Vec<f64> {
+            (0..count)
+                .map(|index| {
+                    let (x, y) = cell(index);
+                    (x - origin.0).hypot(y - origin.1)
+                })
+                .collect()
+        };
+        let center = ((columns - 1) as f64 / 2.0, (rows - 1) as f64 / 2.0);
+
+        match self.distribution {
+            Distribution::Linear => from(cell(0)),
+            Distribution::Reverse => from(cell(count - 1)),
+            Distribution::FromCenter => from(center),
+            Distribution::FromEdges => {
+                let distances = from(center);
+                let span = max_distance(&distances);
+                distances.iter().map(|distance| span - distance).collect()
+            }
+            Distribution::FromIndex(index) => from(cell(index.min(count - 1))),
+            Distribution::Random { seed } => {
+                // Fisher-Yates over the linear order, so every slot is used once.
+                let mut distances = from(cell(0));
+                let mut rng = Rng::new(seed);
+                for i in (1..count).rev() {
+                    let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+                    distances.swap(i, j);
+                }
+                distances
+            }
+        }
+    }
+
+    /// Normalizes a distance to 0.0..=1.0 and applies the easing.
+    fn shape(&self, distance: f64, span: f64) -> f64 {
+        let fraction = if span > 0.0 { distance / span } else { 0.0 };
+        match self.easing {
+            Some(easing) => easing.ease(fraction),
+            None => fraction,
+        }
+    }
+}
+
+fn max_distance(distances: &[f64]) -> f64 {
+    distances.iter().copied().fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_all_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn test_linear() {
+        let stagger = Stagger::new();
+        assert_all_close(&stagger.fractions(5), &[0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_all_close(&stagger.phase_offsets(5), &[0.0, 0.2, 0.4, 0.6, 0.8]);
+        assert_eq!(
+            stagger.delays(3, Duration::from_millis(100)),
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverse_and_index() {
+        let reverse = Stagger::new().from(Distribution::Reverse);
+        assert_all_close(&reverse.fractions(3), &[1.0, 0.5, 0.0]);
+
+        let from_index = Stagger::new().from(Distribution::FromIndex(1));
+        assert_all_close(&from_index.fractions(4), &[0.5, 0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_center_and_edges() {
+        let center = Stagger::new().from(Distribution::FromCenter);
+        assert_all_close(&center.fractions(5), &[1.0, 0.5, 0.0, 0.5, 1.0]);
+        assert_all_close(&center.fractions(4), &[1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0]);
+
+        let edges = Stagger::new().from(Distribution::FromEdges);
+        assert_all_close(&edges.fractions(5), &[0.0, 0.5, 1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_grid_from_center() {
+        let stagger = Stagger::new().grid(3).from(Distribution::FromCenter);
+        let fractions = stagger.fractions(9);
+        assert_eq!(fractions[4], 0.0);
+        assert!((fractions[1] - fractions[3]).abs() < 1e-9);
+        assert_eq!(fractions[0], 1.0);
+        assert!(fractions[1] < fractions[0]);
+    }
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let stagger = Stagger::new().from(Distribution::Random { seed: 7 });
+        let first = stagger.delays(8, Duration::from_millis(50));
+        assert_eq!(first, stagger.delays(8, Duration::from_millis(50)));
+        assert!(first
+            .iter()
+            .all(|&delay| delay <= Duration::from_millis(350)));
+
+        let other = Stagger::new().from(Distribution::Random { seed: 8 });
+        assert_ne!(first, other.delays(8, Duration::from_millis(50)));
+
+        // A shuffle of the linear delays: no collisions, full span used.
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, Stagger::new().delays(8, Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_eased() {
+        let stagger = Stagger::new().easing(Easing::In);
+        let fractions = stagger.fractions(5);
+        assert_eq!(fractions[0], 0.0);
+        assert!((fractions[4] - 1.0).abs() < 1e-6);
+        assert!(fractions[2] < 0.5);
+    }
+
+    #[test]
+    fn test_degenerate_counts() {
+        let stagger = Stagger::new();
+        assert!(stagger.fractions(0).is_empty());
+        assert_eq!(stagger.fractions(1), vec![0.0]);
+        assert_eq!(stagger.phase_offsets(1), vec![0.0]);
+        assert_eq!(
+            stagger.delays(1, Duration::from_secs(1)),
+            vec![Duration::ZERO]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 //! Builder for `CATextLayer` (text rendering layer).
 
-use crate::animation_builder::{CABasicAnimationBuilder, KeyPath};
+use crate::animation_builder::presets::PresetBuilder;
+use crate::animation_builder::{CABasicAnimationBuilder, KeyPath, MotionPathBuilder};
 use crate::color::Color;
+use crate::layer_ext::CALayerExt;
 use objc2::rc::Retained;
 use objc2_core_foundation::{CFRetained, CFString, CGFloat, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::CGColor;
@@ -10,13 +12,13 @@ use objc2_foundation::NSString;
 use objc2_quartz_core::{
     kCAAlignmentCenter, kCAAlignmentJustified, kCAAlignmentLeft, kCAAlignmentNatural,
     kCAAlignmentRight, kCATruncationEnd, kCATruncationMiddle, kCATruncationNone,
-    kCATruncationStart, CABasicAnimation, CATextLayer, CATransform3D,
+    kCATruncationStart, CAAnimation, CALayer, CATextLayer, CATransform3D,
 };
 
 /// A pending animation to be applied when the layer is built.
 struct PendingAnimation {
     name: String,
-    animation: Retained<CABasicAnimation>,
+    animation: Retained<CAAnimation>,
 }
 
 /// Text alignment modes for `CATextLayer`.
@@ -661,7 +663,7 @@ impl CATextLayerBuilder {
         F: FnOnce(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
     {
         let builder = CABasicAnimationBuilder::new(key_path);
-        let animation = configure(builder).build();
+        let animation = configure(builder).build_animation();
         self.animations.push(PendingAnimation {
             name: name.into(),
             animation,
@@ -669,6 +671,71 @@ impl CATextLayerBuilder {
         self
     }
 
+    /// Adds a motion path animation to be applied when the layer is built.
+    ///
+    /// The closure receives a [`MotionPathBuilder`] that moves the layer's
+    /// `position` along a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `configure` - A closure that configures the motion path builder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Slide a label along a curve, starting halfway
+    /// CATextLayerBuilder::new()
+    ///     .text("Hello")
+    ///     .font_size(18.0)
+    ///     .animate_motion("slide", |m| {
+    ///         m.path(&curve)
+    ///             .start_offset(0.5)
+    ///             .duration(1.seconds())
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn animate_motion<F>(mut self, name: impl Into<String>, configure: F) -> Self
+    where
+        F: FnOnce(MotionPathBuilder) -> MotionPathBuilder,
+    {
+        let animation = configure(MotionPathBuilder::new()).build();
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: Retained::into_super(Retained::into_super(animation)),
+        });
+        self
+    }
+
+    /// Adds a ready-made effect to be applied when the layer is built.
+    ///
+    /// Accepts a [`Preset`](crate::animation_builder::presets::Preset) directly, or a [`PresetBuilder`] for tuning.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A unique identifier for this animation (used as the animation key)
+    /// * `preset` - The effect to apply
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// CATextLayerBuilder::new()
+    ///     .animate_preset("intro", Preset::BounceIn)
+    ///     .animate_preset("idle", Preset::Heartbeat.repeat(Repeat::Forever).delay(1.seconds()))
+    ///     .build();
+    /// ```
+    pub fn animate_preset(
+        mut self,
+        name: impl Into<String>,
+        preset: impl Into<PresetBuilder>,
+    ) -> Self {
+        self.animations.push(PendingAnimation {
+            name: name.into(),
+            animation: preset.into().build(),
+        });
+        self
+    }
+
     // ========================================================================
     // Build
     // ========================================================================
@@ -677,6 +744,12 @@ impl CATextLayerBuilder {
     ///
     /// All pending animations added via `.animate()` are applied to the layer.
     pub fn build(self) -> Retained<CATextLayer> {
+        self.build_in(None)
+    }
+
+    /// Builds the layer and adds it to `superlayer` before applying the
+    /// pending animations, so their delays count on the final tree's clock.
+    pub(crate) fn build_in(self, superlayer: Option<&CALayer>) -> Retained<CATextLayer> {
         let layer = CATextLayer::new();
 
         // Set text content
@@ -785,10 +858,13 @@ impl CATextLayerBuilder {
             layer.setShadowOpacity(opacity);
         }
 
+        if let Some(superlayer) = superlayer {
+            superlayer.addSublayer(&layer);
+        }
+
         // Apply all pending animations
         for pending in self.animations {
-            let key = NSString::from_str(&pending.name);
-            layer.addAnimation_forKey(&pending.animation, Some(&key));
+            layer.add_animation(&pending.animation, &pending.name);
         }
 
         layer
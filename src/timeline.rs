@@ -0,0 +1,568 @@
+//! Timeline sequencing for multi-step animations.
+//!
+//! A [`Timeline`] lays out animations against a shared clock: steps run in
+//! sequence by default, and [`parallel`](Timeline::parallel),
+//! [`delay`](Timeline::delay), [`repeat`](Timeline::repeat) and
+//! [`label`](Timeline::label) shape the layout. [`play`](Timeline::play)
+//! resolves every begin time and adds the animations to their layers in one
+//! go, so intros no longer need hand-computed `beginTime` offsets.
+//!
+//! ```ignore
+//! let intro = Timeline::new()
+//!     .animate(&title, "fade", KeyPath::Opacity, |a| {
+//!         a.values(0.0, 1.0).duration(300.millis())
+//!     })
+//!     .delay(100.millis())
+//!     .label("cards")
+//!     .parallel(|p| {
+//!         p.animate(&left, "slide", KeyPath::PositionY, |a| {
+//!             a.values(-40.0, 80.0).duration(400.millis())
+//!         })
+//!         .animate(&right, "slide", KeyPath::PositionY, |a| {
+//!             a.values(-40.0, 80.0).duration(400.millis())
+//!         })
+//!     })
+//!     .repeat(3, |r| {
+//!         r.animate(&badge, "pop", KeyPath::TransformScale, |a| {
+//!             a.values(1.0, 1.2).duration(150.millis()).autoreverses()
+//!         })
+//!     });
+//!
+//! let schedule = intro.play();
+//! println!("cards start at {:?}", schedule.marker("cards"));
+//! ```
+//!
+//! # Scheduling
+//!
+//! The layout is plain data. A `Timeline<T>` works with any cue type, and
+//! [`schedule`](Timeline::schedule) returns the resolved [`Schedule`]
+//! without touching Core Animation:
+//!
+//! ```ignore
+//! let schedule = Timeline::new()
+//!     .cue("a", 200.millis())
+//!     .parallel(|p| p.cue("b", 100.millis()).cue("c", 300.millis()))
+//!     .cue("d", 100.millis())
+//!     .schedule();
+//!
+//! assert_eq!(schedule.duration(), 600.millis());
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2::Message;
+use objc2_foundation::NSString;
+use objc2_quartz_core::{CAAnimation, CACurrentMediaTime, CALayer, CAMediaTiming};
+
+use crate::animation_builder::{CABasicAnimationBuilder, FillMode, KeyPath};
+use crate::layer_ext::add_animation_at;
+use crate::settings::recorded_multiplier;
+
+/// A step in a [`Timeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step<T> {
+    /// A cue that occupies `duration` on the clock.
+    Cue {
+        /// The scheduled item.
+        item: T,
+        /// How long the item runs.
+        duration: Duration,
+    },
+    /// A pause.
+    Delay(Duration),
+    /// A named marker at the current time.
+    Label(String),
+    /// Steps that run one after another.
+    Sequence(Vec<Step<T>>),
+    /// Steps that start together; the group ends with the longest one.
+    Parallel(Vec<Step<T>>),
+    /// Steps that run in sequence, a number of times in a row.
+    Repeat(u32, Vec<Step<T>>),
+}
+
+/// A sequence of steps laid out on a shared clock.
+///
+/// Top-level steps run one after another. See the [module docs](self) for
+/// an overview.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline<T = LayerAnimation> {
+    steps: Vec<Step<T>>,
+}
+
+impl<T> Timeline<T> {
+    /// Creates an empty timeline.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Appends a cue that occupies `duration`.
+    #[must_use]
+    pub fn cue(mut self, item: T, duration: Duration) -> Self {
+        self.steps.push(Step::Cue { item, duration });
+        self
+    }
+
+    /// Appends a pause.
+    #[must_use]
+    pub fn delay(mut self, duration: Duration) -> Self {
+        self.steps.push(Step::Delay(duration));
+        self
+    }
+
+    /// Marks the current time with a name.
+    ///
+    /// Look the time up with [`Schedule::marker`]. Inside
+    /// [`parallel`](Self::parallel), the marker is at the group's start.
+    #[must_use]
+    pub fn label(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(Step::Label(name.into()));
+        self
+    }
+
+    /// Appends a nested sequence.
+    ///
+    /// Mostly useful inside [`parallel`](Self::parallel), to run a chain of
+    /// steps alongside other steps.
+    #[must_use]
+    pub fn sequence<F>(mut self, configure: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let group = configure(Self::new());
+        self.steps.push(Step::Sequence(group.steps));
+        self
+    }
+
+    /// Appends steps that all start at the same time.
+    ///
+    /// The next step starts when the longest one has finished.
+    #[must_use]
+    pub fn parallel<F>(mut self, configure: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let group = configure(Self::new());
+        self.steps.push(Step::Parallel(group.steps));
+        self
+    }
+
+    /// Appends steps that run in sequence, `times` times in a row.
+    #[must_use]
+    pub fn repeat<F>(mut self, times: u32, configure: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        let group = configure(Self::new());
+        self.steps.push(Step::Repeat(times, group.steps));
+        self
+    }
+
+    /// Returns the steps of this timeline.
+    pub fn steps(&self) -> &[Step<T>] {
+        &self.steps
+    }
+
+    /// Resolves the begin time of every cue.
+    ///
+    /// Times are relative to the start of the timeline. Cues inside
+    /// [`repeat`](Self::repeat) appear once per pass.
+    pub fn schedule(&self) -> Schedule<'_, T> {
+        let mut schedule = Schedule {
+            entries: Vec::new(),
+            markers: Vec::new(),
+            duration: Duration::ZERO,
+        };
+        schedule.duration = schedule.lay_out_sequence(&self.steps, Duration::ZERO);
+        schedule
+    }
+}
+
+impl<T> Default for Timeline<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cue with its resolved begin time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledCue<'a, T> {
+    /// The scheduled item.
+    pub item: &'a T,
+    /// Begin time relative to the start of the timeline.
+    pub begin: Duration,
+    /// How long the item runs.
+    pub duration: Duration,
+}
+
+impl<T> ScheduledCue<'_, T> {
+    /// End time relative to the start of the timeline.
+    pub fn end(&self) -> Duration {
+        self.begin + self.duration
+    }
+}
+
+/// The resolved layout of a [`Timeline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule<'a, T> {
+    entries: Vec<ScheduledCue<'a, T>>,
+    markers: Vec<(String, Duration)>,
+    duration: Duration,
+}
+
+impl<'a, T> Schedule<'a, T> {
+    /// All cues in layout order.
+    pub fn entries(&self) -> &[ScheduledCue<'a, T>] {
+        &self.entries
+    }
+
+    /// Time of the first marker named `name`.
+    pub fn marker(&self, name: &str) -> Option<Duration> {
+        self.markers
+            .iter()
+            .find(|(marker, _)| marker == name)
+            .map(|&(_, time)| time)
+    }
+
+    /// Total length of the timeline.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn lay_out_sequence(&mut self, steps: &'a [Step<T>], start: Duration) -> Duration {
+        steps
+            .iter()
+            .fold(start, |cursor, step| self.lay_out(step, cursor))
+    }
+
+    fn lay_out(&mut self, step: &'a Step<T>, start: Duration) -> Duration {
+        match step {
+            Step::Cue { item, duration } => {
+                self.entries.push(ScheduledCue {
+                    item,
+                    begin: start,
+                    duration: *duration,
+                });
+                start + *duration
+            }
+            Step::Delay(duration) => start + *duration,
+            Step::Label(name) => {
+                self.markers.push((name.clone(), start));
+                start
+            }
+            Step::Sequence(steps) => self.lay_out_sequence(steps, start),
+            Step::Parallel(steps) => steps
+                .iter()
+                .map(|step| self.lay_out(step, start))
+                .fold(start, Duration::max),
+            Step::Repeat(times, steps) => {
+                (0..*times).fold(start, |cursor, _| self.lay_out_sequence(steps, cursor))
+            }
+        }
+    }
+}
+
+/// Length of one run of an animation, including repeats, autoreverse and
+/// speed.
+///
+/// A `repeat_duration` is used when there is no repeat count, as in Core
+/// Animation. Animations that repeat forever count as a single cycle, so
+/// steps after them start once the first cycle is done while the loop keeps
+/// running.
+pub(crate) fn active_duration(
+    duration: f64,
+    repeat_count: f32,
+    repeat_duration: f64,
+    autoreverses: bool,
+    speed: f32,
+) -> Duration {
+    let cycle = if autoreverses {
+        duration * 2.0
+    } else {
+        duration
+    };
+    let active = if repeat_count.is_finite() && repeat_count > 0.0 {
+        cycle * f64::from(repeat_count)
+    } else if repeat_count == 0.0 && repeat_duration.is_finite() && repeat_duration > 0.0 {
+        repeat_duration
+    } else {
+        cycle
+    };
+    let speed = if speed > 0.0 { f64::from(speed) } else { 1.0 };
+    Duration::from_secs_f64((active / speed).max(0.0))
+}
+
+/// Fill mode for a cue starting at `begin`, holding its first frame until
+/// then.
+pub(crate) fn cue_fill_mode(begin: Duration, fill_mode: FillMode) -> FillMode {
+    if begin > Duration::ZERO {
+        fill_mode.holding_start()
+    } else {
+        fill_mode
+    }
+}
+
+// ============================================================================
+// Layer animations
+// ============================================================================
+
+/// An animation bound to the layer it plays on.
+#[derive(Debug, Clone)]
+pub struct LayerAnimation {
+    layer: Retained<CALayer>,
+    name: String,
+    animation: Retained<CAAnimation>,
+}
+
+impl LayerAnimation {
+    /// Creates a cue for `animation` on `layer`, added under the key `name`.
+    pub fn new(layer: &CALayer, name: impl Into<String>, animation: Retained<CAAnimation>) -> Self {
+        Self {
+            layer: layer.retain(),
+            name: name.into(),
+            animation,
+        }
+    }
+
+    /// Length of one run of the animation, read from its timing.
+    ///
+    /// Includes the animation's own delay, which it waits after the cue
+    /// starts.
+    pub fn duration(&self) -> Duration {
+        self.delay()
+            + active_duration(
+                self.animation.duration(),
+                self.animation.repeatCount(),
+                self.animation.repeatDuration(),
+                self.animation.autoreverses(),
+                self.animation.speed(),
+            )
+    }
+
+    /// The global speed the animation was built with.
+    fn multiplier(&self) -> f32 {
+        recorded_multiplier(&self.animation).unwrap_or(1.0)
+    }
+
+    /// The delay set on the animation's builder, stored as a relative
+    /// `beginTime`.
+    fn delay(&self) -> Duration {
+        Duration::from_secs_f64(self.animation.beginTime().max(0.0))
+    }
+}
+
+impl Timeline<LayerAnimation> {
+    /// Appends an animation configured like `.animate()` on layer builders.
+    ///
+    /// The animation's delay, duration, repeats, autoreverse and speed
+    /// decide how long it occupies on the timeline. A delay set on the
+    /// builder is part of the cue: the animation waits that long after the
+    /// cue starts.
+    #[must_use]
+    pub fn animate<F>(self, layer: &CALayer, name: &str, key_path: KeyPath, configure: F) -> Self
+    where
+        F: FnOnce(CABasicAnimationBuilder) -> CABasicAnimationBuilder,
+    {
+        let animation = configure(CABasicAnimationBuilder::new(key_path)).build_animation();
+        self.add(layer, name, animation)
+    }
+
+    /// Appends an already built animation, such as a motion path.
+    #[must_use]
+    pub fn add(self, layer: &CALayer, name: &str, animation: Retained<CAAnimation>) -> Self {
+        let cue = LayerAnimation::new(layer, name, animation);
+        // Lay out at normal speed; `play` stretches the whole schedule.
+        let duration = cue.duration().mul_f32(cue.multiplier());
+        self.cue(cue, duration)
+    }
+
+    /// Starts the timeline now.
+    ///
+    /// Every animation gets an absolute `beginTime` in its layer's time
+    /// space and is added to its layer. Animations that start later also
+    /// fill backwards, so their layers show the first frame while they
+    /// wait instead of snapping to it. Repeated cues are added under
+    /// `name`, `name#1`, `name#2`, … so passes don't replace each other.
+    ///
+    /// Returns the resolved schedule, e.g. to look up marker times. Its
+    /// times are at normal speed; begin times are stretched by the global
+    /// [`AnimationSettings::speed`](crate::settings::AnimationSettings::speed)
+    /// when playing, delays included.
+    pub fn play(&self) -> Schedule<'_, LayerAnimation> {
+        let schedule = self.schedule();
+        let now = CACurrentMediaTime();
+        let mut passes: HashMap<*const LayerAnimation, u32> = HashMap::new();
+
+        for entry in schedule.entries() {
+            let cue = entry.item;
+            let pass = passes.entry(cue as *const _).or_insert(0);
+            let key = if *pass == 0 {
+                cue.name.clone()
+            } else {
+                format!("{}#{}", cue.name, pass)
+            };
+            *pass += 1;
+
+            let delay = cue.delay();
+            let own_fill_mode = cue.animation.fillMode();
+            let fill_mode =
+                FillMode::from_ca_fill_mode(&own_fill_mode).unwrap_or(FillMode::Forwards);
+            let begin = entry.begin.div_f32(cue.multiplier()) + delay;
+            cue.animation.setBeginTime(begin.as_secs_f64());
+            cue.animation
+                .setFillMode(cue_fill_mode(begin, fill_mode).to_ca_fill_mode());
+            // Core Animation copies the animation, so reusing it per pass is
+            // fine once its own timing is back in place.
+            add_animation_at(&cue.layer, &cue.animation, &NSString::from_str(&key), now);
+            cue.animation.setBeginTime(delay.as_secs_f64());
+            cue.animation.setFillMode(&own_fill_mode);
+        }
+
+        schedule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn begins<'a>(schedule: &Schedule<'a, &'a str>) -> Vec<(&'a str, Duration)> {
+        schedule
+            .entries()
+            .iter()
+            .map(|entry| (*entry.item, entry.begin))
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_timeline() {
+        let timeline: Timeline<&str> = Timeline::new();
+        let schedule = timeline.schedule();
+        assert!(schedule.entries().is_empty());
+        assert_eq!(schedule.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_sequence_and_delay() {
+        let timeline = Timeline::new()
+            .cue("a", ms(200))
+            .delay(ms(50))
+            .cue("b", ms(100));
+        let schedule = timeline.schedule();
+        assert_eq!(begins(&schedule), vec![("a", ms(0)), ("b", ms(250))]);
+        assert_eq!(schedule.duration(), ms(350));
+        assert_eq!(schedule.entries()[1].end(), ms(350));
+    }
+
+    #[test]
+    fn test_parallel_ends_with_longest() {
+        let timeline = Timeline::new()
+            .cue("a", ms(200))
+            .parallel(|p| p.cue("b", ms(100)).cue("c", ms(300)))
+            .cue("d", ms(100));
+        let schedule = timeline.schedule();
+        assert_eq!(
+            begins(&schedule),
+            vec![("a", ms(0)), ("b", ms(200)), ("c", ms(200)), ("d", ms(500))]
+        );
+        assert_eq!(schedule.duration(), ms(600));
+    }
+
+    #[test]
+    fn test_sequence_inside_parallel() {
+        let timeline = Timeline::new().parallel(|p| {
+            p.cue("a", ms(300))
+                .sequence(|s| s.cue("b", ms(100)).delay(ms(100)).cue("c", ms(200)))
+        });
+        let schedule = timeline.schedule();
+        assert_eq!(
+            begins(&schedule),
+            vec![("a", ms(0)), ("b", ms(0)), ("c", ms(200))]
+        );
+        assert_eq!(schedule.duration(), ms(400));
+    }
+
+    #[test]
+    fn test_repeat() {
+        let timeline = Timeline::new()
+            .repeat(3, |r| r.cue("a", ms(100)).delay(ms(50)))
+            .cue("b", ms(10));
+        let schedule = timeline.schedule();
+        assert_eq!(
+            begins(&schedule),
+            vec![("a", ms(0)), ("a", ms(150)), ("a", ms(300)), ("b", ms(450))]
+        );
+        assert_eq!(schedule.duration(), ms(460));
+    }
+
+    #[test]
+    fn test_repeat_zero_times() {
+        let timeline = Timeline::new().repeat(0, |r| r.cue("a", ms(100)));
+        let schedule = timeline.schedule();
+        assert!(schedule.entries().is_empty());
+        assert_eq!(schedule.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_labels() {
+        let timeline = Timeline::new()
+            .label("start")
+            .cue("a", ms(200))
+            .label("middle")
+            .parallel(|p| p.label("group").cue("b", ms(100)))
+            .label("end");
+        let schedule = timeline.schedule();
+        assert_eq!(schedule.marker("start"), Some(ms(0)));
+        assert_eq!(schedule.marker("middle"), Some(ms(200)));
+        assert_eq!(schedule.marker("group"), Some(ms(200)));
+        assert_eq!(schedule.marker("end"), Some(ms(300)));
+        assert_eq!(schedule.marker("missing"), None);
+    }
+
+    #[test]
+    fn test_later_cues_fill_backwards() {
+        let timeline = Timeline::new()
+            .cue("a", ms(200))
+            .cue("b", ms(100))
+            .parallel(|p| p.cue("c", ms(100)));
+        let fills: Vec<_> = timeline
+            .schedule()
+            .entries()
+            .iter()
+            .map(|entry| cue_fill_mode(entry.begin, FillMode::Forwards))
+            .collect();
+        assert_eq!(fills, [FillMode::Forwards, FillMode::Both, FillMode::Both]);
+
+        assert_eq!(cue_fill_mode(ms(0), FillMode::Removed), FillMode::Removed);
+        assert_eq!(
+            cue_fill_mode(ms(50), FillMode::Removed),
+            FillMode::Backwards
+        );
+        assert_eq!(cue_fill_mode(ms(50), FillMode::Both), FillMode::Both);
+    }
+
+    #[test]
+    fn test_active_duration() {
+        assert_eq!(active_duration(0.5, 1.0, 0.0, false, 1.0), ms(500));
+        assert_eq!(active_duration(0.5, 1.0, 0.0, true, 1.0), ms(1000));
+        assert_eq!(active_duration(0.5, 3.0, 0.0, true, 1.0), ms(3000));
+        assert_eq!(active_duration(0.5, 2.5, 0.0, false, 1.0), ms(1250));
+        assert_eq!(active_duration(0.5, 1.0, 0.0, false, 2.0), ms(250));
+        // Zero means "once" in Core Animation; forever counts one cycle.
+        assert_eq!(active_duration(0.5, 0.0, 0.0, false, 1.0), ms(500));
+        assert_eq!(
+            active_duration(0.5, f32::INFINITY, 0.0, true, 1.0),
+            ms(1000)
+        );
+        // A repeat duration only counts without a repeat count.
+        assert_eq!(active_duration(0.5, 0.0, 1.75, true, 1.0), ms(1750));
+        assert_eq!(active_duration(0.5, 0.0, 1.75, true, 0.5), ms(3500));
+        assert_eq!(active_duration(0.5, 2.0, 1.75, false, 1.0), ms(1000));
+    }
+}
@@ -0,0 +1,159 @@
+//! Control over implicit animations using `CATransaction`.
+//!
+//! Changing an animatable property of a layer that is already on screen
+//! animates the change implicitly, with Core Animation's default timing.
+//! [`transaction`] groups such changes and decides how they animate:
+//!
+//! ```ignore
+//! transaction(
+//!     |t| t.duration(600.millis()).easing(Easing::Out),
+//!     || {
+//!         layer.setPosition(CGPoint::new(300.0, 200.0));
+//!         layer.setOpacity(0.5);
+//!     },
+//! );
+//!
+//! // Jump without animating
+//! transaction(|t| t.disable_actions(true), || layer.setOpacity(1.0));
+//! ```
+//!
+//! Transactions nest: an inner transaction starts from the settings of the
+//! outer one and overrides only what it sets itself.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use block2::RcBlock;
+use objc2_quartz_core::CATransaction;
+
+use crate::animation_builder::Easing;
+
+/// Settings for a group of implicit animations.
+///
+/// Usually configured through [`transaction`]. Unset options are inherited
+/// from the enclosing transaction, or Core Animation's defaults.
+#[derive(Default)]
+pub struct Transaction {
+    duration: Option<Duration>,
+    easing: Option<Easing>,
+    disable_actions: Option<bool>,
+    on_complete: Option<Box<dyn FnOnce()>>,
+}
+
+impl Transaction {
+    /// Creates a transaction that changes no settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the duration of the implicit animations.
+    #[must_use]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Sets the easing curve of the implicit animations.
+    #[must_use]
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = Some(easing);
+        self
+    }
+
+    /// Turns implicit animations off, so changes apply immediately.
+    #[must_use]
+    pub fn disable_actions(mut self, disable: bool) -> Self {
+        self.disable_actions = Some(disable);
+        self
+    }
+
+    /// Sets a closure to run once all animations of the transaction are done.
+    ///
+    /// Runs right away if the transaction added no animations.
+    #[must_use]
+    pub fn on_complete(mut self, callback: impl FnOnce() + 'static) -> Self {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Applies the settings, runs `changes` and commits.
+    ///
+    /// The transaction is committed even if `changes` panics.
+    pub fn run<R>(self, changes: impl FnOnce() -> R) -> R {
+        CATransaction::begin();
+        let _commit = CommitGuard;
+
+        if let Some(duration) = self.duration {
+            CATransaction::setAnimationDuration(duration.as_secs_f64());
+        }
+        if let Some(easing) = self.easing {
+            CATransaction::setAnimationTimingFunction(Some(&easing.to_timing_function()));
+        }
+        if let Some(disable) = self.disable_actions {
+            CATransaction::setDisableActions(disable);
+        }
+        if let Some(callback) = self.on_complete {
+            let callback = Cell::new(Some(callback));
+            let block = RcBlock::new(move || {
+                if let Some(callback) = callback.take() {
+                    callback();
+                }
+            });
+            // SAFETY: The block is retained by the transaction and has no
+            // captured references.
+            unsafe { CATransaction::setCompletionBlock(Some(&block)) };
+        }
+
+        changes()
+    }
+
+    /// Commits the implicit transaction of the current thread now, instead
+    /// of at the end of the run loop pass.
+    ///
+    /// Useful when changes are made off the main thread or outside a run
+    /// loop, where nothing else would commit them.
+    pub fn flush() {
+        CATransaction::flush();
+    }
+}
+
+/// Runs `changes` inside a transaction configured by `configure`.
+///
+/// See the [module docs](self) for examples.
+pub fn transaction<R>(
+    configure: impl FnOnce(Transaction) -> Transaction,
+    changes: impl FnOnce() -> R,
+) -> R {
+    configure(Transaction::new()).run(changes)
+}
+
+/// Commits the current transaction when dropped.
+struct CommitGuard;
+
+impl Drop for CommitGuard {
+    fn drop(&mut self) {
+        CATransaction::commit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_chaining() {
+        let t = Transaction::new()
+            .duration(Duration::from_millis(600))
+            .easing(Easing::Out)
+            .disable_actions(true)
+            .on_complete(|| {});
+        assert_eq!(t.duration, Some(Duration::from_millis(600)));
+        assert_eq!(t.easing, Some(Easing::Out));
+        assert_eq!(t.disable_actions, Some(true));
+        assert!(t.on_complete.is_some());
+
+        let t = Transaction::new();
+        assert!(t.duration.is_none() && t.easing.is_none() && t.disable_actions.is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Reproducible randomness for scenes with many similar layers.
+//!
+//! Dozens of identical dots pulsing in lockstep look mechanical. A
+//! [`Variation`] jitters durations, delays, amplitudes and colors within
+//! ranges, and the same seed always gives the same scene, so screenshots
+//! and tests stay stable.
+//!
+//! ```ignore
+//! let mut vary = Variation::new(42);
+//! let mut window = WindowBuilder::new().size(640.0, 480.0);
+//!
+//! for i in 0..40 {
+//!     window = window.layer(&format!("dot{i}"), |s| {
+//!         s.circle(vary.uniform(12.0, 20.0))
+//!             .position(CGPoint::new(vary.uniform(40.0, 600.0), vary.uniform(40.0, 440.0)))
+//!             .fill_color(vary.color(Color::CYAN, 0.1))
+//!             .animate("pulse", KeyPath::TransformScale, |a| {
+//!                 a.values(1.0, vary.around(1.2, 0.1))
+//!                     .duration(vary.duration(800.millis(), 0.2))
+//!                     .delay(vary.duration_between(0.millis(), 300.millis()))
+//!                     .autoreverses()
+//!                     .repeat(Repeat::Forever)
+//!             })
+//!     });
+//! }
+//! ```
+//!
+//! Every value drawn moves the sequence along, so drawing one more value
+//! for one layer changes all the layers after it. When that matters, give
+//! each layer its own stream with [`fork`](Variation::fork).
+
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use crate::animation_builder::SampleValue;
+use crate::color::Color;
+use crate::random::{mix, Rng};
+
+/// How many standard deviations [`Variation::gaussian`] may stray.
+const GAUSSIAN_LIMIT: f64 = 3.0;
+
+/// A seeded source of random variation for builder parameters.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut a = Variation::new(7);
+/// let mut b = Variation::new(7);
+/// assert_eq!(a.uniform(0.0, 1.0), b.uniform(0.0, 1.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Variation {
+    seed: u64,
+    rng: Rng,
+}
+
+impl Variation {
+    /// Creates a variation from a seed.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Returns an independent variation for item `index`.
+    ///
+    /// The result only depends on the seed and `index`, not on how many
+    /// values were drawn before, so items keep their look when others
+    /// change.
+    ///
+    /// ```ignore
+    /// let vary = Variation::new(42);
+    /// for (i, dot) in dots.iter().enumerate() {
+    ///     let mut vary = vary.fork(i as u64);
+    ///     dot.setOpacity(vary.uniform(0.5, 1.0) as f32);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn fork(&self, index: u64) -> Self {
+        Self::new(mix(self.seed, index))
+    }
+
+    /// Returns a value between `min` and `max`, all equally likely.
+    pub fn uniform(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.rng.next_f64()
+    }
+
+    /// Returns a value from a normal distribution around `mean`.
+    ///
+    /// Most values land close to the mean; about two thirds within
+    /// `std_dev` of it. Values are kept within three standard deviations,
+    /// so a rare outlier never breaks the scene.
+    pub fn gaussian(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // Box-Muller; `1 - x` keeps the logarithm away from zero.
+        let radius = (-2.0 * (1.0 - self.rng.next_f64()).ln()).sqrt();
+        let normal = radius * (TAU * self.rng.next_f64()).cos();
+        mean + normal.clamp(-GAUSSIAN_LIMIT, GAUSSIAN_LIMIT) * std_dev
+    }
+
+    /// Returns `value` give or take the fraction `spread` of it.
+    ///
+    /// `around(100.0, 0.1)` is between 90 and 110.
+    pub fn around(&mut self, value: f64, spread: f64) -> f64 {
+        value * self.uniform(1.0 - spread, 1.0 + spread)
+    }
+
+    /// Returns `base` give or take the fraction `spread` of it.
+    ///
+    /// `duration(1.seconds(), 0.2)` is between 0.8 and 1.2 seconds.
+    pub fn duration(&mut self, base: Duration, spread: f64) -> Duration {
+        let secs = self.around(base.as_secs_f64(), spread);
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+
+    /// Returns a duration between `min` and `max`, all equally likely.
+    pub fn duration_between(&mut self, min: Duration, max: Duration) -> Duration {
+        let secs = self.uniform(min.as_secs_f64(), max.as_secs_f64());
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+
+    /// Returns `base` with each of red, green and blue moved by up to
+    /// `spread`.
+    ///
+    /// Components stay between 0.0 and 1.0, and alpha is kept.
+    pub fn color(&mut self, base: Color, spread: f64) -> Color {
+        let mut channel = |value: f64| (value + self.uniform(-spread, spread)).clamp(0.0, 1.0);
+        Color::rgba(channel(base.r), channel(base.g), channel(base.b), base.a)
+    }
+
+    /// Returns a color somewhere between `a` and `b`.
+    pub fn color_between(&mut self, a: Color, b: Color) -> Color {
+        a.lerp(b, self.rng.next_f64())
+    }
+
+    /// Returns `true` with the given probability, from 0.0 to 1.0.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.rng.next_f64() < probability
+    }
+
+    /// Returns a random item of `items`, or `None` if it is empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        let index = (self.rng.next_f64() * items.len() as f64) as usize;
+        items.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let draw = |seed| {
+            let mut vary = Variation::new(seed);
+            (
+                vary.uniform(0.0, 10.0),
+                vary.gaussian(5.0, 1.0),
+                vary.duration(Duration::from_secs(1), 0.2),
+                vary.color(Color::CYAN, 0.1),
+            )
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut vary = Variation::new(1);
+        for _ in 0..1000 {
+            assert!((2.0..5.0).contains(&vary.uniform(2.0, 5.0)));
+            assert!((90.0..=110.0).contains(&vary.around(100.0, 0.1)));
+            let duration = vary.duration(Duration::from_millis(500), 0.2);
+            assert!(duration >= Duration::from_millis(400));
+            assert!(duration <= Duration::from_millis(600));
+            let delay = vary.duration_between(Duration::ZERO, Duration::from_millis(300));
+            assert!(delay <= Duration::from_millis(300));
+
+            let color = vary.color(Color::rgba(0.05, 0.5, 1.0, 0.8), 0.1);
+            assert!((0.0..=0.15).contains(&color.r));
+            assert!((0.4..=0.6).contains(&color.g));
+            assert!((0.9..=1.0).contains(&color.b));
+            assert_eq!(color.a, 0.8);
+        }
+    }
+
+    #[test]
+    fn test_gaussian() {
+        let mut vary = Variation::new(9);
+        let values: Vec<f64> = (0..5000).map(|_| vary.gaussian(10.0, 2.0)).collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        assert!((mean - 10.0).abs() < 0.1, "mean {mean}");
+        assert!(
+            (variance.sqrt() - 2.0).abs() < 0.1,
+            "std dev {}",
+            variance.sqrt()
+        );
+        assert!(values.iter().all(|v| (4.0..=16.0).contains(v)));
+    }
+
+    #[test]
+    fn test_fork_is_independent_of_draws() {
+        let vary = Variation::new(5);
+        let mut used = vary.clone();
+        used.uniform(0.0, 1.0);
+        assert_eq!(
+            vary.fork(3).uniform(0.0, 1.0),
+            used.fork(3).uniform(0.0, 1.0)
+        );
+        assert_ne!(
+            vary.fork(3).uniform(0.0, 1.0),
+            vary.fork(4).uniform(0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_pick_and_chance() {
+        let mut vary = Variation::new(2);
+        let items = [1, 2, 3];
+        let mut seen = [false; 3];
+        for _ in 0..100 {
+            seen[*vary.pick(&items).unwrap() - 1] = true;
+        }
+        assert_eq!(seen, [true; 3]);
+        assert_eq!(vary.pick::<i32>(&[]), None);
+
+        assert!(!vary.chance(0.0));
+        assert!(vary.chance(1.0));
+    }
+}
@@ -5,6 +5,8 @@
 use std::time::Duration;
 
 use crate::color::Color;
+use crate::controller::AnimationController;
+use crate::settings::AnimationSettings;
 use crate::shape_layer_builder::CAShapeLayerBuilder;
 use crate::text_layer_builder::CATextLayerBuilder;
 use objc2::rc::Retained;
@@ -16,7 +18,7 @@ use objc2_app_kit::{
 use objc2_core_foundation::{kCFRunLoopDefaultMode, CFRunLoop, CFTimeInterval};
 use objc2_core_graphics::CGColor;
 use objc2_foundation::{NSPoint, NSRect, NSSize, NSString};
-use objc2_quartz_core::{CALayer, CAShapeLayer, CATextLayer};
+use objc2_quartz_core::CALayer;
 
 /// Specifies which screen to use for window placement.
 #[derive(Clone, Debug, Default)]
@@ -129,8 +131,8 @@ pub struct WindowBuilder {
     corner_radius: Option<f64>,
     level: Option<WindowLevel>,
     border_color: Option<Color>,
-    layers: Vec<(String, Retained<CAShapeLayer>)>,
-    text_layers: Vec<(String, Retained<CATextLayer>)>,
+    layers: Vec<(String, CAShapeLayerBuilder)>,
+    text_layers: Vec<(String, CATextLayerBuilder)>,
     non_activating: bool,
     ignores_mouse: bool,
 }
@@ -417,7 +419,8 @@ impl WindowBuilder {
     /// Add a shape layer to the window.
     ///
     /// The closure receives a [`CAShapeLayerBuilder`] for configuration.
-    /// The built layer will be added to [`Window::container()`] when [`build()`](Self::build) is called.
+    /// The layer is built into [`Window::container()`] when [`build()`](Self::build) is called,
+    /// before its animations are added.
     ///
     /// This allows configuring shape layers inline in the fluent API,
     /// creating a fully fluent flow from window to layers to animations.
@@ -480,15 +483,15 @@ impl WindowBuilder {
     {
         let builder = CAShapeLayerBuilder::new();
         let configured = configure(builder);
-        let layer = configured.build();
-        self.layers.push((name.to_string(), layer));
+        self.layers.push((name.to_string(), configured));
         self
     }
 
     /// Add a text layer to the window.
     ///
     /// The closure receives a [`CATextLayerBuilder`] for configuration.
-    /// The built layer will be added to [`Window::container()`] when [`build()`](Self::build) is called.
+    /// The layer is built into [`Window::container()`] when [`build()`](Self::build) is called,
+    /// before its animations are added.
     ///
     /// # Arguments
     ///
@@ -543,8 +546,7 @@ impl WindowBuilder {
     {
         let builder = CATextLayerBuilder::new();
         let configured = configure(builder);
-        let layer = configured.build();
-        self.text_layers.push((name.to_string(), layer));
+        self.text_layers.push((name.to_string(), configured));
         self
     }
 
@@ -666,16 +668,18 @@ impl WindowBuilder {
             container.setBorderWidth(1.0);
         }
 
+        // Slow motion applies to everything in the window, once.
+        AnimationSettings::apply_speed(&container);
+
         root_layer.addSublayer(&container);
 
-        // Add all configured shape layers to the container
-        for (_name, layer) in &self.layers {
-            container.addSublayer(layer);
+        // Build the configured layers into the container, so their
+        // animations start on the container's clock
+        for (_name, layer) in self.layers {
+            layer.build_in(Some(&container));
         }
-
-        // Add all configured text layers to the container
-        for (_name, layer) in &self.text_layers {
-            container.addSublayer(layer);
+        for (_name, layer) in self.text_layers {
+            layer.build_in(Some(&container));
         }
 
         // Apply mouse event handling
@@ -732,6 +736,14 @@ impl Window {
         &self.container
     }
 
+    /// Get a controller for all animations in the window.
+    ///
+    /// Pausing, seeking or changing the rate affects every layer added to
+    /// the container.
+    pub fn animation_controller(&self) -> AnimationController {
+        AnimationController::new(&self.container)
+    }
+
     /// Get the window size in points.
     pub fn size(&self) -> (f64, f64) {
         self.size